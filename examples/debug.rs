@@ -3,10 +3,11 @@ extern crate serde_derive;
 extern crate serde_json;
 extern crate strum;
 
-use libswe_sys::constants::EPHEMERIS_PATH;
+use libswe_sys::constants::{ CalculationFlags, EPHEMERIS_PATH };
+use libswe_sys::SweError;
 use strum::IntoEnumIterator;
 
-use libswe_sys::sweconst::{ Angle, Bodies, Calendar, House, Object, ObjectType, OptionalFlag };
+use libswe_sys::sweconst::{ Angle, Bodies, Calendar, House, HouseSystem, Object, ObjectType };
 use libswe_sys::swerust::{
     handler_swe02,
     handler_swe03,
@@ -33,13 +34,13 @@ pub struct Data {
     lng: f64,
 }
 
-fn main() {
+fn main() -> Result<(), SweError> {
     println!("Swissephem C -> Rust");
     
-    handler_swe02::set_ephe_path(&EPHEMERIS_PATH);
+    handler_swe02::set_ephe_path(EPHEMERIS_PATH)?;
     println!("Set the path of ephemeris to: {}", &EPHEMERIS_PATH);
-    println!("Version swephem: {}", handler_swe02::version());
-    println!("Get path of library: {}", handler_swe02::get_library_path());
+    println!("Version swephem: {}", handler_swe02::version()?);
+    println!("Get path of library: {}", handler_swe02::get_library_path()?);
 
     const PATH: &str = "examples/data.json";
     let mut s = String::new();
@@ -62,15 +63,15 @@ fn main() {
     let mut calc: handler_swe03::CalcUtResult;
     for bodies in Bodies::iter() {
         if
-            bodies.clone().object_type() == ObjectType::PlanetOrStar ||
-            bodies.clone().object_type() == ObjectType::Fiction
+            bodies.object_type() == ObjectType::PlanetOrStar ||
+            bodies.object_type() == ObjectType::Fiction
         {
-            calc = handler_swe03::calc_ut(julday, bodies.clone(), OptionalFlag::Speed as i32);
+            calc = handler_swe03::calc_ut(julday, bodies, CalculationFlags::SPEED_PRECISION)?;
             object.push(
                 Object::new(
-                    bodies.clone(),
-                    bodies.clone().into(),
-                    bodies.clone().object_type(),
+                    bodies,
+                    bodies.into(),
+                    bodies.object_type(),
                     calc.longitude,
                     calc.latitude,
                     calc.speed_longitude
@@ -86,12 +87,12 @@ fn main() {
     let pheno_ut: handler_swe07::PhenoUtResult = handler_swe07::pheno_ut(
         julday,
         Bodies::Sun,
-        OptionalFlag::Speed as i32
-    );
+        CalculationFlags::SPEED_PRECISION
+    )?;
     println!("PhenoUt: {:?}", pheno_ut);
 
-    // let hsys = HouseSystem::Placidus;
-    let name = handler_swe14::house_name('P');
+    let hsys = HouseSystem::Placidus;
+    let name = hsys.name()?;
     println!("Hsys: {}", name);
 
     let utc_time_zone: handler_swe08::UtcTimeZoneResult = handler_swe08::utc_time_zone(
@@ -119,18 +120,18 @@ fn main() {
         utc_time_zone.min[1],
         utc_time_zone.sec[1],*/
         Calendar::Gregorian
-    );
+    )?;
     println!("utc_to_jd: {:?}", utc_to_jd);
 
     // Whole signs
-    let result_w = handler_swe14::houses(utc_to_jd.julian_day_ut, data.lat, data.lng, 'W');
+    let result_w = handler_swe14::houses(utc_to_jd.julian_day_ut, data.lat, data.lng, HouseSystem::WholeSign)?;
     //println!("House object: {:?}", result);
     let mut house2: Vec<House> = Vec::new();
     for (i, res) in result_w.clone().cusps.iter().enumerate() {
         if i > 0 {
             // No angle calculation when Nothing
             let angle = Angle::Nothing;
-            house2.push(House::new(i as i32, res.clone(), angle));
+            house2.push(House::new(i as i32, *res, angle));
             if i + 1 > 12 {
                 break;
             }
@@ -143,12 +144,11 @@ fn main() {
     println!("House (wohle signs): {:?}", result_w.clone());
 
     // Wohle Signs
-    let result = handler_swe14::houses(utc_to_jd.julian_day_ut, data.lat, data.lng, 'P');
+    let result = handler_swe14::houses(utc_to_jd.julian_day_ut, data.lat, data.lng, hsys)?;
     //println!("House object: {:?}", result);
     let mut house: Vec<House> = Vec::new();
     for (i, res) in result.clone().cusps.iter().enumerate() {
         if i > 0 {
-            let angle;
             /*
             if result.clone().ascmc[0] == res.clone() {
                 angle = Angle::Asc;
@@ -164,14 +164,14 @@ fn main() {
             }*/
             // This is tested with Placidus only
             // the line above ascmc[?] don't work for Desc and Mc
-            angle = match i {
+            let angle = match i {
                 1 => Angle::Asc,
                 4 => Angle::Fc,
                 7 => Angle::Desc,
                 10 => Angle::Mc,
                 _ => Angle::Nothing,
             };
-            house.push(House::new(i as i32, res.clone(), angle));
+            house.push(House::new(i as i32, *res, angle));
             if i + 1 > 12 {
                 break;
             }
@@ -188,11 +188,12 @@ fn main() {
         julday,
         data.lat,
         data.lng,
-        'P',
-        OptionalFlag::Speed as i32,
-    );
+        hsys,
+        CalculationFlags::SPEED_PRECISION,
+    )?;
     println!("Fortuna Part: {}", calcfp.longitude);
 
     println!("Exit and free memory swephem");
     handler_swe02::close();
+    Ok(())
 }
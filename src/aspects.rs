@@ -0,0 +1,342 @@
+use crate::sweconst::{Angle, Aspects, AspectsFilter, Bodies, House, Object};
+use crate::swerust::handler_swe17::degnorm;
+use strum::IntoEnumIterator;
+
+/*
+ * Aspects
+ *
+ * Aspect grid of a chart: every pair of objects, and every object with the
+ * angles of the chart (Asc, Fc, Desc, Mc), is checked against the aspects of
+ * an AspectsFilter. A pair gets the aspect with the smallest orb, if any is
+ * within the allowed orb.
+ *
+ * The allowed orbs default to the ones of Aspects::angle and can be changed
+ * per aspect and per body with an OrbTable. When an orb is set for a body, the
+ * larger orb of the two bodies of a pair is used.
+ */
+
+/// Point of the chart taking part in an aspect
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AspectPoint {
+    Body(Bodies),
+    Angle(Angle),
+}
+
+/// Whether the orb of an aspect is decreasing or increasing
+#[derive(Debug, Clone, Copy, PartialEq, Display)]
+pub enum AspectMotion {
+    Applying,
+    Separating,
+    Stationary, // no relative motion
+}
+
+/// Orbs allowed for each aspect, with overrides per body
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbTable {
+    aspect_orbs: Vec<f64>, // indexed by Aspects as usize
+    body_orbs: Vec<(Bodies, Aspects, f64)>,
+}
+
+impl Default for OrbTable {
+    /// Orbs of Aspects::angle
+    fn default() -> Self {
+        OrbTable {
+            aspect_orbs: Aspects::iter().map(|a| a.angle().1 as f64).collect(),
+            body_orbs: Vec::new(),
+        }
+    }
+}
+
+impl OrbTable {
+    /// Orb for an aspect
+    pub fn with_aspect_orb(mut self, aspect: Aspects, orb: f64) -> Self {
+        self.aspect_orbs[aspect as usize] = orb;
+        self
+    }
+
+    /// Orb for an aspect involving a given body
+    pub fn with_body_orb(
+        mut self,
+        body: Bodies,
+        aspect: Aspects,
+        orb: f64,
+    ) -> Self {
+        self.body_orbs.retain(|&(b, a, _)| b != body || a != aspect);
+        self.body_orbs.push((body, aspect, orb));
+        self
+    }
+
+    /// Orb allowed for an aspect between two points
+    pub fn orb(
+        &self,
+        aspect: Aspects,
+        first: AspectPoint,
+        second: AspectPoint,
+    ) -> f64 {
+        let body_orb = |point: AspectPoint| match point {
+            AspectPoint::Body(body) => self
+                .body_orbs
+                .iter()
+                .find(|&&(b, a, _)| b == body && a == aspect)
+                .map(|&(_, _, orb)| orb),
+            AspectPoint::Angle(_) => None,
+        };
+        match (body_orb(first), body_orb(second)) {
+            (Some(a), Some(b)) => a.max(b),
+            (Some(a), None) | (None, Some(a)) => a,
+            (None, None) => self.aspect_orbs[aspect as usize],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AspectEntry {
+    pub first: AspectPoint,
+    pub second: AspectPoint,
+    pub aspect: Aspects,
+    pub separation: f64, // angular distance of the points, 0 to 180
+    pub orb: f64,        // distance from the exact aspect
+    pub max_orb: f64,
+    pub exactness: f64, // 1 when exact, 0 at the edge of the orb
+    pub motion: AspectMotion,
+    pub major: bool,
+}
+
+/// Aspects found between the points of a chart
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AspectGrid {
+    pub entries: Vec<AspectEntry>,
+}
+
+impl AspectGrid {
+    /// Aspect between two points, in either order
+    pub fn get(
+        &self,
+        first: AspectPoint,
+        second: AspectPoint,
+    ) -> Option<&AspectEntry> {
+        self.entries.iter().find(|e| {
+            (e.first == first && e.second == second)
+                || (e.first == second && e.second == first)
+        })
+    }
+
+    /// Aspects involving a point
+    pub fn of(&self, point: AspectPoint) -> Vec<&AspectEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.first == point || e.second == point)
+            .collect()
+    }
+}
+
+struct Point {
+    point: AspectPoint,
+    longitude: f64,
+    speed_longitude: f64,
+}
+
+/// Aspect grid of `objects` and of the angles among `houses`
+pub fn aspects(
+    objects: &[Object],
+    houses: Option<&[House]>,
+    filter: AspectsFilter,
+    orbs: &OrbTable,
+) -> AspectGrid {
+    let bodies: Vec<Point> = objects
+        .iter()
+        .map(|o| Point {
+            point: AspectPoint::Body(o.object_enum),
+            longitude: o.longitude,
+            speed_longitude: o.speed_longitude,
+        })
+        .collect();
+    let angles: Vec<Point> = houses
+        .unwrap_or(&[])
+        .iter()
+        .filter(|h| h.angle != Angle::Nothing)
+        .map(|h| Point {
+            point: AspectPoint::Angle(h.angle),
+            longitude: h.longitude,
+            speed_longitude: 0.0,
+        })
+        .collect();
+    let candidates = filter.vec_aspects();
+
+    let mut entries = Vec::new();
+    for (i, first) in bodies.iter().enumerate() {
+        for second in bodies[i + 1..].iter().chain(angles.iter()) {
+            if let Some(entry) =
+                aspect_between(first, second, &candidates, orbs)
+            {
+                entries.push(entry);
+            }
+        }
+    }
+    AspectGrid { entries }
+}
+
+fn aspect_between(
+    first: &Point,
+    second: &Point,
+    candidates: &[Aspects],
+    orbs: &OrbTable,
+) -> Option<AspectEntry> {
+    let mut d = degnorm(second.longitude - first.longitude);
+    if d > 180.0 {
+        d -= 360.0;
+    }
+    let separation = d.abs();
+    let (aspect, orb, max_orb) = candidates
+        .iter()
+        .map(|&aspect| {
+            let orb = (separation - aspect.angle().0 as f64).abs();
+            (aspect, orb, orbs.orb(aspect, first.point, second.point))
+        })
+        .filter(|&(_, orb, max_orb)| orb <= max_orb)
+        .min_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    // rate of change of the orb
+    let relative_speed = second.speed_longitude - first.speed_longitude;
+    let towards_exact = if separation >= aspect.angle().0 as f64 {
+        1.0
+    } else {
+        -1.0
+    };
+    let rate = towards_exact * d.signum() * relative_speed;
+    let motion = if relative_speed == 0.0 {
+        AspectMotion::Stationary
+    } else if rate < 0.0 {
+        AspectMotion::Applying
+    } else {
+        AspectMotion::Separating
+    };
+
+    Some(AspectEntry {
+        first: first.point,
+        second: second.point,
+        aspect,
+        separation,
+        orb,
+        max_orb,
+        exactness: if max_orb > 0.0 {
+            1.0 - orb / max_orb
+        } else {
+            1.0
+        },
+        motion,
+        major: aspect.maj(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn object(body: Bodies, longitude: f64, speed_longitude: f64) -> Object {
+        Object::new(
+            body,
+            &body.to_string(),
+            body.object_type(),
+            longitude,
+            0.0,
+            speed_longitude,
+        )
+    }
+
+    fn chart() -> Vec<Object> {
+        vec![
+            object(Bodies::Sun, 10.0, 1.0),
+            object(Bodies::Moon, 12.5, 13.0),
+            object(Bodies::Mars, 190.5, -0.3),
+        ]
+    }
+
+    #[test]
+    fn test_conjunction_and_opposition() {
+        let grid = aspects(
+            &chart(),
+            None,
+            AspectsFilter::AllAspects,
+            &OrbTable::default(),
+        );
+        let sun = AspectPoint::Body(Bodies::Sun);
+        let moon = AspectPoint::Body(Bodies::Moon);
+        let mars = AspectPoint::Body(Bodies::Mars);
+
+        let conjunction = grid.get(moon, sun).unwrap();
+        assert_eq!(conjunction.aspect, Aspects::Conjunction);
+        assert_approx_eq!(conjunction.orb, 2.5);
+        assert_approx_eq!(conjunction.exactness, 0.75);
+        // the faster moon is ahead of the sun
+        assert_eq!(conjunction.motion, AspectMotion::Separating);
+        assert!(conjunction.major);
+
+        let opposition = grid.get(sun, mars).unwrap();
+        assert_eq!(opposition.aspect, Aspects::Opposition);
+        assert_approx_eq!(opposition.separation, 179.5);
+        assert_approx_eq!(opposition.orb, 0.5);
+        assert_eq!(opposition.motion, AspectMotion::Applying);
+
+        let moon_mars = grid.get(moon, mars).unwrap();
+        assert_eq!(moon_mars.aspect, Aspects::Opposition);
+        assert_eq!(moon_mars.motion, AspectMotion::Separating);
+        assert_eq!(grid.entries.len(), 3);
+    }
+
+    #[test]
+    fn test_orb_table() {
+        let sun = AspectPoint::Body(Bodies::Sun);
+        let moon = AspectPoint::Body(Bodies::Moon);
+
+        let orbs =
+            OrbTable::default().with_aspect_orb(Aspects::Conjunction, 2.0);
+        let grid = aspects(&chart(), None, AspectsFilter::AllAspects, &orbs);
+        assert!(grid.get(sun, moon).is_none());
+
+        let orbs = orbs.with_body_orb(Bodies::Moon, Aspects::Conjunction, 3.0);
+        let grid = aspects(&chart(), None, AspectsFilter::AllAspects, &orbs);
+        assert_eq!(grid.get(sun, moon).unwrap().max_orb, 3.0);
+        assert_eq!(orbs.orb(Aspects::Conjunction, sun, sun), 2.0);
+    }
+
+    #[test]
+    fn test_aspects_to_angles() {
+        let houses = vec![
+            House::new(1, 99.0, Angle::Asc),
+            House::new(2, 125.0, Angle::Nothing),
+            House::new(10, 8.0, Angle::Mc),
+        ];
+        let grid = aspects(
+            &chart(),
+            Some(&houses),
+            AspectsFilter::AllMajorsAspects,
+            &OrbTable::default(),
+        );
+        let sun = AspectPoint::Body(Bodies::Sun);
+
+        let square = grid.get(sun, AspectPoint::Angle(Angle::Asc)).unwrap();
+        assert_eq!(square.aspect, Aspects::Square);
+        assert_approx_eq!(square.orb, 1.0);
+        // the sun moves towards the ascendant, away from the exact square
+        assert_eq!(square.motion, AspectMotion::Separating);
+
+        let mc = grid.get(sun, AspectPoint::Angle(Angle::Mc)).unwrap();
+        assert_eq!(mc.aspect, Aspects::Conjunction);
+        assert_eq!(mc.motion, AspectMotion::Separating);
+        assert_eq!(grid.of(AspectPoint::Angle(Angle::Asc)).len(), 3);
+        assert!(grid.of(AspectPoint::Angle(Angle::Nothing)).is_empty());
+    }
+
+    #[test]
+    fn test_no_aspects() {
+        let grid = aspects(
+            &chart(),
+            None,
+            AspectsFilter::NoAspects,
+            &OrbTable::default(),
+        );
+        assert!(grid.entries.is_empty());
+    }
+}
@@ -0,0 +1,470 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{Ayanamsha, CalculationFlags};
+use crate::ephemeris::with_sidereal_mode;
+use crate::error::SweError;
+use crate::sweconst::{
+    Angle, Bodies, Calendar, Element, House, HouseSystem, Object, Signs,
+};
+use crate::swerust::handler_swe03::{calc_ut, calc_ut_fp};
+use crate::swerust::handler_swe08::{deltat, utc_time_zone, utc_to_jd};
+use crate::swerust::handler_swe14::{houses_with_flag, object_house_position};
+use crate::swerust::handler_swe17::degnorm;
+
+/*
+ * Natal chart
+ *
+ * ChartBuilder assembles a chart from a local date and time, a time zone, a
+ * location, a house system and an optional ayanamsha: the positions of the
+ * bodies, the twelve house cusps tagged with the angles they coincide with,
+ * and the house, sign and element of every body.
+ *
+ * Bodies::SouthNode is computed opposite Bodies::TrueNode by calc_ut and
+ * Bodies::FortunaPart with calc_ut_fp.
+ */
+
+/// Bodies of a chart when none are given
+pub const DEFAULT_BODIES: [Bodies; 12] = [
+    Bodies::Sun,
+    Bodies::Moon,
+    Bodies::Mercury,
+    Bodies::Venus,
+    Bodies::Mars,
+    Bodies::Jupiter,
+    Bodies::Saturn,
+    Bodies::Uranus,
+    Bodies::Neptune,
+    Bodies::Pluto,
+    Bodies::TrueNode,
+    Bodies::Chiron,
+];
+
+/// Largest distance between a cusp and an angle for the cusp to be tagged
+const ANGLE_TOLERANCE: f64 = 1e-7;
+
+/// House, sign and element of a body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Placement {
+    pub body: Bodies,
+    pub house: i32,
+    pub house_position: f64, // 1.0 to 12.999, see handler_swe14::house_pos
+    pub sign: Signs,
+    pub element: Element,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chart {
+    pub julian_day_ut: f64,
+    pub julian_day_et: f64,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub house_system: HouseSystem,
+    pub ayanamsha: Option<Ayanamsha>,
+    pub ascendant: f64,
+    pub midheaven: f64,
+    pub objects: Vec<Object>,
+    pub houses: Vec<House>, // cusps 1 to 12
+    pub placements: Vec<Placement>,
+}
+
+impl Chart {
+    /// Placement of a body, if it is part of the chart
+    pub fn placement(&self, body: Bodies) -> Option<&Placement> {
+        self.placements.iter().find(|p| p.body == body)
+    }
+
+    /// House (1 to 12) containing a point of the ecliptic (latitude 0),
+    /// see Placement::house for the bodies
+    pub fn house_of(&self, longitude: f64) -> i32 {
+        house_of(&self.houses, longitude)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChartBuilder {
+    year: i32,
+    month: i32,
+    day: i32,
+    hour: i32,
+    min: i32,
+    sec: f64,
+    timezone: f64,
+    julian_day_ut: Option<f64>, // instead of the local date and time
+    latitude: f64,
+    longitude: f64,
+    house_system: HouseSystem,
+    ayanamsha: Option<Ayanamsha>,
+    bodies: Vec<Bodies>,
+}
+
+impl ChartBuilder {
+    /// Local date and time with its offset from UTC in hours, and the
+    /// geographic position. Placidus houses, tropical zodiac.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        year: i32,
+        month: i32,
+        day: i32,
+        hour: i32,
+        min: i32,
+        sec: f64,
+        timezone: f64,
+        latitude: f64,
+        longitude: f64,
+    ) -> ChartBuilder {
+        ChartBuilder {
+            year,
+            month,
+            day,
+            hour,
+            min,
+            sec,
+            timezone,
+            julian_day_ut: None,
+            latitude,
+            longitude,
+            house_system: HouseSystem::Placidus,
+            ayanamsha: None,
+            bodies: DEFAULT_BODIES.to_vec(),
+        }
+    }
+
+    /// Julian day (UT) and the geographic position. Placidus houses,
+    /// tropical zodiac.
+    pub fn from_julian_day(
+        julian_day_ut: f64,
+        latitude: f64,
+        longitude: f64,
+    ) -> ChartBuilder {
+        ChartBuilder {
+            julian_day_ut: Some(julian_day_ut),
+            ..ChartBuilder::new(0, 1, 1, 0, 0, 0.0, 0.0, latitude, longitude)
+        }
+    }
+
+    /// House system
+    pub fn house_system(mut self, hsys: HouseSystem) -> Self {
+        self.house_system = hsys;
+        self
+    }
+
+    /// Sidereal zodiac with an ayanamsha
+    pub fn ayanamsha(mut self, ayanamsha: Ayanamsha) -> Self {
+        self.ayanamsha = Some(ayanamsha);
+        self
+    }
+
+    pub fn bodies(mut self, bodies: &[Bodies]) -> Self {
+        self.bodies = bodies.to_vec();
+        self
+    }
+
+    pub fn build(&self) -> Result<Chart, SweError> {
+        if self.house_system == HouseSystem::GauquelinSectors {
+            return Err(SweError::InvalidArgument(
+                "Gauquelin sectors are not supported in a chart".to_string(),
+            ));
+        }
+        let (tjd_ut, tjd_et) = match self.julian_day_ut {
+            Some(tjd_ut) => (tjd_ut, tjd_ut + deltat(tjd_ut)),
+            None => {
+                let utc = utc_time_zone(
+                    self.year,
+                    self.month,
+                    self.day,
+                    self.hour,
+                    self.min,
+                    self.sec,
+                    self.timezone,
+                );
+                let jd = utc_to_jd(
+                    utc.year[0],
+                    utc.month[0],
+                    utc.day[0],
+                    utc.hour[0],
+                    utc.min[0],
+                    utc.sec[0],
+                    Calendar::Gregorian,
+                )?;
+                (jd.julian_day_ut, jd.julian_day_et)
+            },
+        };
+
+        // the sidereal mode stays set for the whole chart
+        with_sidereal_mode(self.ayanamsha, || self.positions(tjd_ut, tjd_et))
+    }
+
+    fn positions(&self, tjd_ut: f64, tjd_et: f64) -> Result<Chart, SweError> {
+        let mut iflag = CalculationFlags::SPEED_PRECISION;
+        if self.ayanamsha.is_some() {
+            iflag |= CalculationFlags::SIDEREAL_POSITIONS;
+        }
+
+        let result = houses_with_flag(
+            tjd_ut,
+            iflag,
+            self.latitude,
+            self.longitude,
+            self.house_system,
+        )?;
+        let ascendant = result.ascmc[0];
+        let midheaven = result.ascmc[1];
+        let angles = [
+            (Angle::Asc, ascendant),
+            (Angle::Fc, degnorm(midheaven + 180.0)),
+            (Angle::Desc, degnorm(ascendant + 180.0)),
+            (Angle::Mc, midheaven),
+        ];
+        let houses: Vec<House> = (1..=12)
+            .map(|i| {
+                let cusp = result.cusps[i];
+                let angle = angles
+                    .iter()
+                    .find(|(_, longitude)| {
+                        angle_distance(cusp, *longitude) < ANGLE_TOLERANCE
+                    })
+                    .map(|(angle, _)| *angle)
+                    .unwrap_or(Angle::Nothing);
+                House::new(i as i32, cusp, angle)
+            })
+            .collect();
+
+        let mut objects = Vec::new();
+        for &body in &self.bodies {
+            let position = match body {
+                Bodies::FortunaPart => calc_ut_fp(
+                    tjd_ut,
+                    self.latitude,
+                    self.longitude,
+                    self.house_system,
+                    iflag,
+                )?,
+                _ => calc_ut(tjd_ut, body, iflag)?,
+            };
+            objects.push(Object::new(
+                body,
+                body.into(),
+                body.object_type(),
+                position.longitude,
+                position.latitude,
+                position.speed_longitude,
+            ));
+        }
+
+        let mut placements = Vec::new();
+        for object in &objects {
+            // the latitude of the body matters in most house systems
+            let position = object_house_position(
+                tjd_ut,
+                iflag,
+                self.latitude,
+                self.longitude,
+                self.house_system,
+                object,
+            )?;
+            placements.push(Placement {
+                body: object.object_enum,
+                house: position.house,
+                house_position: position.position,
+                sign: object.split.sign.clone(),
+                element: object.split.sign.clone().element(),
+            });
+        }
+
+        Ok(Chart {
+            julian_day_ut: tjd_ut,
+            julian_day_et: tjd_et,
+            latitude: self.latitude,
+            longitude: self.longitude,
+            house_system: self.house_system,
+            ayanamsha: self.ayanamsha,
+            ascendant,
+            midheaven,
+            objects,
+            houses,
+            placements,
+        })
+    }
+}
+
+fn angle_distance(a: f64, b: f64) -> f64 {
+    let d = degnorm(a - b);
+    d.min(360.0 - d)
+}
+
+fn house_of(houses: &[House], longitude: f64) -> i32 {
+    for (i, house) in houses.iter().enumerate() {
+        let next = &houses[(i + 1) % houses.len()];
+        let width = degnorm(next.longitude - house.longitude);
+        if degnorm(longitude - house.longitude) < width {
+            return house.object_id;
+        }
+    }
+    // only reached with degenerate cusps
+    houses[0].object_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ensure_ephemeris_initialized;
+
+    fn builder() -> ChartBuilder {
+        ChartBuilder::new(2024, 11, 21, 16, 10, 0.0, 2.0, 43.084128, 25.5919228)
+    }
+
+    #[test]
+    fn test_placidus_chart() {
+        let _ = ensure_ephemeris_initialized();
+        let chart = builder().build().unwrap();
+
+        assert_eq!(chart.objects.len(), DEFAULT_BODIES.len());
+        assert_eq!(chart.houses.len(), 12);
+        let angles: Vec<Angle> = chart.houses.iter().map(|h| h.angle).collect();
+        assert_eq!(angles[0], Angle::Asc);
+        assert_eq!(angles[3], Angle::Fc);
+        assert_eq!(angles[6], Angle::Desc);
+        assert_eq!(angles[9], Angle::Mc);
+        assert_eq!(angles.iter().filter(|&&a| a == Angle::Nothing).count(), 8);
+        assert_eq!(chart.houses[0].longitude, chart.ascendant);
+
+        // the sun is a little above the western horizon
+        let sun = chart.placement(Bodies::Sun).unwrap();
+        assert_eq!(sun.house, 7);
+        assert!(sun.house_position > 7.0 && sun.house_position < 8.0);
+        assert_eq!(sun.sign.to_string(), "Scorpio");
+        assert_eq!(sun.element, Element::Water);
+    }
+
+    #[test]
+    fn test_whole_sign_chart_angles() {
+        let _ = ensure_ephemeris_initialized();
+        let chart = builder()
+            .house_system(HouseSystem::WholeSign)
+            .build()
+            .unwrap();
+
+        for house in &chart.houses {
+            assert_eq!(house.longitude % 30.0, 0.0);
+        }
+        // the ascendant is not on a cusp in whole sign houses
+        assert_eq!(chart.houses[0].angle, Angle::Nothing);
+        let sun = chart.placement(Bodies::Sun).unwrap();
+        assert_eq!(chart.house_of(chart.ascendant), 1);
+        assert!(sun.house >= 1 && sun.house <= 12);
+    }
+
+    #[test]
+    fn test_sidereal_chart_with_derived_points() {
+        let _ = ensure_ephemeris_initialized();
+        let tropical = builder()
+            .bodies(&[
+                Bodies::Sun,
+                Bodies::TrueNode,
+                Bodies::SouthNode,
+                Bodies::FortunaPart,
+            ])
+            .build()
+            .unwrap();
+        let sidereal = builder()
+            .ayanamsha(Ayanamsha::Lahiri)
+            .bodies(&[
+                Bodies::Sun,
+                Bodies::TrueNode,
+                Bodies::SouthNode,
+                Bodies::FortunaPart,
+            ])
+            .build()
+            .unwrap();
+
+        let difference = degnorm(
+            tropical.objects[0].longitude - sidereal.objects[0].longitude,
+        );
+        // Lahiri ayanamsha is about 24° in 2024
+        assert!((difference - 24.2).abs() < 0.1);
+        let opposite = degnorm(
+            sidereal.objects[2].longitude - sidereal.objects[1].longitude,
+        );
+        assert!((opposite - 180.0).abs() < 1e-9);
+        assert_eq!(sidereal.objects[3].object_enum, Bodies::FortunaPart);
+        // Ascendant, Sun and Moon are all shifted by the ayanamsha
+        let fortuna = degnorm(
+            tropical.objects[3].longitude - sidereal.objects[3].longitude,
+        );
+        assert!((fortuna - difference).abs() < 1e-6);
+        assert_eq!(sidereal.ayanamsha, Some(Ayanamsha::Lahiri));
+    }
+
+    #[test]
+    fn test_chart_serialization() {
+        let _ = ensure_ephemeris_initialized();
+        let chart = builder().build().unwrap();
+        let json = serde_json::to_string(&chart).unwrap();
+        let back: Chart = serde_json::from_str(&json).unwrap();
+
+        assert!(json.contains("\"house_system\":\"P\""));
+        assert_eq!(back.objects.len(), chart.objects.len());
+        assert_eq!(back.julian_day_ut, chart.julian_day_ut);
+        assert_eq!(back.houses[9].angle, Angle::Mc);
+    }
+
+    #[test]
+    fn test_concurrent_sidereal_charts() {
+        let _ = ensure_ephemeris_initialized();
+        let sun = |ayanamsha| {
+            builder()
+                .ayanamsha(ayanamsha)
+                .bodies(&[Bodies::Sun])
+                .build()
+                .unwrap()
+                .objects[0]
+                .longitude
+        };
+        let lahiri = sun(Ayanamsha::Lahiri);
+        let fagan = sun(Ayanamsha::FaganBradley);
+
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    let _ = ensure_ephemeris_initialized();
+                    let (ayanamsha, expected) = if i % 2 == 0 {
+                        (Ayanamsha::Lahiri, lahiri)
+                    } else {
+                        (Ayanamsha::FaganBradley, fagan)
+                    };
+                    for _ in 0..20 {
+                        assert_eq!(sun(ayanamsha), expected);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert!((lahiri - fagan).abs() > 0.5);
+    }
+
+    #[test]
+    fn test_gauquelin_sectors_not_supported() {
+        assert!(builder()
+            .house_system(HouseSystem::GauquelinSectors)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_chart_from_julian_day() {
+        let _ = ensure_ephemeris_initialized();
+        let chart = builder().build().unwrap();
+        let from_jd = ChartBuilder::from_julian_day(
+            chart.julian_day_ut,
+            43.084128,
+            25.5919228,
+        )
+        .build()
+        .unwrap();
+
+        assert_eq!(from_jd.ascendant, chart.ascendant);
+        assert!((from_jd.julian_day_et - chart.julian_day_et).abs() < 1e-9);
+    }
+}
@@ -1,123 +1,262 @@
-use crate::swerust::handler_swe14::house_name;
+use crate::ephemeris::{observer_applied, sidereal_mode_applied};
+use crate::swerust::handler_swe03::get_ayanamsha_name;
+use crate::sweconst::HouseSystem;
+use crate::error::SweError;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
 
 pub static EPHEMERIS_PATH: &str =
     "/home/kaliorion/dev/sandbox/swisseph/ephe/www.astro.com/ftp/swisseph/ephe";
 
 // Calculation options: https://www.astro.com/swisseph/swephprg.htm
-pub struct CalculationFlags;
+bitflags! {
+    /// Flags of calc_ut, fixstar_ut, houses_with_flag, rise_trans... combined with |
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct CalculationFlags: i32 {
+        const JPL_EPHEMERIS = 1; // use JPL ephemeris
+        const SWISS_EPHEMERIS = 2; // use SWISSEPH ephemeris, default
+        const MOSHIER_EPHEMERIS = 4; // use Moshier ephemeris
+        const HELIOCENTRIC_POSITIONS = 8; // return heliocentric position
+        const TRUE_POSITIONS = 16; // return true geometric positions, not apparent
+        const NO_PROCESSION_J2000 = 32; // no precession, i.e. give J2000 equinox
+        const MEAN_EQUINOX_OF_DATE_NO_NUTATION = 64; // no nutation, i.e. mean equinox of date
+        const SPEED3 = 128; // speed from 3 positions (do not use it, SPEED_PRECISION is better and faster)
+        const SPEED_PRECISION = 256; // high precision speed (analyt. comp.)
+        const NO_GRAVITY_DEFLECTION = 512; // turn off gravitational deflection
+        const NO_ABERRATION = 1024; // turn off 'annual' aberration of light
+        const ASTROMETRIC_POSITIONS = Self::NO_ABERRATION.bits() | Self::NO_GRAVITY_DEFLECTION.bits(); // astrometric positions. With light-time, without aberration and light deflection.
+        const EQUATORIAL_POSITIONS = 2048; // equatorial positions are wanted (incl. declination)
+        const CARTESIAN_COORDINATES = 4096; // cartesian, not polar, coordinates
+        const RADIAN_COORDINATES = 8192; // coordinates in radians, not degrees
+        const BARYCENTRIC_POSITIONS = 16384; // barycentric positions
+        const TOPOCENTRIC_POSITIONS = 32768; // topocentric positions
+        const TROPICAL_POSITIONS = 0; // Default
+        const SIDEREAL_POSITIONS = 65536; // sidereal positions
+        const ICRS = 131072; // ICRS (DE406 reference frame)
+        const JPL_HORIZONS = 262144; // reproduce JPL Horizons 1962 - today to 0.002 arcsec.
+        const APPROXIMATE_JPL_HORIZONS = 524288; // approximate JPL Horizons 1962 - today
+        const CENTER_BODY = 1048576; // calculate position of center of body (COB) of planet, not barycenter of its system
+    }
+}
 
 impl CalculationFlags {
-    pub const JPL_EPHEMERIS: i32 = 1; // use JPL ephemeris
-    pub const SWISS_EPHEMERIS: i32 = 2; // use SWISSEPH ephemeris, default
-    pub const MOSHIER_EPHEMERIS: i32 = 4; // use Moshier ephemeris
-    pub const HELIOCENTRIC_POSITIONS: i32 = 8; // return heliocentric position
-    pub const TRUE_POSITIONS: i32 = 16; // return true geometric positions, not apparent
-    pub const NO_PROCESSION_J2000: i32 = 32; // no precession, i.e. give J2000 equinox
-    pub const MEAN_EQUINOX_OF_DATE_NO_NUTATION: i32 = 64; // no nutation, i.e. mean equinox of date
-    pub const SPEED_PRECISION: i32 = 256; // high precision speed (analyt. comp.)
-    pub const NO_GRAVITY_DEFLECTION: i32 = 512; // turn off gravitational deflection
-    pub const NO_ABERRATION: i32 = 1024; // turn off 'annual' aberration of light
-    pub const ASTROMETRIC_POSITIONS: i32 = 1536; // astrometric positions. With light-time, without aberration and light deflection.
-    pub const EQUATORIAL_POSITIONS: i32 = 2048; // equatorial positions are wanted (incl. declination)
-    pub const CARTESIAN_COORDINATES: i32 = 4096; // cartesian, not polar, coordinates
-    pub const RADIAN_COORDINATES: i32 = 8192; // coordinates in radians, not degrees
-    pub const BARYCENTRIC_POSITIONS: i32 = 16384; // barycentric positions
-    pub const TOPOCENTRIC_POSITIONS: i32 = 32768; // topocentric positions
-    pub const TROPICAL_POSITIONS: i32 = 0; // Default 
-    pub const SIDEREAL_POSITIONS: i32 = 65536; // sidereal positions
-    pub const ICRS: i32 = 131072; // ICRS (DE406 reference frame)
-    pub const JPL_HORIZONS: i32 = 262144; // reproduce JPL Horizons 1962 - today to 0.002 arcsec.
-    pub const APPROXIMATE_JPL_HORIZONS: i32 = 524288; // approximate JPL Horizons 1962 - today
-    pub const CENTER_BODY: i32 = 1048576; // calculate position of center of body (COB) of planet, not barycenter of its system
+    /// Flags returned by the C library, which may contain bits unknown here
+    pub fn from_status(status: i32) -> CalculationFlags {
+        CalculationFlags::from_bits_retain(status)
+    }
+
+    /// Rejects combinations the C library would silently resolve on its own:
+    /// more than one ephemeris, heliocentric with barycentric or topocentric
+    /// positions, sidereal or topocentric positions outside an Ephemeris
+    /// applying a sidereal mode or an observer
+    pub fn validate(self) -> Result<(), SweError> {
+        let ephemerides = self
+            & (CalculationFlags::JPL_EPHEMERIS
+                | CalculationFlags::SWISS_EPHEMERIS
+                | CalculationFlags::MOSHIER_EPHEMERIS);
+        if ephemerides.bits().count_ones() > 1 {
+            return Err(SweError::InvalidArgument(format!(
+                "more than one ephemeris requested: {:?}",
+                ephemerides
+            )));
+        }
+        let centers = self
+            & (CalculationFlags::HELIOCENTRIC_POSITIONS
+                | CalculationFlags::BARYCENTRIC_POSITIONS
+                | CalculationFlags::TOPOCENTRIC_POSITIONS);
+        if centers.bits().count_ones() > 1 {
+            return Err(SweError::InvalidArgument(format!(
+                "more than one center of positions requested: {:?}",
+                centers
+            )));
+        }
+        if self.contains(CalculationFlags::SIDEREAL_POSITIONS) && !sidereal_mode_applied() {
+            return Err(SweError::InvalidArgument(
+                "sidereal positions requested without a sidereal mode, use an Ephemeris with a sidereal mode"
+                    .to_string(),
+            ));
+        }
+        if self.contains(CalculationFlags::TOPOCENTRIC_POSITIONS) && !observer_applied() {
+            return Err(SweError::InvalidArgument(
+                "topocentric positions requested without an observer, use an Ephemeris with an observer or calc_ut_topo"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Ayanamsha;
+bitflags! {
+    /// Options of a sidereal mode (SE_SIDBIT_*), combined with |
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct SiderealBits: i32 {
+        const ECLIPTIC_T0 = 256; // projection onto the ecliptic of t0
+        const SOLAR_SYSTEM_PLANE = 512; // projection onto the solar system plane
+        const USER_UT = 1024; // t0 of Ayanamsha::User is UT instead of TT
+        const ECLIPTIC_DATE = 2048; // ayanamsha measured on the ecliptic of date
+        const NO_PRECESSION_OFFSET = 4096; // test feature: no constant offset of the ayanamsha
+        const ORIGINAL_PRECESSION = 8192; // test feature: original precession model of the ayanamsha
+    }
+}
+
+/// Sidereal modes of the C library (SE_SIDM_*)
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, Serialize, Deserialize)]
+pub enum Ayanamsha {
+    FaganBradley, // Fagan/Bradley
+    Lahiri, // Lahiri
+    DeLuce, // De Luce
+    Raman, // Raman
+    UshaShashi, // Usha/Shashi
+    Krishnamurti, // Krishnamurti
+    DjwhalKhul, // Djwhal Khul
+    Yukteshwar, // Yukteshwar
+    JnBhasin, // J.N. Bhasin
+    BabylonianKugler1, // Babylonian/Kugler 1
+    BabylonianKugler2, // Babylonian/Kugler 2
+    BabylonianKugler3, // Babylonian/Kugler 3
+    BabylonianHuber, // Babylonian/Huber
+    BabylonianEtaPiscium, // Babylonian/Eta Piscium
+    BabylonianAldebaran15Tau, // Babylonian/Aldebaran = 15 Tau
+    Hipparchos, // Hipparchos
+    Sassanian, // Sassanian
+    GalacticCenter0Sag, // Galactic Center = 0 Sag
+    J2000, // J2000
+    J1900, // J1900
+    B1950, // B1950
+    Suryasiddhanta, // Suryasiddhanta
+    SuryasiddhantaMeanSun, // Suryasiddhanta, mean Sun
+    Aryabhata, // Aryabhata
+    AryabhataMeanSun, // Aryabhata, mean Sun
+    SsRevati, // SS Revati
+    SsCitra, // SS Citra
+    TrueCitra, // True Citra
+    TrueRevati, // True Revati
+    TruePushya, // True Pushya (PVRN Rao)
+    GalacticCenterGilBrand, // Galactic Center (Gil Brand)
+    GalacticEquatorIau1958, // Galactic Equator (IAU1958)
+    GalacticEquatorTrue, // Galactic Equator
+    GalacticEquatorMidMula, // Galactic Equator mid-Mula
+    GalacticAlignmentSkydramMardyks, // Skydram (Mardyks)
+    TrueMulaChandraHari, // True Mula (Chandra Hari)
+    GalacticCenterMulaWilhelm, // Dhruva/Gal.Center/Mula (Wilhelm)
+    Aryabhata522, // Aryabhata 522
+    BabylonianBritton, // Babylonian/Britton
+    VedicSheoran, // "Vedic"/Sheoran
+    GalacticCenterCochrane0Cap, // Galactic Center (Cochrane) = 0 Cap
+    GalacticEquatorFiorenza, // Galactic Equator (Fiorenza)
+    VettiusValensMoon, // Vettius Valens Moon
+    Lahiri1940, // Lahiri 1940
+    LahiriVp285, // Lahiri VP285
+    KrishnamurtiSenthilathiban, // Krishnamurti-Senthilathiban (VP291)
+    LahiriIcrc, // Lahiri ICRC
+    /// Ayanamsha of ayan_t0 degrees at the julian day t0 (TT, or UT with
+    /// SiderealBits::USER_UT)
+    User { t0: f64, ayan_t0: f64 },
+}
 
 impl Ayanamsha {
-    pub const FAGAN_BRADLEY: i32 = 0; // Fagan/Bradley
-    pub const LAHIRI: i32 = 1; // Lahiri
-    pub const DE_LUCE: i32 = 2; // De Luce
-    pub const RAMAN: i32 = 3; // Raman
-    pub const USHA_SHASHI: i32 = 4; // Usha/Shashi
-    pub const KRISHNAMURTI: i32 = 5; // Krishnamurti
-    pub const DJWHAL_KHUL: i32 = 6; // Djwhal Khul
-    pub const YUKTESHWAR: i32 = 7; // Yukteshwar
-    pub const JN_BHASIN: i32 = 8; // J.N. Bhasin
-    pub const BABYLONIAN_KUGLER_1: i32 = 9; // Babylonian/Kugler 1
-    pub const BABYLONIAN_KUGLER_2: i32 = 10; // Babylonian/Kugler 2
-    pub const BABYLONIAN_KUGLER_3: i32 = 11; // Babylonian/Kugler 3
-    pub const BABYLONIAN_HUBER: i32 = 12; // Babylonian/Huber
-    pub const BABYLONIAN_ETA_PISCIUM: i32 = 13; // Babylonian/Eta Piscium
-    pub const BABYLONIAN_ALDEBARAN_15_TAU: i32 = 14; // Babylonian/Aldebaran = 15 Tau
-    pub const HIPPARCHOS: i32 = 15; // Hipparchos
-    pub const SASSANIAN: i32 = 16; // Sassanian
-    pub const GALACTIC_CENTER_0_SAG: i32 = 17; // Galactic Center = 0 Sag
-    pub const J2000: i32 = 18; // J2000
-    pub const J1900: i32 = 19; // J1900
-    pub const B1950: i32 = 20; // B1950
-    pub const SURYASIDDHANTA: i32 = 21; // Suryasiddhanta
-    pub const SURYASIDDHANTA_MEAN_SUN: i32 = 22; // Suryasiddhanta, mean Sun
-    pub const ARYABHATA: i32 = 23; // Aryabhata
-    pub const ARYABHATA_MEAN_SUN: i32 = 24; // Aryabhata, mean Sun
-    pub const SS_REVATI: i32 = 25; // SS Revati
-    pub const SS_CITRA: i32 = 26; // SS Citra
-    pub const TRUE_CITRA: i32 = 27; // True Citra
-    pub const TRUE_REVATI: i32 = 28; // True Revati
-    pub const TRUE_PUSHYA: i32 = 29; // True Pushya (PVRN Rao)
-    pub const GALACTIC_CENTER_GIL_BRAND: i32 = 30; // Galactic Center (Gil Brand)
-    pub const GALACTIC_EQUATOR_IAU1958: i32 = 31; // Galactic Equator (IAU1958)
-    pub const GALACTIC_EQUATOR_TRUE: i32 = 32; // Galactic Equator
-    pub const GALACTIC_EQUATOR_MID_MULA: i32 = 33; // Galactic Equator mid-Mula
-    pub const GALACTIC_ALIGNMENT_SKYDRAM_MARDYKS: i32 = 34; // Skydram (Mardyks)
-    pub const TRUE_MULA_CHANDRA_HARI: i32 = 35; // True Mula (Chandra Hari)
-    pub const GALACTIC_CENTER_MULA_WILHELM: i32 = 36; // Dhruva/Gal.Center/Mula (Wilhelm)
-    pub const ARYABHATA_522: i32 = 37; // Aryabhata 522
-    pub const BABYLONIAN_BRITTON: i32 = 38; // Babylonian/Britton
-    pub const VEDIC_SHEORAN: i32 = 39; // \"Vedic\"/Sheoran
-    pub const GALACTIC_CENTER_COCHRANE_0_CAP: i32 = 40; // \"Vedic\"/Sheoran
-    pub const GALACTIC_EQUATOR_FIORENZA: i32 = 41; // Galactic Equator (Fiorenza)
-    pub const VETTIUS_VALENS_MOON: i32 = 42; // Galactic Equator (Fiorenza)
-    pub const LAHIRI_1940: i32 = 43; // Lahiri 1940
-    pub const LAHIRI_VP285: i32 = 44; // Lahiri VP285
-    pub const KRISHNAMURTI_SENTHILATHIBAN: i32 = 45; // Krishnamurti-Senthilathiban
-    pub const LHIRI_ICRC: i32 = 45; // Lahiri ICRC
+    /// Number of the sidereal mode for the C library (sid_mode)
+    pub fn sid_mode(self) -> i32 {
+        match self {
+            Ayanamsha::FaganBradley => 0,
+            Ayanamsha::Lahiri => 1,
+            Ayanamsha::DeLuce => 2,
+            Ayanamsha::Raman => 3,
+            Ayanamsha::UshaShashi => 4,
+            Ayanamsha::Krishnamurti => 5,
+            Ayanamsha::DjwhalKhul => 6,
+            Ayanamsha::Yukteshwar => 7,
+            Ayanamsha::JnBhasin => 8,
+            Ayanamsha::BabylonianKugler1 => 9,
+            Ayanamsha::BabylonianKugler2 => 10,
+            Ayanamsha::BabylonianKugler3 => 11,
+            Ayanamsha::BabylonianHuber => 12,
+            Ayanamsha::BabylonianEtaPiscium => 13,
+            Ayanamsha::BabylonianAldebaran15Tau => 14,
+            Ayanamsha::Hipparchos => 15,
+            Ayanamsha::Sassanian => 16,
+            Ayanamsha::GalacticCenter0Sag => 17,
+            Ayanamsha::J2000 => 18,
+            Ayanamsha::J1900 => 19,
+            Ayanamsha::B1950 => 20,
+            Ayanamsha::Suryasiddhanta => 21,
+            Ayanamsha::SuryasiddhantaMeanSun => 22,
+            Ayanamsha::Aryabhata => 23,
+            Ayanamsha::AryabhataMeanSun => 24,
+            Ayanamsha::SsRevati => 25,
+            Ayanamsha::SsCitra => 26,
+            Ayanamsha::TrueCitra => 27,
+            Ayanamsha::TrueRevati => 28,
+            Ayanamsha::TruePushya => 29,
+            Ayanamsha::GalacticCenterGilBrand => 30,
+            Ayanamsha::GalacticEquatorIau1958 => 31,
+            Ayanamsha::GalacticEquatorTrue => 32,
+            Ayanamsha::GalacticEquatorMidMula => 33,
+            Ayanamsha::GalacticAlignmentSkydramMardyks => 34,
+            Ayanamsha::TrueMulaChandraHari => 35,
+            Ayanamsha::GalacticCenterMulaWilhelm => 36,
+            Ayanamsha::Aryabhata522 => 37,
+            Ayanamsha::BabylonianBritton => 38,
+            Ayanamsha::VedicSheoran => 39,
+            Ayanamsha::GalacticCenterCochrane0Cap => 40,
+            Ayanamsha::GalacticEquatorFiorenza => 41,
+            Ayanamsha::VettiusValensMoon => 42,
+            Ayanamsha::Lahiri1940 => 43,
+            Ayanamsha::LahiriVp285 => 44,
+            Ayanamsha::KrishnamurtiSenthilathiban => 45,
+            Ayanamsha::LahiriIcrc => 46,
+            Ayanamsha::User { .. } => 255,
+        }
+    }
+
+    /// Predefined sidereal mode of a number, User is built with its values
+    pub fn from_sid_mode(sid_mode: i32) -> Result<Ayanamsha, SweError> {
+        Ayanamsha::iter()
+            .find(|a| a.sid_mode() == sid_mode)
+            .filter(|a| !matches!(a, Ayanamsha::User { .. }))
+            .ok_or_else(|| SweError::InvalidArgument(format!("unknown sidereal mode {}", sid_mode)))
+    }
+
+    /// Name given by the C library (swe_get_ayanamsa_name), None for User
+    pub fn name(self) -> Option<String> {
+        get_ayanamsha_name(self)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct HouseSystems;
 
-/// 'G': GAUQUELIN_SECTORS is not included since it returns array of 36 and currently
-/// it is not implemented.
+/// Chars of sweconst::HouseSystem, the type taken by the house functions
 impl HouseSystems {
-    pub const PLACIDUS: char = 'P';
-    pub const KOCH: char = 'K';
-    pub const PORPHYRIUS: char = 'O';
-    pub const REGIOMONTANUS: char = 'R';
-    pub const CAMPANUS: char = 'C';
-    pub const EQUAL: char = 'E'; // Equal (cusp 1 is Ascendant)
-    pub const WHOLE_SIGN: char = 'W';
-    pub const ALCABITUS: char = 'B';
-    pub const EQUAL_MC: char = 'D'; // Equal MC (cusp 10 is MC)
-    pub const CARTHER_POLI_EQUATORIAL: char = 'F'; // Carter "Poli-Equatorial"
-    pub const HORIZONTAL_SYSTEM: char = 'H'; // Azimuthal or horizontal system
-    pub const SUNSHINE_TREINDL: char = 'I'; // Sunshine (Makransky, solution Treindl)
-    pub const SUNSHINE_MAKRANSKY: char = 'i'; // Sunshine (Makransky, solution Makransky)
-    pub const KRUSINSKI_PISA_GOELZER: char = 'U'; // Krusinski-Pisa-Goelzer Meridian system -> axial rotation
-    pub const MORINUS: char = 'M';
-    pub const POLICH_PAGE_TOPOCENTRIC: char = 'T'; // Polich/Page (topocentric system)
-    pub const PULLEN_SINUSOIDAL_DELTA: char = 'L'; // Pullen SD (sinusoidal delta): ex Neo-Porphyry
-    pub const PULLEN_SINUSOIDAL_RATIO: char = 'Q'; // Pullen SR (sinusoidal ratio)
-    pub const SRIPATI_TOPOCENTRIC: char = 'S'; // Sripati Topocentric system -> Polich/Page
-    pub const VEHLOW_EQUAL: char = 'V'; // Vehlow equal (Asc. in middle of house 1)
-    pub const APC_HOUSES: char = 'Y'; // APC houses
-    pub const MERIDIAN_HOUSES: char = 'X'; // Axial rotation system / Meridian system / Zariel
+    pub const PLACIDUS: char = HouseSystem::Placidus.to_char();
+    pub const KOCH: char = HouseSystem::Koch.to_char();
+    pub const PORPHYRIUS: char = HouseSystem::Porphyrius.to_char();
+    pub const REGIOMONTANUS: char = HouseSystem::Regiomontanus.to_char();
+    pub const CAMPANUS: char = HouseSystem::Campanus.to_char();
+    pub const EQUAL: char = HouseSystem::Equal.to_char(); // Equal (cusp 1 is Ascendant)
+    pub const EQUAL_ARIES: char = HouseSystem::EqualAries.to_char(); // Equal (cusp 1 is 0° Aries)
+    pub const WHOLE_SIGN: char = HouseSystem::WholeSign.to_char();
+    pub const ALCABITUS: char = HouseSystem::Alcabitius.to_char();
+    pub const EQUAL_MC: char = HouseSystem::EqualMc.to_char(); // Equal MC (cusp 10 is MC)
+    pub const CARTHER_POLI_EQUATORIAL: char = HouseSystem::CarterPoliEquatorial.to_char(); // Carter "Poli-Equatorial"
+    pub const HORIZONTAL_SYSTEM: char = HouseSystem::Horizontal.to_char(); // Azimuthal or horizontal system
+    pub const SUNSHINE_TREINDL: char = HouseSystem::SunshineTreindl.to_char(); // Sunshine (Makransky, solution Treindl)
+    pub const SUNSHINE_MAKRANSKY: char = HouseSystem::SunshineMakransky.to_char(); // Sunshine (Makransky, solution Makransky)
+    pub const KRUSINSKI_PISA_GOELZER: char = HouseSystem::KrusinskiPisaGoelzer.to_char(); // Krusinski-Pisa-Goelzer
+    pub const MORINUS: char = HouseSystem::Morinus.to_char();
+    pub const POLICH_PAGE_TOPOCENTRIC: char = HouseSystem::PolichPage.to_char(); // Polich/Page (topocentric system)
+    pub const PULLEN_SINUSOIDAL_DELTA: char = HouseSystem::PullenSinusoidalDelta.to_char(); // Pullen SD (sinusoidal delta): ex Neo-Porphyry
+    pub const PULLEN_SINUSOIDAL_RATIO: char = HouseSystem::PullenSinusoidalRatio.to_char(); // Pullen SR (sinusoidal ratio)
+    pub const SRIPATI: char = HouseSystem::Sripati.to_char(); // Sripati, not the topocentric system
+    #[deprecated(note = "'S' is Sripati, see SRIPATI and POLICH_PAGE_TOPOCENTRIC")]
+    pub const SRIPATI_TOPOCENTRIC: char = HouseSystems::SRIPATI;
+    pub const VEHLOW_EQUAL: char = HouseSystem::VehlowEqual.to_char(); // Vehlow equal (Asc. in middle of house 1)
+    pub const APC_HOUSES: char = HouseSystem::Apc.to_char(); // APC houses
+    pub const MERIDIAN_HOUSES: char = HouseSystem::AxialRotation.to_char(); // Axial rotation system / Meridian system / Zariel
+    pub const SAVARD_A: char = HouseSystem::SavardA.to_char(); // Savard-A
+    pub const GAUQUELIN_SECTORS: char = HouseSystem::GauquelinSectors.to_char(); // 36 sectors, clockwise from the Ascendant
 
-    pub fn get_house_system_name(house_system: char) -> String {
-        let name = house_name(house_system);
-        if house_system == HouseSystems::WHOLE_SIGN {
-            return name.replace("equal/ ", "");
-        }
-        name
+    pub fn get_house_system_name(house_system: char) -> Result<String, SweError> {
+        HouseSystem::from_char(house_system)?.name()
     }
 }
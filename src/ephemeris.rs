@@ -0,0 +1,601 @@
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread::{self, ThreadId};
+
+use crate::constants::{Ayanamsha, CalculationFlags, SiderealBits};
+use crate::error::SweError;
+use crate::init_ephemeris::{
+    ensure_ephemeris_initialized, ephemeris_path, validate_ephemeris_directory,
+    EphemerisError, Result,
+};
+use crate::sweconst::{Bodies, HouseSystem, Observer};
+use crate::swerust::handler_swe02::{set_ephe_path, set_jpl_file};
+use crate::swerust::handler_swe03::{
+    calc_ut, fixstar_ut, get_ayanamsa_ex_ut, set_sidereal_mode, set_topo,
+    AyanamshaResult, CalcUtResult, FixStarResult,
+};
+use crate::swerust::handler_swe08::{deltat_ex, set_delta_t_userdef, DeltaT};
+use crate::swerust::handler_swe14::{houses_with_flag, HousesResult};
+
+/*
+ * Ephemeris handle
+ *
+ * The Swiss Ephemeris keeps its configuration (ephemeris path, JPL file,
+ * sidereal mode, topocentric position, Delta T) in global state of the C
+ * library. An Ephemeris owns such a configuration and applies it before each
+ * calculation, while holding a lock that serializes every access to the C
+ * library made through an Ephemeris. Handles with different ayanamshas or
+ * observers can therefore be used from several threads at the same time.
+ *
+ * The sidereal mode, observer and Delta T are only changed by this module,
+ * inside a lock scope. The settings of the scope are recorded so that
+ * CalculationFlags::validate can tell whether they were applied, nested
+ * scopes restore the settings of the enclosing one, and a user defined
+ * Delta T is reset when the outermost scope ends.
+ *
+ * The ephemeris path and JPL file are only set again when they differ from
+ * the ones last set, or were last set by another thread, as setting them
+ * closes the open files. The C library keeps them per thread on Linux but
+ * process-wide on macOS and Windows (no TLS, sweodef.h), so the last files
+ * are kept for the whole process together with the thread that set them.
+ */
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Thread, ephemeris path and JPL file of the last set_ephe_path
+type Files = (ThreadId, String, Option<String>);
+
+/// Files last set by an Ephemeris while holding LOCK
+static FILES: Mutex<Option<Files>> = Mutex::new(None);
+
+/// Settings of the C library applied in the current lock scope
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Settings {
+    sidereal: Option<SiderealMode>,
+    topo: Option<Observer>,
+    delta_t: Option<f64>,
+}
+
+/// Settings of the thread holding LOCK, None while the lock is free
+static SETTINGS: Mutex<Option<Settings>> = Mutex::new(None);
+
+thread_local! {
+    static LOCKED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Sidereal zodiac: ayanamsha and the options of the sidereal mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SiderealMode {
+    pub ayanamsha: Ayanamsha,
+    pub bits: SiderealBits,
+}
+
+impl SiderealMode {
+    pub fn new(ayanamsha: Ayanamsha) -> SiderealMode {
+        SiderealMode {
+            ayanamsha,
+            bits: SiderealBits::empty(),
+        }
+    }
+
+    /// User defined ayanamsha: its value at a reference epoch (julian day)
+    pub fn user(t0: f64, ayan_t0: f64) -> SiderealMode {
+        SiderealMode::new(Ayanamsha::User { t0, ayan_t0 })
+    }
+
+    /// Projection and precession options, see constants::SiderealBits
+    pub fn bits(mut self, bits: SiderealBits) -> SiderealMode {
+        self.bits = bits;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ephemeris {
+    path: String,
+    jpl_file: Option<String>,
+    sidereal: Option<SiderealMode>,
+    topo: Option<Observer>,
+    delta_t: Option<f64>,
+}
+
+impl Ephemeris {
+    /// Ephemeris files resolved by ensure_ephemeris_initialized, tropical
+    /// zodiac, geocentric positions
+    pub fn new() -> Result<Ephemeris> {
+        ensure_ephemeris_initialized()?;
+        let path = ephemeris_path()
+            .ok_or_else(|| EphemerisError::InvalidPath(PathBuf::new()))?;
+        Ephemeris::with_path(path)
+    }
+
+    /// Ephemeris files of a directory containing .se1 and .txt files
+    pub fn with_path<P: AsRef<Path>>(path: P) -> Result<Ephemeris> {
+        let path = path.as_ref();
+        validate_ephemeris_directory(path)?;
+        let path_str = path
+            .to_str()
+            .filter(|p| p.len() <= 255)
+            .ok_or_else(|| EphemerisError::InvalidPath(path.to_path_buf()))?;
+        Ok(Ephemeris {
+            path: path_str.to_string(),
+            jpl_file: None,
+            sidereal: None,
+            topo: None,
+            delta_t: None,
+        })
+    }
+
+    /// JPL file of the ephemeris directory, used with
+    /// CalculationFlags::JPL_EPHEMERIS
+    pub fn jpl_file(mut self, fname: &str) -> Self {
+        self.jpl_file = Some(fname.to_string());
+        self
+    }
+
+    /// Sidereal positions with an ayanamsha
+    pub fn sidereal(mut self, ayanamsha: Ayanamsha) -> Self {
+        self.sidereal = Some(SiderealMode::new(ayanamsha));
+        self
+    }
+
+    pub fn sidereal_mode(mut self, mode: SiderealMode) -> Self {
+        self.sidereal = Some(mode);
+        self
+    }
+
+    /// Topocentric positions for an observer
+    pub fn topo(mut self, observer: Observer) -> Self {
+        self.topo = Some(observer);
+        self
+    }
+
+    /// Fixed Delta T in days instead of the computed one
+    pub fn delta_t(mut self, days: f64) -> Self {
+        self.delta_t = Some(days);
+        self
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn get_sidereal_mode(&self) -> Option<SiderealMode> {
+        self.sidereal
+    }
+
+    pub fn observer(&self) -> Option<Observer> {
+        self.topo
+    }
+
+    /// iflag with the sidereal and topocentric flags of the configuration
+    pub fn flags(&self, iflag: CalculationFlags) -> CalculationFlags {
+        let mut iflag = iflag;
+        if self.sidereal.is_some() {
+            iflag |= CalculationFlags::SIDEREAL_POSITIONS;
+        }
+        if self.topo.is_some() {
+            iflag |= CalculationFlags::TOPOCENTRIC_POSITIONS;
+        }
+        iflag
+    }
+
+    /// Runs f with the configuration applied and the C library locked.
+    /// Calls of other Ephemeris methods inside f are allowed on the same
+    /// thread and apply their own configuration.
+    pub fn with<R, F>(&self, f: F) -> std::result::Result<R, SweError>
+    where
+        F: FnOnce() -> std::result::Result<R, SweError>,
+    {
+        let settings = Settings {
+            sidereal: self.sidereal,
+            topo: self.topo,
+            delta_t: self.delta_t,
+        };
+        scoped(
+            |s| *s = settings,
+            || {
+                self.apply_files()?;
+                f()
+            },
+        )
+    }
+
+    pub fn calc_ut(
+        &self,
+        tjd_ut: f64,
+        ipl: Bodies,
+        iflag: CalculationFlags,
+    ) -> std::result::Result<CalcUtResult, SweError> {
+        self.with(|| calc_ut(tjd_ut, ipl, self.flags(iflag)))
+    }
+
+    pub fn fixstar_ut(
+        &self,
+        star: &str,
+        tjd_ut: f64,
+        iflag: CalculationFlags,
+    ) -> std::result::Result<FixStarResult, SweError> {
+        self.with(|| fixstar_ut(star, tjd_ut, self.flags(iflag)))
+    }
+
+    /// Houses, sidereal when a sidereal mode is set
+    pub fn houses(
+        &self,
+        tjd_ut: f64,
+        geolat: f64,
+        geolong: f64,
+        hsys: HouseSystem,
+    ) -> std::result::Result<HousesResult, SweError> {
+        let flag = match self.sidereal {
+            Some(_) => CalculationFlags::SIDEREAL_POSITIONS,
+            None => CalculationFlags::empty(),
+        };
+        self.with(|| houses_with_flag(tjd_ut, flag, geolat, geolong, hsys))
+    }
+
+    /// Ayanamsha of the sidereal mode, see get_ayanamsa_ex_ut
+    pub fn ayanamsha(
+        &self,
+        tjd_ut: f64,
+        iflag: CalculationFlags,
+    ) -> std::result::Result<AyanamshaResult, SweError> {
+        if self.sidereal.is_none() {
+            return Err(SweError::InvalidArgument(
+                "ayanamsha of an ephemeris without a sidereal mode".to_string(),
+            ));
+        }
+        self.with(|| get_ayanamsa_ex_ut(tjd_ut, iflag))
+    }
+
+    /// Delta T of the configuration, see deltat_ex
+    pub fn deltat_ex(
+        &self,
+        tjd_ut: f64,
+        iflag: CalculationFlags,
+    ) -> std::result::Result<DeltaT, SweError> {
+        self.with(|| deltat_ex(tjd_ut, iflag))
+    }
+
+    fn apply_files(&self) -> std::result::Result<(), SweError> {
+        let files = (
+            thread::current().id(),
+            self.path.clone(),
+            self.jpl_file.clone(),
+        );
+        let changed = *last_files() != Some(files.clone());
+        if changed {
+            set_ephe_path(&self.path)?;
+            if let Some(ref fname) = self.jpl_file {
+                set_jpl_file(fname)?;
+            }
+            *last_files() = Some(files);
+        }
+        Ok(())
+    }
+}
+
+/// Runs f while holding the lock of the C library, unless the thread already
+/// holds it
+pub(crate) fn exclusive<R, F: FnOnce() -> R>(f: F) -> R {
+    if LOCKED.with(|l| l.get()) {
+        return f();
+    }
+    let _guard = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    LOCKED.with(|l| l.set(true));
+    let _unlocked = Unlock;
+    f()
+}
+
+/// Runs f with the sidereal mode of an ayanamsha set, or tropical with None,
+/// while holding the lock of the C library
+pub(crate) fn with_sidereal_mode<R, F: FnOnce() -> R>(
+    ayanamsha: Option<Ayanamsha>,
+    f: F,
+) -> R {
+    scoped(|s| s.sidereal = ayanamsha.map(SiderealMode::new), f)
+}
+
+/// Runs f with the position of an observer set, while holding the lock of the
+/// C library
+pub(crate) fn with_observer<R, F: FnOnce() -> R>(
+    observer: &Observer,
+    f: F,
+) -> R {
+    let observer = *observer;
+    scoped(|s| s.topo = Some(observer), f)
+}
+
+/// Runs f while holding the lock with the settings of the enclosing scope
+/// changed by change, and restores them afterwards
+fn scoped<R, F: FnOnce() -> R>(change: impl FnOnce(&mut Settings), f: F) -> R {
+    exclusive(|| {
+        let enclosing = *settings();
+        let mut next = enclosing.unwrap_or_default();
+        change(&mut next);
+        apply(next);
+        let result = f();
+        if let Some(enclosing) = enclosing {
+            apply(enclosing);
+        }
+        result
+    })
+}
+
+/// Changes the settings of the C library that differ from the recorded ones
+fn apply(next: Settings) {
+    let mut recorded = settings();
+    let current = recorded.unwrap_or_default();
+    if let Some(mode) = next.sidereal {
+        if recorded.is_none() || current.sidereal != next.sidereal {
+            set_sidereal_mode(mode.ayanamsha, mode.bits);
+        }
+    }
+    if let Some(ref observer) = next.topo {
+        if recorded.is_none() || current.topo != next.topo {
+            set_topo(observer);
+        }
+    }
+    if current.delta_t != next.delta_t {
+        set_delta_t_userdef(next.delta_t);
+    }
+    *recorded = Some(next);
+}
+
+/// A sidereal mode was applied in the lock scope of this thread, required by
+/// CalculationFlags::SIDEREAL_POSITIONS
+pub(crate) fn sidereal_mode_applied() -> bool {
+    LOCKED.with(|l| l.get()) && settings().is_some_and(|s| s.sidereal.is_some())
+}
+
+/// An observer was applied in the lock scope of this thread, required by
+/// CalculationFlags::TOPOCENTRIC_POSITIONS
+pub(crate) fn observer_applied() -> bool {
+    LOCKED.with(|l| l.get()) && settings().is_some_and(|s| s.topo.is_some())
+}
+
+/// Resets a user defined Delta T and clears the thread flag when the lock is
+/// released, also on panic
+struct Unlock;
+
+impl Drop for Unlock {
+    fn drop(&mut self) {
+        let released = settings().take();
+        if released.is_some_and(|s| s.delta_t.is_some()) {
+            set_delta_t_userdef(None);
+        }
+        LOCKED.with(|l| l.set(false));
+    }
+}
+
+fn settings() -> MutexGuard<'static, Option<Settings>> {
+    SETTINGS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn last_files() -> MutexGuard<'static, Option<Files>> {
+    FILES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The ephemeris files were set outside of an Ephemeris
+pub(crate) fn forget_files() {
+    *last_files() = None;
+}
+
+/// swe_close reset the sidereal mode and observer of the C library
+pub(crate) fn forget_settings() {
+    if let Some(ref mut settings) = *settings() {
+        settings.sidereal = None;
+        settings.topo = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::Ayanamsha;
+    use crate::sweconst::Calendar;
+    use crate::swerust::handler_swe08::{utc_time_zone, utc_to_jd};
+    use crate::swerust::handler_swe17::degnorm;
+    use std::thread;
+
+    fn test_jd() -> f64 {
+        let utc = utc_time_zone(2024, 11, 21, 16, 10, 0.0, 2.0);
+        utc_to_jd(
+            utc.year[0],
+            utc.month[0],
+            utc.day[0],
+            utc.hour[0],
+            utc.min[0],
+            utc.sec[0],
+            Calendar::Gregorian,
+        )
+        .unwrap()
+        .julian_day_ut
+    }
+
+    #[test]
+    fn test_concurrent_ayanamshas() {
+        let tjd = test_jd();
+        let lahiri = Ephemeris::new().unwrap().sidereal(Ayanamsha::Lahiri);
+        let fagan = lahiri.clone().sidereal(Ayanamsha::FaganBradley);
+        let expected_lahiri = lahiri
+            .calc_ut(tjd, Bodies::Sun, CalculationFlags::empty())
+            .unwrap()
+            .longitude;
+        let expected_fagan = fagan
+            .calc_ut(tjd, Bodies::Sun, CalculationFlags::empty())
+            .unwrap()
+            .longitude;
+        assert!((expected_lahiri - expected_fagan).abs() > 0.5);
+        let flags = CalculationFlags::empty();
+        let difference = fagan.ayanamsha(tjd, flags).unwrap().ayanamsha
+            - lahiri.ayanamsha(tjd, flags).unwrap().ayanamsha;
+        assert!((difference - (expected_lahiri - expected_fagan)).abs() < 1e-6);
+        let tropical = Ephemeris::new().unwrap();
+        assert!(tropical.ayanamsha(tjd, flags).is_err());
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let (ephemeris, expected) = if i % 2 == 0 {
+                    (lahiri.clone(), expected_lahiri)
+                } else {
+                    (fagan.clone(), expected_fagan)
+                };
+                thread::spawn(move || {
+                    for _ in 0..50 {
+                        let sun = ephemeris
+                            .calc_ut(
+                                tjd,
+                                Bodies::Sun,
+                                CalculationFlags::empty(),
+                            )
+                            .unwrap();
+                        assert_eq!(sun.longitude, expected);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_topocentric_moon() {
+        let tjd = test_jd();
+        let geocentric = Ephemeris::new().unwrap();
+        let topocentric = geocentric
+            .clone()
+            .topo(Observer::new(43.084128, 25.5919228, 265.0));
+
+        let moon = geocentric
+            .calc_ut(tjd, Bodies::Moon, CalculationFlags::empty())
+            .unwrap();
+        let topo_moon = topocentric
+            .calc_ut(tjd, Bodies::Moon, CalculationFlags::empty())
+            .unwrap();
+        // the lunar parallax is up to about one degree
+        let parallax = degnorm(moon.longitude - topo_moon.longitude);
+        let parallax = parallax.min(360.0 - parallax);
+        assert!(parallax > 0.01 && parallax < 1.1);
+        assert_eq!(
+            topocentric.flags(CalculationFlags::empty()),
+            CalculationFlags::TOPOCENTRIC_POSITIONS
+        );
+    }
+
+    #[test]
+    fn test_nested_calls() {
+        let tjd = test_jd();
+        let tropical = Ephemeris::new().unwrap();
+        let lahiri = tropical.clone().sidereal(Ayanamsha::Lahiri);
+
+        let (sun, sidereal_sun) = tropical
+            .with(|| {
+                let sidereal_sun = lahiri.calc_ut(
+                    tjd,
+                    Bodies::Sun,
+                    CalculationFlags::empty(),
+                )?;
+                Ok((
+                    tropical.calc_ut(
+                        tjd,
+                        Bodies::Sun,
+                        CalculationFlags::empty(),
+                    )?,
+                    sidereal_sun,
+                ))
+            })
+            .unwrap();
+        let ayanamsha = degnorm(sun.longitude - sidereal_sun.longitude);
+        // Lahiri ayanamsha is about 24° in 2024
+        assert!((ayanamsha - 24.2).abs() < 0.1);
+        assert_eq!(sidereal_sun.warning, None);
+    }
+
+    #[test]
+    fn test_settings_restored_after_nested_calls() {
+        let tjd = test_jd();
+        let lahiri = Ephemeris::new().unwrap().sidereal(Ayanamsha::Lahiri);
+        let fagan = lahiri.clone().sidereal(Ayanamsha::FaganBradley);
+        let flags = CalculationFlags::SIDEREAL_POSITIONS;
+
+        let (before, after) = lahiri
+            .with(|| {
+                let before = calc_ut(tjd, Bodies::Sun, flags)?;
+                fagan.calc_ut(tjd, Bodies::Sun, flags)?;
+                // a tropical scope does not see the enclosing sidereal mode
+                assert!(with_sidereal_mode(None, || {
+                    calc_ut(tjd, Bodies::Sun, flags)
+                })
+                .is_err());
+                Ok((before, calc_ut(tjd, Bodies::Sun, flags)?))
+            })
+            .unwrap();
+        assert_eq!(before, after);
+        // nor does any calculation once the lock is released
+        assert!(calc_ut(tjd, Bodies::Sun, flags).is_err());
+        let flags = CalculationFlags::TOPOCENTRIC_POSITIONS;
+        assert!(calc_ut(tjd, Bodies::Moon, flags).is_err());
+    }
+
+    #[test]
+    fn test_delta_t_reset_when_released() {
+        let j2000 = 2451545.0;
+        let computed = crate::swerust::handler_swe08::deltat(j2000);
+        let fixed = Ephemeris::new().unwrap().delta_t(100.0 / 86400.0);
+        let delta_t = fixed
+            .deltat_ex(j2000, CalculationFlags::SWISS_EPHEMERIS)
+            .unwrap();
+        assert!((delta_t.seconds() - 100.0).abs() < 1e-6);
+
+        let sun = fixed
+            .with(|| {
+                // a nested handle without Delta T uses the computed one
+                let computed = Ephemeris::new()
+                    .unwrap()
+                    .deltat_ex(j2000, CalculationFlags::SWISS_EPHEMERIS)?;
+                assert!((computed.seconds() - 63.8).abs() < 0.5);
+                calc_ut(j2000, Bodies::Sun, CalculationFlags::empty())
+            })
+            .unwrap();
+        assert_eq!(crate::swerust::handler_swe08::deltat(j2000), computed);
+        let tropical = Ephemeris::new().unwrap();
+        let computed_sun = tropical
+            .calc_ut(j2000, Bodies::Sun, CalculationFlags::empty())
+            .unwrap();
+        // the Sun moves about 1.5" in the 36 s of difference
+        assert!((sun.longitude - computed_sun.longitude).abs() > 1e-4);
+    }
+
+    #[test]
+    fn test_files_set_again_on_another_thread() {
+        let tjd = test_jd();
+        let ephemeris = Ephemeris::new().unwrap();
+        ephemeris
+            .calc_ut(tjd, Bodies::Sun, CalculationFlags::empty())
+            .unwrap();
+
+        // a new thread has not set the files yet, even where the C library
+        // keeps them per thread
+        let other = ephemeris.clone();
+        let sun = thread::spawn(move || {
+            other.calc_ut(tjd, Bodies::Sun, CalculationFlags::empty())
+        })
+        .join()
+        .unwrap()
+        .unwrap();
+        assert!(sun.flags.contains(CalculationFlags::SWISS_EPHEMERIS));
+        assert_eq!(sun.warning, None);
+    }
+
+    #[test]
+    fn test_invalid_path() {
+        let path = Path::new("/nonexistent/ephemeris");
+        assert!(matches!(
+            Ephemeris::with_path(path),
+            Err(EphemerisError::PathDoesNotExist(_))
+        ));
+    }
+}
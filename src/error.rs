@@ -0,0 +1,284 @@
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::c_char;
+use thiserror::Error;
+
+use crate::constants::CalculationFlags;
+use crate::sweconst::HouseSystem;
+
+/*
+ * Errors and warnings
+ *
+ * The functions of the C library return a status (or the flags actually used
+ * for a calculation) and a message in serr. A negative status is turned into
+ * a SweError, classified from the status and the message. A message returned together with a
+ * valid result is a SweWarning and is kept next to the result, for example
+ * when the Moshier ephemeris was used because a Swiss Ephemeris file is
+ * missing, or Porphyry houses were computed within the polar circle.
+ */
+
+/// Errors returned by the Swiss Ephemeris functions
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SweError {
+    #[error("Invalid body: {0}")]
+    InvalidBody(String),
+
+    #[error("Date out of range: {0}")]
+    DateOutOfRange(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("Invalid UTF-8 string returned by the C library: {0}")]
+    InvalidString(String),
+
+    #[error("Swiss ephemeris error: {0}")]
+    Swe(String),
+}
+
+/// Status of the C functions that found no event (swe_rise_trans and the
+/// heliacal functions), every other failure returns ERR (-1)
+const NOT_FOUND: i32 = -2;
+
+/// Variant of SweError holding a message
+type Variant = fn(String) -> SweError;
+
+/// Phrases of the messages of the C library (sweph.c, swemplan.c, swemmoon.c,
+/// swejpl.c, swecl.c, swehel.c) for failures with a variant of their own, as
+/// they share the status ERR. Date ranges come first, a message may also tell
+/// about an ephemeris file not found before falling back to Moshier.
+const MESSAGES: [(&str, Variant); 10] = [
+    ("illegal planet number", SweError::InvalidBody),
+    ("are not implemented", SweError::InvalidBody),
+    ("outside JPL eph. range", SweError::DateOutOfRange),
+    ("outside Moshier planet range", SweError::DateOutOfRange),
+    ("outside Moshier's Moon range", SweError::DateOutOfRange),
+    ("is beyond range of file", SweError::DateOutOfRange),
+    ("restricted to JD", SweError::DateOutOfRange),
+    ("could not find star name", SweError::NotFound),
+    ("did not match", SweError::NotFound),
+    ("not found", SweError::NotFound),
+];
+
+impl SweError {
+    /// Error of a failed call of the C library from its negative status and
+    /// message
+    pub fn from_status(status: i32, serr: &str) -> SweError {
+        if status == NOT_FOUND {
+            let message = serr.trim();
+            return SweError::NotFound(if message.is_empty() {
+                "not found".to_string()
+            } else {
+                message.to_string()
+            });
+        }
+        SweError::from_serr(serr)
+    }
+
+    /// Error of a failed call of the C library returning ERR, classified from
+    /// its message
+    pub fn from_serr(serr: &str) -> SweError {
+        let message = serr.trim().to_string();
+        if message.is_empty() {
+            return SweError::Swe("unknown error".to_string());
+        }
+        match MESSAGES.iter().find(|(phrase, _)| message.contains(phrase)) {
+            Some((_, error)) => error(message),
+            None => SweError::Swe(message),
+        }
+    }
+}
+
+/// Ephemeris used for a calculation
+#[derive(Debug, Clone, Copy, PartialEq, Display)]
+pub enum EphemerisSource {
+    Jpl,
+    Swiss,
+    Moshier,
+}
+
+impl EphemerisSource {
+    /// Ephemeris selected by calculation flags, Swiss when none is set
+    pub fn from_flags(iflag: CalculationFlags) -> EphemerisSource {
+        if iflag.contains(CalculationFlags::JPL_EPHEMERIS) {
+            EphemerisSource::Jpl
+        } else if iflag.contains(CalculationFlags::MOSHIER_EPHEMERIS) {
+            EphemerisSource::Moshier
+        } else {
+            EphemerisSource::Swiss
+        }
+    }
+}
+
+/// Message returned by the C library together with a valid result
+#[derive(Debug, Clone, PartialEq)]
+pub enum SweWarning {
+    /// An ephemeris file was not found, another ephemeris was used instead
+    EphemerisFallback {
+        requested: EphemerisSource,
+        used: EphemerisSource,
+        message: String,
+    },
+    /// The house system cannot be computed at the latitude, Porphyry houses
+    /// were computed instead
+    HouseSystemFallback {
+        requested: HouseSystem,
+        used: HouseSystem,
+    },
+    Message(String),
+}
+
+impl fmt::Display for SweWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SweWarning::EphemerisFallback {
+                requested,
+                used,
+                message,
+            } => {
+                write!(f, "{} used instead of {}: {}", used, requested, message)
+            },
+            SweWarning::HouseSystemFallback { requested, used } => write!(
+                f,
+                "house system {} used instead of {} within the polar circle",
+                used, requested
+            ),
+            SweWarning::Message(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Message of a serr buffer filled by the C library
+pub(crate) fn serr_message(serr: &[c_char]) -> Result<String, SweError> {
+    c_string(unsafe { CStr::from_ptr(serr.as_ptr()) })
+}
+
+/// String returned by the C library
+pub(crate) fn c_string(s: &CStr) -> Result<String, SweError> {
+    s.to_str()
+        .map(|s| s.to_string())
+        .map_err(|_| SweError::InvalidString(s.to_string_lossy().into_owned()))
+}
+
+/// Warning of a successful calculation with the flags requested and the ones
+/// returned, None when serr is empty
+pub(crate) fn warning(
+    iflag: CalculationFlags,
+    retflag: CalculationFlags,
+    serr: &str,
+) -> Option<SweWarning> {
+    if serr.trim().is_empty() {
+        return None;
+    }
+    let requested = EphemerisSource::from_flags(iflag);
+    let used = EphemerisSource::from_flags(retflag);
+    let message = serr.trim().to_string();
+    if requested != used {
+        Some(SweWarning::EphemerisFallback {
+            requested,
+            used,
+            message,
+        })
+    } else {
+        Some(SweWarning::Message(message))
+    }
+}
+
+/// Result of a calculation returning its flags, or a negative status on
+/// error, with the warning of its message if any
+pub(crate) fn check_flags(
+    iflag: CalculationFlags,
+    retflag: i32,
+    serr: &[c_char],
+) -> Result<Option<SweWarning>, SweError> {
+    let message = serr_message(serr)?;
+    if retflag < 0 {
+        return Err(SweError::from_status(retflag, &message));
+    }
+    Ok(warning(
+        iflag,
+        CalculationFlags::from_status(retflag),
+        &message,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_serr() {
+        assert!(matches!(
+            SweError::from_serr("illegal planet number 99."),
+            SweError::InvalidBody(_)
+        ));
+        assert!(matches!(
+            SweError::from_serr(
+                "jd 99999999.000000 outside Moshier planet range \
+                 -3000000.50 .. 3000000.50 "
+            ),
+            SweError::DateOutOfRange(_)
+        ));
+        assert!(matches!(
+            SweError::from_serr("star Foo not found"),
+            SweError::NotFound(_)
+        ));
+        assert!(matches!(
+            SweError::from_serr(
+                "SwissEph file 'seas_18.se1' not found in PATH '.'\njd \
+                 99999999.000000 outside Moshier planet range"
+            ),
+            SweError::DateOutOfRange(_)
+        ));
+        // not a date range nor a limit of the ephemeris
+        assert!(matches!(
+            SweError::from_serr(
+                "it makes no sense to call swe_vis_limit_mag() for the Sun"
+            ),
+            SweError::Swe(_)
+        ));
+        assert!(matches!(
+            SweError::from_serr(
+                "The provided Meteorological range is too long"
+            ),
+            SweError::Swe(_)
+        ));
+        assert_eq!(
+            SweError::from_serr(""),
+            SweError::Swe("unknown error".to_string())
+        );
+        assert!(matches!(
+            SweError::from_status(-2, ""),
+            SweError::NotFound(_)
+        ));
+    }
+
+    #[test]
+    fn test_ephemeris_fallback_warning() {
+        let w = warning(
+            CalculationFlags::SWISS_EPHEMERIS,
+            CalculationFlags::MOSHIER_EPHEMERIS,
+            "SwissEph file 'sepl_18.se1' not found in PATH",
+        );
+        assert_eq!(
+            w,
+            Some(SweWarning::EphemerisFallback {
+                requested: EphemerisSource::Swiss,
+                used: EphemerisSource::Moshier,
+                message: "SwissEph file 'sepl_18.se1' not found in PATH"
+                    .to_string(),
+            })
+        );
+        assert_eq!(
+            warning(
+                CalculationFlags::empty(),
+                CalculationFlags::SWISS_EPHEMERIS,
+                ""
+            ),
+            None
+        );
+    }
+}
@@ -17,6 +17,8 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Mutex, Once};
 use thiserror::Error;
 
+use crate::ephemeris::exclusive;
+use crate::error::SweError;
 use crate::swerust::handler_swe02::{set_ephe_path, version};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -48,6 +50,9 @@ pub enum EphemerisError {
     #[error("Failed to write file {0:?}: {1}")]
     FileWriteFailed(PathBuf, #[source] std::io::Error),
 
+    #[error("Swiss ephemeris error: {0}")]
+    Swe(#[from] SweError),
+
     #[error("Other IO error: {0}")]
     Io(#[from] std::io::Error),
 }
@@ -100,6 +105,11 @@ pub fn ensure_ephemeris_initialized() -> Result<()> {
     Ok(())
 }
 
+/// Ephemeris directory resolved by ensure_ephemeris_initialized, if any
+pub(crate) fn ephemeris_path() -> Option<PathBuf> {
+    EPHEMERIS_PATH.lock().unwrap().clone()
+}
+
 /// Performs the actual path resolution / extraction / validation (called only once).
 fn perform_extraction_and_validation() -> Result<()> {
     // ------------------------------------------------------------------
@@ -197,7 +207,7 @@ fn resolve_user_ephemeris_path() -> Option<PathBuf> {
 }
 
 /// Validates that the ephemeris directory contains the required files.
-fn validate_ephemeris_directory(path: &Path) -> Result<()> {
+pub(crate) fn validate_ephemeris_directory(path: &Path) -> Result<()> {
     if !path.exists() {
         eprintln!("[DEBUG] Ephemeris path does not exist: {:?}", path);
         return Err(EphemerisError::PathDoesNotExist(path.to_path_buf()));
@@ -209,24 +219,25 @@ fn validate_ephemeris_directory(path: &Path) -> Result<()> {
 
     eprintln!("[DEBUG] Validating ephemeris directory: {:?}", path);
 
-    for entry in fs::read_dir(path).map_err(|e| {
-        EphemerisError::DirectoryReadFailed(path.to_path_buf(), e)
-    })? {
-        if let Ok(entry) = entry {
-            let p = entry.path();
-
-            if let Some(ext) = p.extension().and_then(|e| e.to_str()) {
-                match ext {
-                    "se1" => {
-                        has_se1_files = true;
-                        eprintln!("[DEBUG] Found .se1 file: {:?}", p);
-                    },
-                    "txt" => {
-                        has_txt_files = true;
-                        eprintln!("[DEBUG] Found .txt file: {:?}", p);
-                    },
-                    _ => {},
-                }
+    for entry in fs::read_dir(path)
+        .map_err(|e| {
+            EphemerisError::DirectoryReadFailed(path.to_path_buf(), e)
+        })?
+        .flatten()
+    {
+        let p = entry.path();
+
+        if let Some(ext) = p.extension().and_then(|e| e.to_str()) {
+            match ext {
+                "se1" => {
+                    has_se1_files = true;
+                    eprintln!("[DEBUG] Found .se1 file: {:?}", p);
+                },
+                "txt" => {
+                    has_txt_files = true;
+                    eprintln!("[DEBUG] Found .txt file: {:?}", p);
+                },
+                _ => {},
             }
         }
     }
@@ -251,10 +262,12 @@ fn set_ephemeris_path_only(path: &Path) -> Result<()> {
         .ok_or_else(|| EphemerisError::InvalidPath(path.to_path_buf()))?;
 
     eprintln!("[DEBUG] Setting ephemeris path to: {:?}", path_str);
-    set_ephe_path(path_str);
+    let found_version = exclusive(|| {
+        set_ephe_path(path_str)?;
+        version()
+    })?;
 
     // Validate version
-    let found_version = version();
     let expected_version = "2.10.03";
     if found_version != expected_version {
         return Err(EphemerisError::VersionMismatch {
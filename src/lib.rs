@@ -1,9 +1,21 @@
 extern crate strum;
 #[macro_use]
 extern crate strum_macros;
+#[macro_use]
+extern crate bitflags;
+pub mod aspects;
+pub mod chart;
 pub mod constants;
+pub mod ephemeris;
+pub mod error;
 pub mod init_ephemeris;
+pub mod progressions;
 mod raw;
+pub mod returns;
+pub mod stations;
 pub mod sweconst;
 pub mod swerust;
+pub mod transits;
+pub use ephemeris::Ephemeris;
+pub use error::{SweError, SweWarning};
 pub use init_ephemeris::ensure_ephemeris_initialized;
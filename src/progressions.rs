@@ -0,0 +1,450 @@
+use serde::{Deserialize, Serialize};
+
+use crate::chart::{Chart, DEFAULT_BODIES};
+use crate::constants::{Ayanamsha, CalculationFlags};
+use crate::ephemeris::with_sidereal_mode;
+use crate::error::SweError;
+use crate::sweconst::{Bodies, HouseSystem, Object};
+use crate::swerust::handler_swe03::{
+    calc_ut, fortuna_part, get_ayanamsa_ex_ut, CalcUtResult,
+};
+use crate::swerust::handler_swe14::{houses_armc, houses_with_flag};
+use crate::swerust::handler_swe17::coordinates::{cotrans, true_obliquity};
+use crate::swerust::handler_swe17::degnorm;
+
+/*
+ * Secondary progressions and solar arc directions
+ *
+ * Secondary progressions take the positions of the day after birth that
+ * corresponds to each year of life: the progressed date is the natal date
+ * plus one day per tropical year elapsed until the target date.
+ *
+ * The solar arc is the distance travelled by the progressed Sun since birth.
+ * Solar arc directions add it to every natal position.
+ *
+ * The progressed MC is moved by the chosen McProgression, then the progressed
+ * Ascendant is computed with houses_armc at the natal latitude.
+ */
+
+/// Length of the tropical year in days, one day of progression per year
+pub const TROPICAL_YEAR: f64 = 365.242_19;
+
+/// Mean daily motion of the Sun (0°59'08.33"), the Naibod rate per year
+pub const NAIBOD_RATE: f64 = 0.985_647_36;
+
+/// Method used to move the MC of a progressed chart
+#[derive(
+    Debug, Clone, Copy, PartialEq, Display, EnumIter, Serialize, Deserialize,
+)]
+pub enum McProgression {
+    /// The MC advances by the solar arc in longitude
+    SolarArc,
+    /// The ARMC advances by the Naibod rate in right ascension
+    Naibod,
+    /// The MC advances by the mean motion of the Sun in longitude
+    MeanSun,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Progression {
+    pub natal_julian_day_ut: f64,
+    pub target_julian_day_ut: f64,
+    pub progressed_julian_day_ut: f64,
+    pub age: f64, // tropical years from the natal to the target date
+    pub house_system: HouseSystem,
+    pub ayanamsha: Option<Ayanamsha>,
+    pub mc_progression: McProgression,
+    pub solar_arc: f64,
+    pub ascendant: f64, // progressed
+    pub midheaven: f64, // progressed
+    pub directed_ascendant: f64,
+    pub directed_midheaven: f64,
+    pub progressed: Vec<Object>,
+    pub directed: Vec<Object>, // speed is the rate of the solar arc
+}
+
+impl Progression {
+    /// Secondary progressed position of a body, if it is part of the
+    /// progression
+    pub fn progressed(&self, body: Bodies) -> Option<&Object> {
+        self.progressed.iter().find(|o| o.object_enum == body)
+    }
+
+    /// Solar arc directed position of a body, if it is part of the
+    /// progression
+    pub fn directed(&self, body: Bodies) -> Option<&Object> {
+        self.directed.iter().find(|o| o.object_enum == body)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProgressionBuilder {
+    natal_julian_day_ut: f64,
+    target_julian_day_ut: f64,
+    latitude: f64,
+    longitude: f64,
+    house_system: HouseSystem,
+    ayanamsha: Option<Ayanamsha>,
+    mc_progression: McProgression,
+    bodies: Vec<Bodies>,
+}
+
+impl ProgressionBuilder {
+    /// Natal and target julian days (UT) and the natal geographic position.
+    /// Placidus houses, tropical zodiac, MC progressed by solar arc.
+    pub fn new(
+        natal_julian_day_ut: f64,
+        target_julian_day_ut: f64,
+        latitude: f64,
+        longitude: f64,
+    ) -> ProgressionBuilder {
+        ProgressionBuilder {
+            natal_julian_day_ut,
+            target_julian_day_ut,
+            latitude,
+            longitude,
+            house_system: HouseSystem::Placidus,
+            ayanamsha: None,
+            mc_progression: McProgression::SolarArc,
+            bodies: DEFAULT_BODIES.to_vec(),
+        }
+    }
+
+    /// Progression of a natal chart, with its place, house system,
+    /// ayanamsha and bodies
+    pub fn from_chart(
+        chart: &Chart,
+        target_julian_day_ut: f64,
+    ) -> ProgressionBuilder {
+        ProgressionBuilder {
+            natal_julian_day_ut: chart.julian_day_ut,
+            target_julian_day_ut,
+            latitude: chart.latitude,
+            longitude: chart.longitude,
+            house_system: chart.house_system,
+            ayanamsha: chart.ayanamsha,
+            mc_progression: McProgression::SolarArc,
+            bodies: chart.objects.iter().map(|o| o.object_enum).collect(),
+        }
+    }
+
+    /// House system
+    pub fn house_system(mut self, hsys: HouseSystem) -> Self {
+        self.house_system = hsys;
+        self
+    }
+
+    /// Sidereal zodiac with an ayanamsha
+    pub fn ayanamsha(mut self, ayanamsha: Ayanamsha) -> Self {
+        self.ayanamsha = Some(ayanamsha);
+        self
+    }
+
+    pub fn mc_progression(mut self, method: McProgression) -> Self {
+        self.mc_progression = method;
+        self
+    }
+
+    pub fn bodies(mut self, bodies: &[Bodies]) -> Self {
+        self.bodies = bodies.to_vec();
+        self
+    }
+
+    pub fn build(&self) -> Result<Progression, SweError> {
+        if self.house_system == HouseSystem::GauquelinSectors {
+            return Err(SweError::InvalidArgument(
+                "Gauquelin sectors are not supported in a progression"
+                    .to_string(),
+            ));
+        }
+        // the sidereal mode stays set for the whole progression
+        with_sidereal_mode(self.ayanamsha, || self.progress())
+    }
+
+    fn progress(&self) -> Result<Progression, SweError> {
+        let natal_jd = self.natal_julian_day_ut;
+        let age = (self.target_julian_day_ut - natal_jd) / TROPICAL_YEAR;
+        let progressed_jd = natal_jd + age;
+
+        let mut iflag = CalculationFlags::SPEED_PRECISION;
+        if self.ayanamsha.is_some() {
+            iflag |= CalculationFlags::SIDEREAL_POSITIONS;
+        }
+
+        let natal_houses = houses_with_flag(
+            natal_jd,
+            iflag,
+            self.latitude,
+            self.longitude,
+            self.house_system,
+        )?;
+        let natal_ascendant = natal_houses.ascmc[0];
+        let natal_midheaven = natal_houses.ascmc[1];
+        let natal_armc = natal_houses.ascmc[2];
+
+        let natal_sun = calc_ut(natal_jd, Bodies::Sun, iflag)?;
+        let progressed_sun = calc_ut(progressed_jd, Bodies::Sun, iflag)?;
+        let solar_arc = arc(natal_sun.longitude, progressed_sun.longitude, age);
+
+        // the angles are computed in the tropical zodiac from the ARMC
+        let eps = true_obliquity(progressed_jd)?;
+        let progressed_ayanamsha = match self.ayanamsha {
+            Some(_) => ayanamsha(progressed_jd, iflag)?,
+            None => 0.0,
+        };
+        let armc = match self.mc_progression {
+            McProgression::Naibod => degnorm(natal_armc + age * NAIBOD_RATE),
+            McProgression::SolarArc => {
+                armc_of(natal_midheaven + solar_arc + progressed_ayanamsha, eps)
+            },
+            McProgression::MeanSun => armc_of(
+                natal_midheaven + age * NAIBOD_RATE + progressed_ayanamsha,
+                eps,
+            ),
+        };
+        let progressed_houses =
+            houses_armc(armc, self.latitude, eps, self.house_system)?;
+        let ascendant =
+            degnorm(progressed_houses.ascmc[0] - progressed_ayanamsha);
+        let midheaven =
+            degnorm(progressed_houses.ascmc[1] - progressed_ayanamsha);
+
+        let progressed_moon = calc_ut(progressed_jd, Bodies::Moon, iflag)?;
+        let natal_moon = calc_ut(natal_jd, Bodies::Moon, iflag)?;
+        let mut progressed = Vec::new();
+        let mut directed = Vec::new();
+        for &body in &self.bodies {
+            let (position, natal) = match body {
+                Bodies::FortunaPart => (
+                    fortuna_part(
+                        progressed_jd,
+                        ascendant,
+                        armc,
+                        self.latitude,
+                        &progressed_sun,
+                        &progressed_moon,
+                        iflag,
+                    )?,
+                    fortuna_part(
+                        natal_jd,
+                        natal_ascendant,
+                        natal_armc,
+                        self.latitude,
+                        &natal_sun,
+                        &natal_moon,
+                        iflag,
+                    )?,
+                ),
+                _ => (
+                    calc_ut(progressed_jd, body, iflag)?,
+                    calc_ut(natal_jd, body, iflag)?,
+                ),
+            };
+            progressed.push(object(body, &position, position.speed_longitude));
+            let mut natal = natal;
+            natal.longitude = degnorm(natal.longitude + solar_arc);
+            directed.push(object(body, &natal, progressed_sun.speed_longitude));
+        }
+
+        Ok(Progression {
+            natal_julian_day_ut: natal_jd,
+            target_julian_day_ut: self.target_julian_day_ut,
+            progressed_julian_day_ut: progressed_jd,
+            age,
+            house_system: self.house_system,
+            ayanamsha: self.ayanamsha,
+            mc_progression: self.mc_progression,
+            solar_arc,
+            ascendant,
+            midheaven,
+            directed_ascendant: degnorm(natal_ascendant + solar_arc),
+            directed_midheaven: degnorm(natal_midheaven + solar_arc),
+            progressed,
+            directed,
+        })
+    }
+}
+
+/// Distance travelled by the Sun from natal to progressed, negative for a
+/// target date before birth
+fn arc(natal: f64, progressed: f64, age: f64) -> f64 {
+    let arc = degnorm(progressed - natal);
+    if age < 0.0 && arc > 0.0 {
+        arc - 360.0
+    } else {
+        arc
+    }
+}
+
+/// Right ascension of a point of the ecliptic
+fn armc_of(longitude: f64, eps: f64) -> f64 {
+    degnorm(cotrans([degnorm(longitude), 0.0, 1.0], -eps)[0])
+}
+
+/// True ayanamsha at a date, the distance from the tropical to the sidereal
+/// positions computed with iflag
+fn ayanamsha(tjd_ut: f64, iflag: CalculationFlags) -> Result<f64, SweError> {
+    let tropical = iflag - CalculationFlags::SIDEREAL_POSITIONS;
+    Ok(get_ayanamsa_ex_ut(tjd_ut, tropical)?.ayanamsha)
+}
+
+fn object(body: Bodies, position: &CalcUtResult, speed: f64) -> Object {
+    Object::new(
+        body,
+        body.into(),
+        body.object_type(),
+        position.longitude,
+        position.latitude,
+        speed,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::ChartBuilder;
+    use crate::ensure_ephemeris_initialized;
+
+    fn natal() -> Chart {
+        ChartBuilder::new(1990, 5, 15, 8, 30, 0.0, 3.0, 43.084128, 25.5919228)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_secondary_progression() {
+        let _ = ensure_ephemeris_initialized();
+        let chart = natal();
+        // thirty years later
+        let target = chart.julian_day_ut + 30.0 * TROPICAL_YEAR;
+        let progression = ProgressionBuilder::from_chart(&chart, target)
+            .build()
+            .unwrap();
+
+        assert!((progression.age - 30.0).abs() < 1e-9);
+        assert!(
+            (progression.progressed_julian_day_ut - chart.julian_day_ut - 30.0)
+                .abs()
+                < 1e-9
+        );
+        assert_eq!(progression.progressed.len(), chart.objects.len());
+        // the Sun moves about a degree a day
+        assert!(progression.solar_arc > 28.0 && progression.solar_arc < 31.0);
+
+        let sun = progression.progressed(Bodies::Sun).unwrap();
+        let natal_sun = &chart.objects[0];
+        assert!(
+            (degnorm(natal_sun.longitude + progression.solar_arc)
+                - sun.longitude)
+                .abs()
+                < 1e-9
+        );
+        // solar arc directions move every point by the same arc
+        let mars = progression.directed(Bodies::Mars).unwrap();
+        let natal_mars = chart
+            .objects
+            .iter()
+            .find(|o| o.object_enum == Bodies::Mars)
+            .unwrap();
+        let arc = degnorm(mars.longitude - natal_mars.longitude);
+        assert!((arc - progression.solar_arc).abs() < 1e-9);
+
+        // with the solar arc method the progressed MC is the directed MC
+        assert!(
+            (progression.midheaven - progression.directed_midheaven).abs()
+                < 1e-6
+        );
+    }
+
+    #[test]
+    fn test_mc_progression_methods() {
+        let _ = ensure_ephemeris_initialized();
+        let chart = natal();
+        let target = chart.julian_day_ut + 40.0 * TROPICAL_YEAR;
+        let builder = ProgressionBuilder::from_chart(&chart, target);
+
+        let naibod = builder
+            .clone()
+            .mc_progression(McProgression::Naibod)
+            .build()
+            .unwrap();
+        let mean_sun = builder
+            .clone()
+            .mc_progression(McProgression::MeanSun)
+            .build()
+            .unwrap();
+        let solar_arc = builder.build().unwrap();
+
+        let expected = degnorm(chart.midheaven + 40.0 * NAIBOD_RATE);
+        assert!((mean_sun.midheaven - expected).abs() < 1e-6);
+        // the three methods differ by less than a few degrees after 40 years
+        for progression in &[&naibod, &solar_arc] {
+            let d = degnorm(progression.midheaven - mean_sun.midheaven);
+            assert!(d.min(360.0 - d) < 5.0);
+        }
+        assert_ne!(naibod.midheaven, mean_sun.midheaven);
+        assert_ne!(naibod.ascendant, chart.ascendant);
+    }
+
+    #[test]
+    fn test_directed_fortuna_matches_chart() {
+        let _ = ensure_ephemeris_initialized();
+        // a day and a night birth
+        for &hour in &[8, 22] {
+            let chart = ChartBuilder::new(
+                1990, 5, 15, hour, 30, 0.0, 3.0, 43.084128, 25.5919228,
+            )
+            .bodies(&[Bodies::Sun, Bodies::Moon, Bodies::FortunaPart])
+            .build()
+            .unwrap();
+            let target = chart.julian_day_ut + 30.0 * TROPICAL_YEAR;
+            let progression = ProgressionBuilder::from_chart(&chart, target)
+                .build()
+                .unwrap();
+
+            let natal = chart.objects[2].longitude;
+            let directed =
+                progression.directed(Bodies::FortunaPart).unwrap().longitude;
+            let arc = degnorm(directed - natal);
+            assert!((arc - progression.solar_arc).abs() < 1e-9, "{}", hour);
+        }
+    }
+
+    #[test]
+    fn test_sidereal_progression() {
+        let _ = ensure_ephemeris_initialized();
+        let natal_jd = natal().julian_day_ut;
+        let target = natal_jd + 20.0 * TROPICAL_YEAR;
+        let tropical =
+            ProgressionBuilder::new(natal_jd, target, 43.084128, 25.5919228)
+                .bodies(&[Bodies::Sun, Bodies::FortunaPart])
+                .build()
+                .unwrap();
+        let sidereal =
+            ProgressionBuilder::new(natal_jd, target, 43.084128, 25.5919228)
+                .bodies(&[Bodies::Sun, Bodies::FortunaPart])
+                .ayanamsha(Ayanamsha::Lahiri)
+                .build()
+                .unwrap();
+
+        // Lahiri ayanamsha is about 23.7° in 1990
+        let sun_offset = degnorm(
+            tropical.progressed(Bodies::Sun).unwrap().longitude
+                - sidereal.progressed(Bodies::Sun).unwrap().longitude,
+        );
+        let asc_offset = degnorm(tropical.ascendant - sidereal.ascendant);
+        assert!((sun_offset - 23.7).abs() < 0.1);
+        assert!((asc_offset - sun_offset).abs() < 1e-6);
+        assert!(
+            (sidereal.midheaven - sidereal.directed_midheaven).abs() < 1e-6
+        );
+        // the Part of Fortune keeps its distance to the Ascendant
+        let fortuna = |p: &Progression| {
+            degnorm(
+                p.progressed(Bodies::FortunaPart).unwrap().longitude
+                    - p.ascendant,
+            )
+        };
+        assert!((fortuna(&tropical) - fortuna(&sidereal)).abs() < 1e-6);
+    }
+}
@@ -85,6 +85,235 @@ extern "C" {
         serr: *mut c_char,
     ) -> c_int;
 
+    /// int32 swe_sol_eclipse_when_glob(
+    ///     double tjd_start,    /* start date for search, Jul. day UT */
+    ///     int32 ifl,           /* ephemeris flag */
+    ///     int32 ifltype,       /* eclipse type wanted: SE_ECL_TOTAL etc. or 0, if any eclipse type */
+    ///     double *tret,        /* return array, 10 doubles, see below */
+    ///     int32 backward,      /* TRUE, if backward search */
+    ///     char *serr);         /* return error string */
+    ///
+    /// tret[0] time of maximum eclipse
+    /// tret[1] time, when eclipse takes place at local apparent noon
+    /// tret[2] time of eclipse begin
+    /// tret[3] time of eclipse end
+    /// tret[4] time of totality begin
+    /// tret[5] time of totality end
+    /// tret[6] time of center line begin
+    /// tret[7] time of center line end
+    pub fn swe_sol_eclipse_when_glob(
+        tjd_start: c_double,
+        ifl: c_int,
+        ifltype: c_int,
+        tret: *mut c_double,
+        backward: c_int,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// int32 swe_sol_eclipse_when_loc(
+    ///     double tjd_start,    /* start date for search, Jul. day UT */
+    ///     int32 ifl,           /* ephemeris flag */
+    ///     double *geopos,      /* 3 doubles for geographic lon, lat, height.
+    ///                           * eastern longitude is positive,
+    ///                           * western longitude is negative,
+    ///                           * northern latitude is positive,
+    ///                           * southern latitude is negative */
+    ///     double *tret,        /* return array, 10 doubles, see below */
+    ///     double *attr,        /* return array, 20 doubles, see below */
+    ///     int32 backward,      /* TRUE, if backward search */
+    ///     char *serr);         /* return error string */
+    ///
+    /// tret[0] time of maximum eclipse
+    /// tret[1] time of first contact
+    /// tret[2] time of second contact
+    /// tret[3] time of third contact
+    /// tret[4] time of forth contact
+    /// tret[5] time of sunrise between first and forth contact
+    /// tret[6] time of sunset between first and forth contact
+    pub fn swe_sol_eclipse_when_loc(
+        tjd_start: c_double,
+        ifl: c_int,
+        geopos: *const c_double,
+        tret: *mut c_double,
+        attr: *mut c_double,
+        backward: c_int,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// int32 swe_sol_eclipse_where(
+    ///     double tjd_ut,       /* time Jul. Day UT */
+    ///     int32 ifl,           /* ephemeris flag */
+    ///     double *geopos,      /* return array, 10 doubles, geo. long. and lat.
+    ///                           * of the central line in geopos[0] and geopos[1] */
+    ///     double *attr,        /* return array, 20 doubles, see below */
+    ///     char *serr);         /* return error string */
+    ///
+    /// attr[0] fraction of solar diameter covered by moon (magnitude)
+    /// attr[1] ratio of lunar diameter to solar one
+    /// attr[2] fraction of solar disc covered by moon (obscuration)
+    /// attr[3] diameter of core shadow in km
+    /// attr[4] azimuth of sun at tjd
+    /// attr[5] true altitude of sun above horizon at tjd
+    /// attr[6] apparent altitude of sun above horizon at tjd
+    /// attr[7] angular distance of moon from sun in degrees
+    /// attr[8] magnitude acc. to NASA
+    /// attr[9] saros series number
+    /// attr[10] saros series member number
+    pub fn swe_sol_eclipse_where(
+        tjd_ut: c_double,
+        ifl: c_int,
+        geopos: *mut c_double,
+        attr: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// int32 swe_sol_eclipse_how(
+    ///     double tjd_ut,       /* time, Jul. day UT */
+    ///     int32 ifl,           /* ephemeris flag */
+    ///     double *geopos,      /* geogr. longitude, latitude, height above sea */
+    ///     double *attr,        /* return array, 20 doubles, same as swe_sol_eclipse_where() */
+    ///     char *serr);         /* return error string */
+    pub fn swe_sol_eclipse_how(
+        tjd_ut: c_double,
+        ifl: c_int,
+        geopos: *const c_double,
+        attr: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// int32 swe_lun_eclipse_when(
+    ///     double tjd_start,    /* start date for search, Jul. day UT */
+    ///     int32 ifl,           /* ephemeris flag */
+    ///     int32 ifltype,       /* eclipse type wanted: SE_ECL_TOTAL etc. or 0, if any eclipse type */
+    ///     double *tret,        /* return array, 10 doubles, see below */
+    ///     int32 backward,      /* TRUE, if backward search */
+    ///     char *serr);         /* return error string */
+    ///
+    /// tret[0] time of maximum eclipse
+    /// tret[2] time of partial phase begin (indices consistent with solar eclipses)
+    /// tret[3] time of partial phase end
+    /// tret[4] time of totality begin
+    /// tret[5] time of totality end
+    /// tret[6] time of penumbral phase begin
+    /// tret[7] time of penumbral phase end
+    pub fn swe_lun_eclipse_when(
+        tjd_start: c_double,
+        ifl: c_int,
+        ifltype: c_int,
+        tret: *mut c_double,
+        backward: c_int,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// int32 swe_lun_eclipse_when_loc(
+    ///     double tjd_start,    /* start date for search, Jul. day UT */
+    ///     int32 ifl,           /* ephemeris flag */
+    ///     double *geopos,      /* 3 doubles for geogr. longitude, latitude, height above sea */
+    ///     double *tret,        /* return array, 10 doubles, see below */
+    ///     double *attr,        /* return array, 20 doubles, see below */
+    ///     int32 backward,      /* TRUE, if backward search */
+    ///     char *serr);         /* return error string */
+    ///
+    /// tret[0..7] same as swe_lun_eclipse_when()
+    /// tret[8] time of moonrise, if it occurs during the eclipse
+    /// tret[9] time of moonset, if it occurs during the eclipse
+    pub fn swe_lun_eclipse_when_loc(
+        tjd_start: c_double,
+        ifl: c_int,
+        geopos: *const c_double,
+        tret: *mut c_double,
+        attr: *mut c_double,
+        backward: c_int,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// int32 swe_lun_eclipse_how(
+    ///     double tjd_ut,       /* time Jul. Day UT */
+    ///     int32 ifl,           /* ephemeris flag */
+    ///     double *geopos,      /* input array, geopos, geolon, geoheight, may be NULL */
+    ///     double *attr,        /* return array, 20 doubles, see below */
+    ///     char *serr);         /* return error string */
+    ///
+    /// attr[0] umbral magnitude at tjd
+    /// attr[1] penumbral magnitude
+    /// attr[4] azimuth of moon at tjd
+    /// attr[5] true altitude of moon above horizon at tjd
+    /// attr[6] apparent altitude of moon above horizon at tjd
+    /// attr[7] distance of moon from opposition in degrees
+    /// attr[8] umbral magnitude at tjd (= attr[0])
+    /// attr[9] saros series number
+    /// attr[10] saros series member number
+    pub fn swe_lun_eclipse_how(
+        tjd_ut: c_double,
+        ifl: c_int,
+        geopos: *const c_double,
+        attr: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// void swe_azalt(
+    ///     double tjd_ut,      /* UT */
+    ///     int32 calc_flag,    /* SE_ECL2HOR or SE_EQU2HOR */
+    ///     double *geopos,     /* array of 3 doubles: geograph. long., lat., height */
+    ///     double atpress,     /* atmospheric pressure in mbar (hPa) */
+    ///     double attemp,      /* atmospheric temperature in degrees Celsius */
+    ///     double *xin,        /* array of 3 doubles: position of body in either ecliptical or equatorial coordinates,
+    ///                          * depending on calc_flag */
+    ///     double *xaz);       /* return array of 3 doubles, containing azimuth, true altitude, apparent altitude */
+    /// If atpress is 0, the pressure is estimated from geopos[2] and attemp.
+    /// The azimuth is measured from the south point, clockwise via west.
+    pub fn swe_azalt(
+        tjd_ut: c_double,
+        calc_flag: c_int,
+        geopos: *const c_double,
+        atpress: c_double,
+        attemp: c_double,
+        xin: *const c_double,
+        xaz: *mut c_double,
+    );
+
+    /// void swe_azalt_rev(
+    ///     double tjd_ut,
+    ///     int32 calc_flag,    /* either SE_HOR2ECL or SE_HOR2EQU */
+    ///     double *geopos,     /* array of 3 doubles for geograph. pos. of observer */
+    ///     double *xin,        /* array of 2 doubles for azimuth and true altitude of planet */
+    ///     double *xout);      /* return array of 2 doubles for either ecliptic or
+    ///                          * equatorial coordinates, depending on calc_flag */
+    pub fn swe_azalt_rev(
+        tjd_ut: c_double,
+        calc_flag: c_int,
+        geopos: *const c_double,
+        xin: *const c_double,
+        xout: *mut c_double,
+    );
+
+    /// double swe_refrac_extended(
+    ///     double inalt,       /* altitude of object above geometric horizon in degrees */
+    ///     double geoalt,      /* altitude of observer above sea level in meters */
+    ///     double atpress,     /* atmospheric pressure in mbar (hPa) */
+    ///     double attemp,      /* atmospheric temperature in degrees Celsius */
+    ///     double lapse_rate,  /* (dattemp/dgeoalt) = [deg K/m] */
+    ///     int32 calc_flag,    /* either SE_TRUE_TO_APP or SE_APP_TO_TRUE */
+    ///     double *dret);      /* array of 4 doubles:
+    ///                          * dret[0] true altitude, if possible; otherwise input value
+    ///                          * dret[1] apparent altitude, if possible; otherwise input value
+    ///                          * dret[2] refraction
+    ///                          * dret[3] dip of the horizon */
+    /// The body is above the horizon if dret[0] != dret[1].
+    pub fn swe_refrac_extended(
+        inalt: c_double,
+        geoalt: c_double,
+        atpress: c_double,
+        attemp: c_double,
+        lapse_rate: c_double,
+        calc_flag: c_int,
+        dret: *mut c_double,
+    ) -> c_double;
+
+    /// void swe_set_lapse_rate(double lapse_rate);
+    /// Sets the atmospheric lapse rate used by swe_azalt() (default 0.0065 K/m)
+    pub fn swe_set_lapse_rate(lapse_rate: c_double);
+
     /// Computes the times of rising, setting and meridian transits for all planets,
     /// asteroids, the moon, nd the fixed stars.
     /// Returns rising time of an object
@@ -139,6 +368,64 @@ extern "C" {
         serr: *mut i8,       // Error message buffer (if needed)
     ) -> i32; // Returns error code (0 if success)
 
+    /// int32 swe_heliacal_ut(
+    ///     double tjdstart_ut, double *geopos, double *datm, double *dobs,
+    ///     char *ObjectName, int32 TypeEvent, int32 iflag,
+    ///     double *dret, char *serr);
+    /// geopos[3]: longitude, latitude, eye height in meters
+    /// datm[4]: pressure (mbar), temperature (C), relative humidity (%),
+    /// meteorological range (km) or total extinction coefficient if < 1
+    /// dobs[6]: age, Snellen ratio, binocular, magnification, aperture (mm),
+    /// transmission; the last four only with SE_HELFLAG_OPTICAL_PARAMS
+    /// dret[0..3]: beginning, optimum and end of visibility
+    /// datm, dobs and ObjectName are modified (defaults, lower case)
+    pub fn swe_heliacal_ut(
+        tjdstart_ut: c_double,
+        geopos: *mut c_double,
+        datm: *mut c_double,
+        dobs: *mut c_double,
+        object_name: *mut c_char,
+        type_event: c_int,
+        iflag: c_int,
+        dret: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// int32 swe_heliacal_pheno_ut(
+    ///     double tjd_ut, double *geopos, double *datm, double *dobs,
+    ///     char *ObjectName, int32 TypeEvent, int32 helflag,
+    ///     double *darr, char *serr);
+    /// darr: at least 30 doubles, see swehel.c
+    pub fn swe_heliacal_pheno_ut(
+        tjd_ut: c_double,
+        geopos: *mut c_double,
+        datm: *mut c_double,
+        dobs: *mut c_double,
+        object_name: *mut c_char,
+        type_event: c_int,
+        helflag: c_int,
+        darr: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// int32 swe_vis_limit_mag(
+    ///     double tjdut, double *geopos, double *datm, double *dobs,
+    ///     char *ObjectName, int32 helflag, double *dret, char *serr);
+    /// Returns -1 on error, -2 if the object is below the horizon, else
+    /// 0 (photopic vision), |1 (scotopic vision), |2 (near the limit)
+    /// dret[0..8]: limiting magnitude, altitude and azimuth of the object,
+    /// the Sun and the Moon, magnitude of the object
+    pub fn swe_vis_limit_mag(
+        tjdut: c_double,
+        geopos: *mut c_double,
+        datm: *mut c_double,
+        dobs: *mut c_double,
+        object_name: *mut c_char,
+        helflag: c_int,
+        dret: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
     /*
      * 8. Date and time conversion functions
      */
@@ -157,6 +444,58 @@ extern "C" {
         gregflag: c_int,
     ) -> c_double;
 
+    /// double swe_deltat(
+    ///     double tjd);
+    pub fn swe_deltat(tjd: c_double) -> c_double;
+
+    /// double swe_deltat_ex(
+    ///     double tjd,
+    ///     int32 iflag,
+    ///     char *serr);
+    pub fn swe_deltat_ex(
+        tjd: c_double,
+        iflag: c_int,
+        serr: *mut c_char,
+    ) -> c_double;
+
+    /// void swe_set_delta_t_userdef(
+    ///     double t_acc);
+    pub fn swe_set_delta_t_userdef(t_acc: c_double);
+
+    /// int32 swe_time_equ(
+    ///     double tjd_ut,
+    ///     double *te,
+    ///     char *serr);
+    pub fn swe_time_equ(
+        tjd_ut: c_double,
+        te: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// int32 swe_lmt_to_lat(
+    ///     double tjd_lmt,
+    ///     double geolon,
+    ///     double *tjd_lat,
+    ///     char *serr);
+    pub fn swe_lmt_to_lat(
+        tjd_lmt: c_double,
+        geolon: c_double,
+        tjd_lat: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// int32 swe_lat_to_lmt(
+    ///     double tjd_lat,
+    ///     double geolon,
+    ///     double *tjd_lmt,
+    ///     char *serr);
+    pub fn swe_lat_to_lmt(
+        tjd_lat: c_double,
+        geolon: c_double,
+        tjd_lmt: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
     /// void swe_utc_time_zone(
     ///     int32 iyear, int32 imonth, int32 iday,
     ///     int32 ihour, int32 imin, double dsec,
@@ -199,6 +538,52 @@ extern "C" {
         serr: *mut c_char,
     ) -> c_int;
 
+    /// void swe_jdet_to_utc(
+    ///     double tjd_et, int32 gregflag,
+    ///     int32 *iyear, int32 *imonth, int32 *iday,
+    ///     int32 *ihour, int32 *imin, double *dsec);
+    /// dsec is 60.x during a leap second
+    pub fn swe_jdet_to_utc(
+        tjd_et: c_double,
+        gregflag: c_int,
+        iyear: *mut c_int,
+        imonth: *mut c_int,
+        iday: *mut c_int,
+        ihour: *mut c_int,
+        imin: *mut c_int,
+        dsec: *mut c_double,
+    );
+
+    /// void swe_jdut1_to_utc(
+    ///     double tjd_ut, int32 gregflag,
+    ///     int32 *iyear, int32 *imonth, int32 *iday,
+    ///     int32 *ihour, int32 *imin, double *dsec);
+    pub fn swe_jdut1_to_utc(
+        tjd_ut: c_double,
+        gregflag: c_int,
+        iyear: *mut c_int,
+        imonth: *mut c_int,
+        iday: *mut c_int,
+        ihour: *mut c_int,
+        imin: *mut c_int,
+        dsec: *mut c_double,
+    );
+
+    /// int swe_date_conversion(
+    ///     int y, int m, int d,    /* year, month, day */
+    ///     double utime,           /* universal time in hours (decimal) */
+    ///     char c,                 /* calendar g[regorian]|j[ulian] */
+    ///     double *tjd);           /* julian day */
+    /// Returns ERR for an illegal date
+    pub fn swe_date_conversion(
+        y: c_int,
+        m: c_int,
+        d: c_int,
+        utime: c_double,
+        c: c_char,
+        tjd: *mut c_double,
+    ) -> c_int;
+
     /// swe_revjul() is the inverse function to swe_julday(), see the description there.
     /// Arguments are julian day number, calendar flag (0=julian, 1=gregorian)
     /// return values are the calendar day, month, year and the hour of
@@ -207,9 +592,9 @@ extern "C" {
     /// before Christ, not the historical year numbering.
     /// Astronomical years are done with negative numbers, historical
     /// years with indicators BC or BCE (before common era).
-    /// Year  0 (astronomical)  	= 1 BC historical year
-    /// year -1 (astronomical) 	= 2 BC historical year
-    /// year -234 (astronomical) 	= 235 BC historical year
+    /// Year  0 (astronomical)   = 1 BC historical year
+    /// year -1 (astronomical)   = 2 BC historical year
+    /// year -234 (astronomical) = 235 BC historical year
     /// etc.
     pub fn swe_revjul(
         tjd: f64,
@@ -246,6 +631,53 @@ extern "C" {
         ascmc: *mut c_double,
     ) -> c_int;
 
+    /// int swe_houses_armc(
+    ///     double armc,
+    ///     double geolat,
+    ///     double eps,
+    ///     int hsys,
+    ///     double *cusps,
+    ///     double *ascmc);
+    pub fn swe_houses_armc(
+        armc: c_double,
+        geolat: c_double,
+        eps: c_double,
+        hsys: c_int,
+        cusps: *mut c_double,
+        ascmc: *mut c_double,
+    ) -> c_int;
+
+    /// int swe_houses_armc_ex2(
+    ///     double armc, double geolat, double eps, int hsys,
+    ///     double *cusps, double *ascmc, double *cusp_speed,
+    ///     double *ascmc_speed, char *serr);
+    pub fn swe_houses_armc_ex2(
+        armc: c_double,
+        geolat: c_double,
+        eps: c_double,
+        hsys: c_int,
+        cusps: *mut c_double,
+        ascmc: *mut c_double,
+        cusp_speed: *mut c_double,
+        ascmc_speed: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// double swe_house_pos(
+    ///     double armc, double geolat, double eps, int hsys,
+    ///     double *xpin, char *serr);
+    /// xpin: ecliptic longitude and latitude (tropical) of the point
+    /// Returns the house position from 1.0 to 12.999 (36.999 for system G),
+    /// 0 on error
+    pub fn swe_house_pos(
+        armc: c_double,
+        geolat: c_double,
+        eps: c_double,
+        hsys: c_int,
+        xpin: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_double;
+
     /// int swe_houses_ex2(
     ///     double tjd_ut, int32 iflag, double geolat, double geolon, int hsys,
     ///     double *cusps, double *ascmc, double *cusp_speed, double *ascmc_speed, char *serr);
@@ -262,10 +694,63 @@ extern "C" {
         serr: *mut c_char,
     ) -> c_int;
 
+    /// int32 swe_gauquelin_sector(
+    ///     double t_ut, int32 ipl, char *starname, int32 iflag, int32 imeth,
+    ///     double *geopos, double atpress, double attemp,
+    ///     double *dgsect, char *serr);
+    /// imeth: 0 = with latitude, 1 = without latitude, 2 = from rise/set of
+    /// the disc center, 3 = the same with refraction, 4 and 5 = from rise/set
+    /// of the upper limb without and with refraction
+    pub fn swe_gauquelin_sector(
+        t_ut: c_double,
+        ipl: c_int,
+        starname: *mut c_char,
+        iflag: c_int,
+        imeth: c_int,
+        geopos: *mut c_double,
+        atpress: c_double,
+        attemp: c_double,
+        dgsect: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /*
+     * 16. Sidereal time
+     */
+
+    /// double swe_sidtime(double tjd_ut);
+    /// Returns the sidereal time at Greenwich in hours
+    pub fn swe_sidtime(tjd_ut: c_double) -> c_double;
+
+    /// double swe_sidtime0(double tjd_ut, double eps, double nut);
+    /// Sidereal time at Greenwich in hours, with the obliquity and the
+    /// nutation in longitude given in degrees
+    pub fn swe_sidtime0(
+        tjd_ut: c_double,
+        eps: c_double,
+        nut: c_double,
+    ) -> c_double;
+
     /*
      * 17. Auxiliary functions
      */
 
+    /// void swe_cotrans(double *xpo, double *xpn, double eps);
+    /// Coordinate transformation, from ecliptic to equator or vice-versa.
+    /// xpo: array of 3 doubles with the input coordinates (lon, lat, dist)
+    /// xpn: array of 3 doubles for the result, may be the same as xpo
+    /// eps: obliquity of the ecliptic in degrees; positive for equator to
+    ///      ecliptic, negative for ecliptic to equator
+    pub fn swe_cotrans(xpo: *const c_double, xpn: *mut c_double, eps: c_double);
+
+    /// void swe_cotrans_sp(double *xpo, double *xpn, double eps);
+    /// Same as swe_cotrans(), for 6 doubles including the speeds
+    pub fn swe_cotrans_sp(
+        xpo: *const c_double,
+        xpn: *mut c_double,
+        eps: c_double,
+    );
+
     /// double swe_degnorm(double x);
     pub fn swe_degnorm(x: c_double) -> c_double;
 
@@ -304,9 +789,39 @@ extern "C" {
     /// C function: `void swe_set_sid_mode(int32 sid_mode, double t0, double ayan_t0);`
     pub fn swe_set_sid_mode(sid_mode: c_int, t0: c_double, ayan_t0: c_double);
 
+    /// Sets the geographic position of the observer for topocentric positions
+    ///
+    /// Parameters:
+    /// - `geolon`: Geographic longitude in degrees, east positive.
+    /// - `geolat`: Geographic latitude in degrees, north positive.
+    /// - `altitude`: Altitude above sea level in meters.
+    ///
+    /// C function: `void swe_set_topo(double geolon, double geolat, double altitude);`
+    pub fn swe_set_topo(geolon: c_double, geolat: c_double, altitude: c_double);
+
     /// Allows to get ayanamsha name based on the integer number
     pub fn swe_get_ayanamsa_name(isidmode: i32) -> *const std::os::raw::c_char;
 
+    /// int32 swe_get_ayanamsa_ex_ut(
+    ///     double tjd_ut, int32 iflag, double *daya, char *serr);
+    /// Ayanamsha of the current sidereal mode, with nutation unless
+    /// SEFLG_NONUT is in iflag. Returns the flags used or ERR.
+    pub fn swe_get_ayanamsa_ex_ut(
+        tjd_ut: c_double,
+        iflag: c_int,
+        daya: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// int32 swe_get_ayanamsa_ex(
+    ///     double tjd_et, int32 iflag, double *daya, char *serr);
+    pub fn swe_get_ayanamsa_ex(
+        tjd_et: c_double,
+        iflag: c_int,
+        daya: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
     /*
      * Function to calcualte planetary nodes
      */
@@ -319,21 +834,22 @@ extern "C" {
     /// - `ipl`: Planet Number
     /// - `iflag`: Calculation Flags
     /// - `method`: tells what kind of nodes or apsides are required:
-    ///         1 -> Means nodes and apsides are calcualtated for the bodies that have them.
-    ///              For the Moon and planets ME trhoguh NE, osculating ones for PL and the
-    ///              asteroids. This is the default method, also used in method=0;
-    ///         2 -> Osculating nodes and apsides are calculated for all bodies.
-    ///         4 -> Osculating nodes and apsides are calculated for all bodies. With planets
-    ///              beyond Jupiter, the nodes and apsides are calculated from barycentric
-    ///              positions and speed. Cf. the explanations in swisseph.doc. If this bit is
-    ///              combined with SE_NODBIT_MEAN, mean values are given for the planets Mercury - Neptune.
-    ///         256-> The second focal point of the orbital ellipse is computed and returned in the array of
-    ///               the aphelion. This bit can be combined with any other bit.
+    ///   - 1 -> Means nodes and apsides are calcualtated for the bodies that have them.
+    ///     For the Moon and planets ME trhoguh NE, osculating ones for PL and the
+    ///     asteroids. This is the default method, also used in method=0;
+    ///   - 2 -> Osculating nodes and apsides are calculated for all bodies.
+    ///   - 4 -> Osculating nodes and apsides are calculated for all bodies. With planets
+    ///     beyond Jupiter, the nodes and apsides are calculated from barycentric
+    ///     positions and speed. Cf. the explanations in swisseph.doc. If this bit is
+    ///     combined with SE_NODBIT_MEAN, mean values are given for the planets Mercury - Neptune.
+    ///   - 256-> The second focal point of the orbital ellipse is computed and returned in the array of
+    ///     the aphelion. This bit can be combined with any other bit.
     /// - `xnasc`: array of 6 double for ascending node
     /// - `xndsc`: array of 6 double for descending node
     /// - `xperi`: array of 6 double for perihelion
     /// - `xaphe`: array of 6 double for aphelion
     /// - `serr` : character string to contain error messages, 256 chars
+    ///
     /// P.S. When True Nodes needed, select (Osculating Nodes).
     pub fn swe_nod_aps_ut(
         tjd_ut: f64,
@@ -347,6 +863,39 @@ extern "C" {
         serr: *mut c_char,
     ) -> i32;
 
+    /// int32 swe_get_orbital_elements(
+    ///     double tjd_et, int32 ipl, int32 iflag, double *dret, char *serr);
+    /// dret: array of 50 doubles, 17 are used:
+    /// - 0 semimajor axis, 1 eccentricity, 2 inclination
+    /// - 3 longitude of the ascending node, 4 argument of periapsis,
+    ///   5 longitude of periapsis
+    /// - 6 mean anomaly, 7 true anomaly, 8 eccentric anomaly at epoch,
+    ///   9 mean longitude at epoch
+    /// - 10 sidereal period in tropical years, 11 mean daily motion,
+    ///   12 tropical period in years, 13 synodic period in days
+    /// - 14 time of perihelion passage, 15 perihelion distance,
+    ///   16 aphelion distance
+    pub fn swe_get_orbital_elements(
+        tjd_et: c_double,
+        ipl: c_int,
+        iflag: c_int,
+        dret: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
+    /// int32 swe_orbit_max_min_true_distance(
+    ///     double tjd_et, int32 ipl, int32 iflag,
+    ///     double *dmax, double *dmin, double *dtrue, char *serr);
+    pub fn swe_orbit_max_min_true_distance(
+        tjd_et: c_double,
+        ipl: c_int,
+        iflag: c_int,
+        dmax: *mut c_double,
+        dmin: *mut c_double,
+        dtrue: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
+
     pub fn swe_fixstar_ut(
         star: *mut c_char,
         tjd_ut: c_double,
@@ -391,4 +940,64 @@ extern "C" {
         serr: *mut c_char,
     ) -> c_int;
 
+    /*
+     * Crossings of the Sun, Moon and planets over a given longitude
+     */
+
+    /// double swe_solcross_ut(double x2cross, double jd_ut, int32 flag, char *serr);
+    /// Returns the UT of the next crossing of the Sun over x2cross, with
+    /// jd > jd_ut. Errors are indicated by returning a jd < jd_ut.
+    pub fn swe_solcross_ut(
+        x2cross: c_double,
+        jd_ut: c_double,
+        flag: c_int,
+        serr: *mut c_char,
+    ) -> c_double;
+
+    /// double swe_mooncross_ut(double x2cross, double jd_ut, int32 flag, char *serr);
+    /// Returns the UT of the next crossing of the Moon over x2cross, with
+    /// jd > jd_ut. Errors are indicated by returning a jd < jd_ut.
+    pub fn swe_mooncross_ut(
+        x2cross: c_double,
+        jd_ut: c_double,
+        flag: c_int,
+        serr: *mut c_char,
+    ) -> c_double;
+
+    /// double swe_mooncross_node_ut(
+    ///     double jd_ut,
+    ///     int32 flag,
+    ///     double *xlon,   /* longitude of the Moon at the crossing */
+    ///     double *xlat,   /* latitude of the Moon at the crossing */
+    ///     char *serr);
+    /// Returns the UT of the next crossing of the Moon over one of its nodes
+    /// (zero latitude). Errors are indicated by returning a jd < jd_ut.
+    pub fn swe_mooncross_node_ut(
+        jd_ut: c_double,
+        flag: c_int,
+        xlon: *mut c_double,
+        xlat: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_double;
+
+    /// int32 swe_helio_cross_ut(
+    ///     int32 ipl,
+    ///     double x2cross,
+    ///     double jd_ut,
+    ///     int32 iflag,
+    ///     int32 dir,          /* >= 0 next crossing, < 0 previous crossing */
+    ///     double *jd_cross,   /* UT of the crossing */
+    ///     char *serr);
+    /// Heliocentric crossing of a planet over a longitude. Not possible for
+    /// the Sun, the Moon, the lunar nodes and apogees.
+    /// Returns OK (0) or ERR (-1).
+    pub fn swe_helio_cross_ut(
+        ipl: c_int,
+        x2cross: c_double,
+        jd_ut: c_double,
+        iflag: c_int,
+        dir: c_int,
+        jd_cross: *mut c_double,
+        serr: *mut c_char,
+    ) -> c_int;
 }
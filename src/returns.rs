@@ -0,0 +1,301 @@
+use serde::{Deserialize, Serialize};
+
+use crate::chart::{Chart, ChartBuilder, DEFAULT_BODIES};
+use crate::constants::{Ayanamsha, CalculationFlags};
+use crate::ephemeris::with_sidereal_mode;
+use crate::error::SweError;
+use crate::sweconst::{Bodies, Calendar, HouseSystem};
+use crate::swerust::handler_crossings::next_crossing;
+use crate::swerust::handler_swe03::calc_ut;
+use crate::swerust::handler_swe08::julday;
+use crate::swerust::handler_swe17::degnorm;
+
+/*
+ * Solar and lunar returns
+ *
+ * A return is the moment the Sun (or the Moon) comes back to its natal
+ * longitude, found with next_crossing. The return chart is cast for that
+ * moment at the place given to the builder, which may differ from the place
+ * of birth (relocated return).
+ *
+ * With an ayanamsha the longitudes are sidereal, so the returns follow the
+ * stars. Tropical returns can be corrected for precession instead: the
+ * target longitude is then the natal longitude plus the general precession
+ * since birth.
+ */
+
+/// Julian day of J2000.0
+const J2000: f64 = 2451545.0;
+/// Iterations of the precession corrected search, the precession moves the
+/// target by less than 0.01" between two of them
+const PRECESSION_ITERATIONS: usize = 3;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Display, EnumIter, Serialize, Deserialize,
+)]
+pub enum ReturnKind {
+    Solar,
+    Lunar,
+}
+
+impl ReturnKind {
+    pub fn body(&self) -> Bodies {
+        match self {
+            ReturnKind::Solar => Bodies::Sun,
+            ReturnKind::Lunar => Bodies::Moon,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Return {
+    pub kind: ReturnKind,
+    pub julian_day_ut: f64,
+    pub natal_longitude: f64,
+    pub target_longitude: f64, // natal longitude, corrected for precession
+    pub chart: Chart,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReturnBuilder {
+    kind: ReturnKind,
+    natal_julian_day_ut: f64,
+    latitude: f64,
+    longitude: f64,
+    house_system: HouseSystem,
+    ayanamsha: Option<Ayanamsha>,
+    precession_corrected: bool,
+    bodies: Vec<Bodies>,
+}
+
+impl ReturnBuilder {
+    /// Returns of a natal date (julian day UT), cast at a geographic
+    /// position. Placidus houses, tropical zodiac, no precession correction.
+    pub fn new(
+        kind: ReturnKind,
+        natal_julian_day_ut: f64,
+        latitude: f64,
+        longitude: f64,
+    ) -> ReturnBuilder {
+        ReturnBuilder {
+            kind,
+            natal_julian_day_ut,
+            latitude,
+            longitude,
+            house_system: HouseSystem::Placidus,
+            ayanamsha: None,
+            precession_corrected: false,
+            bodies: DEFAULT_BODIES.to_vec(),
+        }
+    }
+
+    /// Returns of a natal chart, at its place, with its house system,
+    /// ayanamsha and bodies
+    pub fn from_chart(kind: ReturnKind, chart: &Chart) -> ReturnBuilder {
+        ReturnBuilder {
+            house_system: chart.house_system,
+            ayanamsha: chart.ayanamsha,
+            bodies: chart.objects.iter().map(|o| o.object_enum).collect(),
+            ..ReturnBuilder::new(
+                kind,
+                chart.julian_day_ut,
+                chart.latitude,
+                chart.longitude,
+            )
+        }
+    }
+
+    /// Place of the return charts
+    pub fn relocate(mut self, latitude: f64, longitude: f64) -> Self {
+        self.latitude = latitude;
+        self.longitude = longitude;
+        self
+    }
+
+    /// House system
+    pub fn house_system(mut self, hsys: HouseSystem) -> Self {
+        self.house_system = hsys;
+        self
+    }
+
+    /// Sidereal returns with an ayanamsha
+    pub fn ayanamsha(mut self, ayanamsha: Ayanamsha) -> Self {
+        self.ayanamsha = Some(ayanamsha);
+        self
+    }
+
+    /// Tropical returns corrected for the precession since birth, no effect
+    /// on sidereal returns
+    pub fn precession_corrected(mut self, corrected: bool) -> Self {
+        self.precession_corrected = corrected;
+        self
+    }
+
+    pub fn bodies(mut self, bodies: &[Bodies]) -> Self {
+        self.bodies = bodies.to_vec();
+        self
+    }
+
+    /// First return on or after January 1 of a year (UT)
+    pub fn year(&self, year: i32) -> Result<Return, SweError> {
+        self.after(julday(year, 1, 1, 0.0, Calendar::Gregorian))
+    }
+
+    /// First return on or after the first day of a month (UT)
+    pub fn month(&self, year: i32, month: i32) -> Result<Return, SweError> {
+        if !(1..=12).contains(&month) {
+            return Err(SweError::InvalidArgument(format!(
+                "invalid month {}",
+                month
+            )));
+        }
+        self.after(julday(year, month, 1, 0.0, Calendar::Gregorian))
+    }
+
+    /// First return after a julian day (UT)
+    pub fn after(&self, tjd_ut: f64) -> Result<Return, SweError> {
+        // the sidereal mode stays set for the whole search
+        with_sidereal_mode(self.ayanamsha, || self.search(tjd_ut))
+    }
+
+    fn search(&self, tjd_ut: f64) -> Result<Return, SweError> {
+        let body = self.kind.body();
+        let mut iflag = CalculationFlags::SPEED_PRECISION;
+        if self.ayanamsha.is_some() {
+            iflag |= CalculationFlags::SIDEREAL_POSITIONS;
+        }
+        let natal_longitude =
+            calc_ut(self.natal_julian_day_ut, body, iflag)?.longitude;
+
+        let mut target_longitude = natal_longitude;
+        let mut crossing =
+            next_crossing(body, target_longitude, tjd_ut, iflag)?;
+        if self.precession_corrected && self.ayanamsha.is_none() {
+            let natal_precession = general_precession(self.natal_julian_day_ut);
+            for _ in 0..PRECESSION_ITERATIONS {
+                target_longitude = degnorm(
+                    natal_longitude
+                        + general_precession(crossing.julian_day_ut)
+                        - natal_precession,
+                );
+                crossing =
+                    next_crossing(body, target_longitude, tjd_ut, iflag)?;
+            }
+        }
+
+        let mut builder = ChartBuilder::from_julian_day(
+            crossing.julian_day_ut,
+            self.latitude,
+            self.longitude,
+        )
+        .house_system(self.house_system)
+        .bodies(&self.bodies);
+        if let Some(ayanamsha) = self.ayanamsha {
+            builder = builder.ayanamsha(ayanamsha);
+        }
+        Ok(Return {
+            kind: self.kind,
+            julian_day_ut: crossing.julian_day_ut,
+            natal_longitude,
+            target_longitude,
+            chart: builder.build()?,
+        })
+    }
+}
+
+/// General precession in longitude since J2000 in degrees (IAU 2006)
+fn general_precession(tjd: f64) -> f64 {
+    let t = (tjd - J2000) / 36525.0;
+    (5028.796195 * t + 1.1054348 * t * t) / 3600.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ensure_ephemeris_initialized;
+
+    fn natal() -> Chart {
+        ChartBuilder::new(1990, 5, 15, 8, 30, 0.0, 3.0, 43.084128, 25.5919228)
+            .build()
+            .unwrap()
+    }
+
+    fn angle_distance(a: f64, b: f64) -> f64 {
+        let d = degnorm(a - b);
+        d.min(360.0 - d)
+    }
+
+    #[test]
+    fn test_solar_return() {
+        let _ = ensure_ephemeris_initialized();
+        let chart = natal();
+        let solar_return = ReturnBuilder::from_chart(ReturnKind::Solar, &chart)
+            .year(2025)
+            .unwrap();
+
+        let sun = &solar_return.chart.objects[0];
+        assert_eq!(sun.object_enum, Bodies::Sun);
+        assert!(
+            angle_distance(sun.longitude, chart.objects[0].longitude) < 1e-6
+        );
+        // around the birthday
+        let may_10 = julday(2025, 5, 10, 0.0, Calendar::Gregorian);
+        let may_20 = julday(2025, 5, 20, 0.0, Calendar::Gregorian);
+        assert!(
+            solar_return.julian_day_ut > may_10
+                && solar_return.julian_day_ut < may_20
+        );
+        assert_eq!(solar_return.chart.houses.len(), 12);
+    }
+
+    #[test]
+    fn test_relocated_lunar_return() {
+        let _ = ensure_ephemeris_initialized();
+        let chart = natal();
+        let builder = ReturnBuilder::from_chart(ReturnKind::Lunar, &chart);
+        let home = builder.month(2025, 3).unwrap();
+        let relocated = builder
+            .clone()
+            .relocate(40.7128, -74.006)
+            .month(2025, 3)
+            .unwrap();
+
+        let march_1 = julday(2025, 3, 1, 0.0, Calendar::Gregorian);
+        assert!(home.julian_day_ut > march_1);
+        assert!(home.julian_day_ut < march_1 + 28.0);
+        let moon = relocated.chart.placement(Bodies::Moon).unwrap();
+        assert_eq!(relocated.julian_day_ut, home.julian_day_ut);
+        assert_ne!(relocated.chart.ascendant, home.chart.ascendant);
+        assert!(moon.house >= 1 && moon.house <= 12);
+        assert!(builder.month(2025, 13).is_err());
+    }
+
+    #[test]
+    fn test_precession_corrected_and_sidereal_returns() {
+        let _ = ensure_ephemeris_initialized();
+        let natal_jd = natal().julian_day_ut;
+        let place = (43.084128, 25.5919228);
+        let builder =
+            ReturnBuilder::new(ReturnKind::Solar, natal_jd, place.0, place.1)
+                .bodies(&[Bodies::Sun]);
+
+        let tropical = builder.year(2025).unwrap();
+        let corrected = builder
+            .clone()
+            .precession_corrected(true)
+            .year(2025)
+            .unwrap();
+        let sidereal = builder.ayanamsha(Ayanamsha::Lahiri).year(2025).unwrap();
+
+        // 35 years of precession are about 0.49°, half a day of the Sun
+        let precession =
+            degnorm(corrected.target_longitude - corrected.natal_longitude);
+        assert!((precession - 0.489).abs() < 0.005);
+        let delay = corrected.julian_day_ut - tropical.julian_day_ut;
+        assert!(delay > 0.4 && delay < 0.6);
+        // a sidereal return is a precession corrected return
+        assert!(
+            (sidereal.julian_day_ut - corrected.julian_day_ut).abs() < 0.01
+        );
+    }
+}
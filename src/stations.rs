@@ -0,0 +1,362 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::CalculationFlags;
+use crate::error::SweError;
+use crate::sweconst::Bodies;
+use crate::swerust::handler_crossings::{
+    next_crossing, previous_crossing, search_step,
+};
+use crate::swerust::handler_swe03::calc_ut;
+
+/*
+ * Stations and retrograde periods
+ *
+ * A station is the moment the speed in longitude of a body changes sign:
+ * station retrograde from direct to retrograde motion, station direct from
+ * retrograde to direct motion. The speed is sampled with the step of the
+ * crossing search, and every change of sign is refined by bisection.
+ *
+ * A retrograde period goes from a station retrograde to the next station
+ * direct. Its shadow starts when the body first reaches the longitude of the
+ * station direct (shadow entry) and ends when it passes the longitude of the
+ * station retrograde again (shadow exit).
+ */
+
+/// Precision of the station times found by bisection (about 1 ms)
+const STATION_PRECISION_DAYS: f64 = 1e-8;
+/// Search limit of next_station(), longer than the synodic period of Mars
+const MAX_SEARCH_DAYS: f64 = 3.0 * 365.25;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Display, EnumIter, Serialize, Deserialize,
+)]
+pub enum RetrogradeEventKind {
+    ShadowEntry,
+    StationRetrograde,
+    StationDirect,
+    ShadowExit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RetrogradeEvent {
+    pub julian_day_ut: f64,
+    pub body: Bodies,
+    pub kind: RetrogradeEventKind,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RetrogradePeriod {
+    pub body: Bodies,
+    pub shadow_entry: RetrogradeEvent,
+    pub station_retrograde: RetrogradeEvent,
+    pub station_direct: RetrogradeEvent,
+    pub shadow_exit: RetrogradeEvent,
+}
+
+impl RetrogradePeriod {
+    /// The four events of the period, in chronological order
+    pub fn events(&self) -> [RetrogradeEvent; 4] {
+        [
+            self.shadow_entry,
+            self.station_retrograde,
+            self.station_direct,
+            self.shadow_exit,
+        ]
+    }
+
+    pub fn is_retrograde_at(&self, tjd_ut: f64) -> bool {
+        tjd_ut >= self.station_retrograde.julian_day_ut
+            && tjd_ut < self.station_direct.julian_day_ut
+    }
+
+    /// Between the shadow entry and exit, retrograde period included
+    pub fn is_in_shadow_at(&self, tjd_ut: f64) -> bool {
+        tjd_ut >= self.shadow_entry.julian_day_ut
+            && tjd_ut < self.shadow_exit.julian_day_ut
+    }
+}
+
+/// Stations of a body between two julian days (UT), in chronological order
+pub fn stations_between(
+    body: Bodies,
+    jd_start: f64,
+    jd_end: f64,
+    iflag: CalculationFlags,
+) -> Result<Vec<RetrogradeEvent>, SweError> {
+    let mut stations = Vec::new();
+    scan(body, jd_start, jd_end, iflag, |station| {
+        stations.push(station);
+        true
+    })?;
+    Ok(stations)
+}
+
+/// First station of a body after a julian day (UT)
+pub fn next_station(
+    body: Bodies,
+    jd_ut: f64,
+    iflag: CalculationFlags,
+) -> Result<RetrogradeEvent, SweError> {
+    let mut found = None;
+    scan(body, jd_ut, jd_ut + MAX_SEARCH_DAYS, iflag, |station| {
+        found = Some(station);
+        false
+    })?;
+    found.ok_or_else(|| {
+        SweError::NotFound(format!(
+            "{} has no station within the search range",
+            body
+        ))
+    })
+}
+
+/// Retrograde periods of a body whose station retrograde is between two
+/// julian days (UT), with their shadows
+pub fn retrograde_periods(
+    body: Bodies,
+    jd_start: f64,
+    jd_end: f64,
+    iflag: CalculationFlags,
+) -> Result<Vec<RetrogradePeriod>, SweError> {
+    let mut periods = Vec::new();
+    for station_retrograde in stations_between(body, jd_start, jd_end, iflag)?
+        .into_iter()
+        .filter(|s| s.kind == RetrogradeEventKind::StationRetrograde)
+    {
+        let station_direct =
+            next_station(body, station_retrograde.julian_day_ut, iflag)?;
+        let entry = previous_crossing(
+            body,
+            station_direct.longitude,
+            station_retrograde.julian_day_ut,
+            iflag,
+        )?;
+        let exit = next_crossing(
+            body,
+            station_retrograde.longitude,
+            station_direct.julian_day_ut,
+            iflag,
+        )?;
+        periods.push(RetrogradePeriod {
+            body,
+            shadow_entry: RetrogradeEvent {
+                julian_day_ut: entry.julian_day_ut,
+                body,
+                kind: RetrogradeEventKind::ShadowEntry,
+                longitude: entry.longitude,
+            },
+            station_retrograde,
+            station_direct,
+            shadow_exit: RetrogradeEvent {
+                julian_day_ut: exit.julian_day_ut,
+                body,
+                kind: RetrogradeEventKind::ShadowExit,
+                longitude: exit.longitude,
+            },
+        });
+    }
+    Ok(periods)
+}
+
+/// Events of the retrograde periods of several bodies whose station
+/// retrograde is between two julian days (UT), in chronological order
+pub fn retrograde_calendar(
+    bodies: &[Bodies],
+    jd_start: f64,
+    jd_end: f64,
+    iflag: CalculationFlags,
+) -> Result<Vec<RetrogradeEvent>, SweError> {
+    let mut events = Vec::new();
+    for &body in bodies {
+        for period in retrograde_periods(body, jd_start, jd_end, iflag)? {
+            events.extend_from_slice(&period.events());
+        }
+    }
+    events.sort_by(|a, b| a.julian_day_ut.total_cmp(&b.julian_day_ut));
+    Ok(events)
+}
+
+fn speed(
+    body: Bodies,
+    jd_ut: f64,
+    iflag: CalculationFlags,
+) -> Result<f64, SweError> {
+    Ok(calc_ut(jd_ut, body, iflag)?.speed_longitude)
+}
+
+/// Walks from jd_start to jd_end and calls `on_station` for every station,
+/// until it returns false
+fn scan<F>(
+    body: Bodies,
+    jd_start: f64,
+    jd_end: f64,
+    iflag: CalculationFlags,
+    mut on_station: F,
+) -> Result<(), SweError>
+where
+    F: FnMut(RetrogradeEvent) -> bool,
+{
+    let iflag = iflag | CalculationFlags::SPEED_PRECISION;
+    let step = search_step(body);
+    let mut jd0 = jd_start;
+    let mut s0 = speed(body, jd0, iflag)?;
+    while jd0 < jd_end {
+        let jd1 = (jd0 + step).min(jd_end);
+        let s1 = speed(body, jd1, iflag)?;
+        if s0 * s1 <= 0.0 && s0 != 0.0 {
+            let station = refine(body, jd0, s0, jd1, iflag)?;
+            if !on_station(station) {
+                return Ok(());
+            }
+        }
+        jd0 = jd1;
+        s0 = s1;
+    }
+    Ok(())
+}
+
+/// Bisection of the speed between two bracketing times, jd0 before jd1
+pub(crate) fn refine(
+    body: Bodies,
+    mut jd0: f64,
+    s0: f64,
+    mut jd1: f64,
+    iflag: CalculationFlags,
+) -> Result<RetrogradeEvent, SweError> {
+    let kind = if s0 > 0.0 {
+        RetrogradeEventKind::StationRetrograde
+    } else {
+        RetrogradeEventKind::StationDirect
+    };
+    while jd1 - jd0 > STATION_PRECISION_DAYS {
+        let jd = (jd0 + jd1) / 2.0;
+        if s0 * speed(body, jd, iflag)? <= 0.0 {
+            jd1 = jd;
+        } else {
+            jd0 = jd;
+        }
+    }
+    Ok(RetrogradeEvent {
+        julian_day_ut: jd1,
+        body,
+        kind,
+        longitude: calc_ut(jd1, body, iflag)?.longitude,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ensure_ephemeris_initialized;
+    use crate::sweconst::Calendar;
+    use crate::swerust::handler_swe08::julday;
+
+    fn jd(year: i32, month: i32, day: i32) -> f64 {
+        julday(year, month, day, 0.0, Calendar::Gregorian)
+    }
+
+    #[test]
+    fn test_mercury_stations_2024() {
+        let _ = ensure_ephemeris_initialized();
+        let flags = CalculationFlags::SWISS_EPHEMERIS;
+        let stations = stations_between(
+            Bodies::Mercury,
+            jd(2024, 1, 1),
+            jd(2025, 1, 1),
+            flags,
+        )
+        .unwrap();
+
+        // Mercury turned retrograde on April 1, August 5 and November 26
+        let retrograde: Vec<f64> = stations
+            .iter()
+            .filter(|s| s.kind == RetrogradeEventKind::StationRetrograde)
+            .map(|s| s.julian_day_ut)
+            .collect();
+        assert_eq!(retrograde.len(), 3);
+        assert!(
+            retrograde[0] > jd(2024, 4, 1) && retrograde[0] < jd(2024, 4, 2)
+        );
+        assert!(
+            retrograde[1] > jd(2024, 8, 4) && retrograde[1] < jd(2024, 8, 6)
+        );
+        assert!(
+            retrograde[2] > jd(2024, 11, 25)
+                && retrograde[2] < jd(2024, 11, 27)
+        );
+        // stations alternate
+        for pair in stations.windows(2) {
+            assert_ne!(pair[0].kind, pair[1].kind);
+        }
+        let speed = calc_ut(
+            stations[0].julian_day_ut,
+            Bodies::Mercury,
+            CalculationFlags::SPEED_PRECISION,
+        )
+        .unwrap()
+        .speed_longitude;
+        assert!(speed.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mars_retrograde_period_with_shadow() {
+        let _ = ensure_ephemeris_initialized();
+        let flags = CalculationFlags::SWISS_EPHEMERIS;
+        let periods = retrograde_periods(
+            Bodies::Mars,
+            jd(2024, 6, 1),
+            jd(2025, 6, 1),
+            flags,
+        )
+        .unwrap();
+
+        // Mars was retrograde from December 6, 2024 to February 24, 2025
+        assert_eq!(periods.len(), 1);
+        let period = periods[0];
+        assert!(period.station_retrograde.julian_day_ut > jd(2024, 12, 5));
+        assert!(period.station_retrograde.julian_day_ut < jd(2024, 12, 7));
+        assert!(period.station_direct.julian_day_ut > jd(2025, 2, 23));
+        assert!(period.station_direct.julian_day_ut < jd(2025, 2, 25));
+
+        let events = period.events();
+        for pair in events.windows(2) {
+            assert!(pair[0].julian_day_ut < pair[1].julian_day_ut);
+        }
+        // the shadow is bounded by the longitudes of the stations
+        assert!(
+            (period.shadow_entry.longitude - period.station_direct.longitude)
+                .abs()
+                < 1e-6
+        );
+        assert!(
+            (period.shadow_exit.longitude
+                - period.station_retrograde.longitude)
+                .abs()
+                < 1e-6
+        );
+        assert!(period.is_retrograde_at(jd(2025, 1, 1)));
+        assert!(!period.is_retrograde_at(period.shadow_entry.julian_day_ut));
+        assert!(period.is_in_shadow_at(period.shadow_entry.julian_day_ut));
+    }
+
+    #[test]
+    fn test_retrograde_calendar() {
+        let _ = ensure_ephemeris_initialized();
+        let flags = CalculationFlags::SWISS_EPHEMERIS;
+        let events = retrograde_calendar(
+            &[Bodies::Sun, Bodies::Mercury, Bodies::Mars],
+            jd(2024, 6, 1),
+            jd(2025, 6, 1),
+            flags,
+        )
+        .unwrap();
+
+        // the Sun is never retrograde; three Mercury periods and one of Mars
+        assert_eq!(events.len(), 4 * 4);
+        assert!(events.iter().all(|e| e.body != Bodies::Sun));
+        for pair in events.windows(2) {
+            assert!(pair[0].julian_day_ut <= pair[1].julian_day_ut);
+        }
+    }
+}
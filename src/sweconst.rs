@@ -2,7 +2,11 @@ extern crate serde;
 extern crate serde_derive;
 extern crate serde_json;
 extern crate strum;
+use crate::error::SweError;
+use crate::swerust::handler_swe14::house_name;
 use crate::swerust::handler_swe17::{ split_deg, SplitDegResult };
+use std::convert::TryFrom;
+use strum::IntoEnumIterator;
 use num_derive::FromPrimitive;
 //use num_traits::FromPrimitive;
 use serde::{ Deserialize, Serialize };
@@ -53,7 +57,7 @@ impl Theme {
 }
 
 /// Zodiac
-#[derive(Debug, Clone, Display, EnumIter)]
+#[derive(Debug, Clone, Display, EnumIter, Serialize, Deserialize)]
 pub enum Signs {
     Aries = 1,
     Taurus = 2,
@@ -131,7 +135,7 @@ impl Signs {
 }
 
 /// Element
-#[derive(Debug, Clone, Copy, PartialEq, IntoStaticStr)]
+#[derive(Debug, Clone, Copy, PartialEq, IntoStaticStr, Serialize, Deserialize)]
 pub enum Element {
     Fire,
     Earth,
@@ -183,7 +187,7 @@ impl Element {
 }
 
 /// Bodies
-#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, IntoStaticStr)]
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, IntoStaticStr, Serialize, Deserialize)]
 pub enum Bodies {
     EclNut = -1, // Computes the obliquity of the ecliptic and the nutation.
     Sun = 0,
@@ -272,7 +276,7 @@ pub enum Bodies {
 }
 
 /// Object type
-#[derive(Debug, Clone, PartialEq, Display, EnumIter)]
+#[derive(Debug, Clone, PartialEq, Display, EnumIter, Serialize, Deserialize)]
 pub enum ObjectType {
     Unknown,
     PlanetOrStar,
@@ -434,15 +438,28 @@ impl Bodies {
 }
 
 /// Object position (direction)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ObjectPos {
     Stationary,
     Direct,
     Retrograde,
 }
 
+impl ObjectPos {
+    /// Direction from the speed in longitude (deg/day)
+    pub fn from_speed(speed_longitude: f64) -> ObjectPos {
+        if f64::abs(speed_longitude) < 0.0003 {
+            ObjectPos::Stationary
+        } else if speed_longitude > 0.0 {
+            ObjectPos::Direct
+        } else {
+            ObjectPos::Retrograde
+        }
+    }
+}
+
 /// Object
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Object {
     pub object_enum: Bodies,
     pub object_name: String,
@@ -464,14 +481,6 @@ impl Object {
         latitude: f64,
         speed_longitude: f64
     ) -> Object {
-        let object_pos;
-        if f64::abs(speed_longitude) < 0.0003 {
-            object_pos = ObjectPos::Stationary;
-        } else if speed_longitude > 0.0 {
-            object_pos = ObjectPos::Direct;
-        } else {
-            object_pos = ObjectPos::Retrograde;
-        }
         Object {
             object_enum,
             object_name: object_name.to_string(),
@@ -479,14 +488,14 @@ impl Object {
             longitude,
             latitude,
             speed_longitude,
-            object_pos,
+            object_pos: ObjectPos::from_speed(speed_longitude),
             split: split_deg(longitude, 0),
         }
     }
 }
 
 /// House
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct House {
     pub object_id: i32,
     pub longitude: f64,
@@ -507,7 +516,7 @@ impl House {
 }
 
 /// Angle
-#[derive(Debug, Clone, Copy, PartialEq, EnumIter)]
+#[derive(Debug, Clone, Copy, PartialEq, EnumIter, Serialize, Deserialize)]
 pub enum Angle {
     Nothing = 0,
     Asc = 1,
@@ -522,45 +531,123 @@ pub enum Calendar {
     Gregorian = 1,
 }
 
-#[allow(clippy::upper_case_acronyms)]
-/// Optional flag swissephem
-pub enum OptionalFlag {
-    JplEph = 1,
-    SwissEph = 2,
-    Moshier = 4,
-    Heliocentric = 8,
-    TruePosition = 16,
-    J2000Equinox = 32,
-    NoNutation = 64,
-    Speed3 = 128,
-    Speed = 256,
-    NoGravitanionalDeflection = 512,
-    NoAnnualAberration = 1024,
-    AstronomicPosition = 1024 | 512,
-    // AstronomicPosition = OptionalFlag::NoAnnualAberration
-    //     | OptionalFlag::NoGravitanionalDeflection,
-    EquatorialPosition = 2 * 1024,
-    XYZCartesianNotPolarCoordinate = 4 * 1024,
-    Radians = 8 * 1024,
-    BarycentricPosition = 16 * 1024,
-    TopocentricPosition = 32 * 1024,
-    SideralPosition = 64 * 1024,
-    ICRS = 128 * 1024,
-    Dpsideps1980 = 256 * 1024,
-    JplHorApprox = 512 * 1024,
+/// Geographic position of an observer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observer {
+    pub latitude: f64,  // geographic latitude, north positive
+    pub longitude: f64, // geographic longitude, east positive
+    pub altitude: f64,  // height above sea level in meters
+}
+
+impl Observer {
+    pub fn new(latitude: f64, longitude: f64, altitude: f64) -> Observer {
+        Observer { latitude, longitude, altitude }
+    }
+
+    /// Position in the order expected by the C library (geopos[3]):
+    /// longitude, latitude, altitude
+    pub fn geopos(&self) -> [f64; 3] {
+        [self.longitude, self.latitude, self.altitude]
+    }
 }
 
-/// House system
-/// I have put in enum only the most important houses methods
-/// To do
+/// House systems of the C library, serialized as their char (hsys)
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumIter, Serialize, Deserialize
+)]
+#[serde(into = "char", try_from = "char")]
 pub enum HouseSystem {
-    Campanus,
-    Equal,
-    Koch,
     Placidus,
+    Koch,
     Porphyrius,
     Regiomontanus,
+    Campanus,
+    Equal, // cusp 1 is the Ascendant
+    EqualMc, // cusp 10 is the MC
+    EqualAries, // cusp 1 is 0° Aries
+    VehlowEqual, // Ascendant in the middle of house 1
     WholeSign,
+    Alcabitius,
+    CarterPoliEquatorial,
+    Horizontal, // azimuthal system
+    SunshineTreindl, // Sunshine (Makransky, solution Treindl)
+    SunshineMakransky, // Sunshine (Makransky, solution Makransky)
+    KrusinskiPisaGoelzer,
+    Morinus,
+    PolichPage, // topocentric system
+    PullenSinusoidalDelta, // ex Neo-Porphyry
+    PullenSinusoidalRatio,
+    Sripati,
+    Apc,
+    AxialRotation, // meridian system, Zariel
+    SavardA,
+    GauquelinSectors, // 36 sectors, see handler_swe14::gauquelin_sectors
+}
+
+impl HouseSystem {
+    /// Char of the house system for the C library
+    pub const fn to_char(self) -> char {
+        match self {
+            HouseSystem::Placidus => 'P',
+            HouseSystem::Koch => 'K',
+            HouseSystem::Porphyrius => 'O',
+            HouseSystem::Regiomontanus => 'R',
+            HouseSystem::Campanus => 'C',
+            HouseSystem::Equal => 'E',
+            HouseSystem::EqualMc => 'D',
+            HouseSystem::EqualAries => 'N',
+            HouseSystem::VehlowEqual => 'V',
+            HouseSystem::WholeSign => 'W',
+            HouseSystem::Alcabitius => 'B',
+            HouseSystem::CarterPoliEquatorial => 'F',
+            HouseSystem::Horizontal => 'H',
+            HouseSystem::SunshineTreindl => 'I',
+            HouseSystem::SunshineMakransky => 'i',
+            HouseSystem::KrusinskiPisaGoelzer => 'U',
+            HouseSystem::Morinus => 'M',
+            HouseSystem::PolichPage => 'T',
+            HouseSystem::PullenSinusoidalDelta => 'L',
+            HouseSystem::PullenSinusoidalRatio => 'Q',
+            HouseSystem::Sripati => 'S',
+            HouseSystem::Apc => 'Y',
+            HouseSystem::AxialRotation => 'X',
+            HouseSystem::SavardA => 'J',
+            HouseSystem::GauquelinSectors => 'G',
+        }
+    }
+
+    /// House system of a char, 'A' is the old char of equal houses
+    pub fn from_char(hsys: char) -> Result<HouseSystem, SweError> {
+        match hsys {
+            'A' => Ok(HouseSystem::Equal),
+            _ =>
+                HouseSystem::iter()
+                    .find(|h| h.to_char() == hsys)
+                    .ok_or_else(|| {
+                        SweError::InvalidArgument(format!("unknown house system {}", hsys))
+                    }),
+        }
+    }
+
+    /// Name given by the C library (swe_house_name), unchanged: equal house
+    /// variants are prefixed, e.g. "equal/ whole sign" for WholeSign
+    pub fn name(self) -> Result<String, SweError> {
+        house_name(self)
+    }
+}
+
+impl From<HouseSystem> for char {
+    fn from(hsys: HouseSystem) -> char {
+        hsys.to_char()
+    }
+}
+
+impl TryFrom<char> for HouseSystem {
+    type Error = SweError;
+
+    fn try_from(hsys: char) -> Result<HouseSystem, SweError> {
+        HouseSystem::from_char(hsys)
+    }
 }
 
 /// Aspects
@@ -4,6 +4,7 @@ mod swe07;
 mod swe08;
 mod swe14;
 mod swe17;
+mod swe_crossings;
 mod swe_nodes;
 pub mod utils;
 
@@ -13,4 +14,5 @@ pub use self::swe07::handler as handler_swe07;
 pub use self::swe08::handler as handler_swe08;
 pub use self::swe14::handler as handler_swe14;
 pub use self::swe17::handler as handler_swe17;
+pub use self::swe_crossings::handler as handler_crossings;
 pub use self::swe_nodes::handler as handler_nodes;
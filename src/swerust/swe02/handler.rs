@@ -1,3 +1,5 @@
+use crate::ephemeris::{self, exclusive};
+use crate::error::{c_string, SweError};
 use crate::raw;
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
@@ -6,55 +8,90 @@ use std::os::raw::c_char;
  * 2. The Ephemeris file related functions
  */
 
-/// Set the path of ephemeris
-pub fn set_ephe_path(path: &str) {
+/// C string of at most 255 bytes, the size of the path buffers of the library
+fn c_path(name: &str, path: &str) -> Result<CString, SweError> {
     if path.len() > 255 {
-        panic!("swe 2.1 -> set_ephe_path -> path to long");
+        return Err(SweError::InvalidArgument(format!(
+            "{} is longer than 255 bytes",
+            name
+        )));
     }
-    let c_str = CString::new(path).unwrap();
+    CString::new(path).map_err(|_| {
+        SweError::InvalidArgument(format!("{} contains a nul byte", name))
+    })
+}
+
+/// Set the path of ephemeris
+pub fn set_ephe_path(path: &str) -> Result<(), SweError> {
+    let c_str = c_path("path", path)?;
     let path_final: *const c_char = c_str.as_ptr() as *const c_char;
-    unsafe {
-        raw::swe_set_ephe_path(path_final);
-    }
+    exclusive(|| {
+        unsafe {
+            raw::swe_set_ephe_path(path_final);
+        }
+        ephemeris::forget_files();
+    });
+    Ok(())
 }
 
-/// Close swiss ephemeris, free memory
+/// Close swiss ephemeris, free memory. Resets the sidereal mode and the
+/// observer applied by an enclosing Ephemeris::with.
 pub fn close() {
-    unsafe { raw::swe_close() }
+    exclusive(|| {
+        unsafe { raw::swe_close() }
+        ephemeris::forget_files();
+        ephemeris::forget_settings();
+    })
 }
 
 /// Set the path of ephemeris for working with JPL file
-pub fn set_jpl_file(fname: &str) {
-    if fname.len() > 255 {
-        panic!("swe 2.3 -> set_jpl_file -> fname to long");
-    }
-    let c_str = CString::new(fname).unwrap();
+pub fn set_jpl_file(fname: &str) -> Result<(), SweError> {
+    let c_str = c_path("fname", fname)?;
     let fname_final: *const c_char = c_str.as_ptr() as *const c_char;
-    unsafe {
-        raw::swe_set_jpl_file(fname_final);
-    }
+    exclusive(|| {
+        unsafe {
+            raw::swe_set_jpl_file(fname_final);
+        }
+        ephemeris::forget_files();
+    });
+    Ok(())
 }
 
 /// Get version of swiss ephemeris
-pub fn version() -> String {
+pub fn version() -> Result<String, SweError> {
     // Get the version
-    let mut version = [0; 255];
+    let mut version = [0; 256];
     let v = unsafe {
         let p = version.as_mut_ptr();
         raw::swe_version(p);
         CStr::from_ptr(p)
     };
-    CString::from(v).to_str().unwrap().to_string()
+    c_string(v)
 }
 
 /// Get librarx path dll
-pub fn get_library_path() -> String {
+pub fn get_library_path() -> Result<String, SweError> {
     // Get dll path
-    let mut dll_path = [0; 255];
+    let mut dll_path = [0; 256];
     let dll = unsafe {
         let p = dll_path.as_mut_ptr();
         raw::swe_get_library_path(p);
         CStr::from_ptr(p)
     };
-    CString::from(dll).to_str().unwrap().to_string()
+    c_string(dll)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_ephe_path_too_long() {
+        let path = "a".repeat(256);
+        assert!(matches!(
+            set_ephe_path(&path),
+            Err(SweError::InvalidArgument(_))
+        ));
+        assert!(set_jpl_file("de\u{0}406.eph").is_err());
+    }
 }
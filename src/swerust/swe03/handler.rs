@@ -1,7 +1,12 @@
+use crate::constants::{Ayanamsha, CalculationFlags, SiderealBits};
+use crate::ephemeris::{sidereal_mode_applied, with_observer};
+use crate::error::{check_flags, SweError, SweWarning};
 use crate::raw::{self, swe_get_ayanamsa_name, swe_set_sid_mode};
-use crate::sweconst::Bodies;
+use crate::sweconst::{Bodies, HouseSystem, Observer};
 use crate::swerust;
+use crate::swerust::handler_swe17::coordinates::{cotrans, true_obliquity};
 use std::ffi::{c_double, c_int, CStr, CString};
+use std::os::raw::c_char;
 
 /*
  * 3. The functions swe_calc_ut() and swe_calc()
@@ -33,15 +38,15 @@ pub struct CalcUtResult {
     pub speed_longitude: f64,
     pub speed_latitude: f64,
     pub speed_distance_au: f64,
-    pub status: i32,
-    pub serr: String,
+    pub flags: CalculationFlags, // flags actually used by the calculation
+    pub warning: Option<SweWarning>,
 }
 
 #[derive(Debug, Clone)]
 pub struct DeclinationResult {
     pub declination: f64,
-    pub status: i32,
-    pub serr: String,
+    pub flags: CalculationFlags,
+    pub warning: Option<SweWarning>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -53,59 +58,59 @@ pub struct FixStarResult {
     pub speed_latitude: f64,
     pub speed_distance_au: f64,
     pub star_name: String, // name returned by swe_fixstar
-    pub serr: String,      // error message if any
-    pub status: i32,       // return status from the C function
+    pub flags: CalculationFlags, // flags actually used by the calculation
+    pub warning: Option<SweWarning>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct FixStarMagResult {
     pub magnitude: f64,
     pub star_name: String,
-    pub serr: String, // error message if any
-    pub status: i32,  // return status from the C function
+}
+
+/// Star name in the buffer of 41 characters expected by swe_fixstar
+fn star_buffer(star: &str) -> Result<[c_char; 41], SweError> {
+    let c_star = CString::new(star).map_err(|_| {
+        SweError::InvalidArgument("star contains a nul byte".to_string())
+    })?;
+    let mut star_buf = [0 as c_char; 41];
+    let bytes = c_star.as_bytes_with_nul();
+    for (i, &b) in bytes.iter().enumerate().take(40) {
+        star_buf[i] = b as c_char;
+    }
+    Ok(star_buf)
 }
 
 /// Shared helper function
 fn call_fixstar_mag_fn(
     f: unsafe extern "C" fn(*mut i8, *mut f64, *mut i8) -> c_int,
     star: &str,
-) -> FixStarMagResult {
+) -> Result<FixStarMagResult, SweError> {
     let mut mag: f64 = 0.0;
     let mut serr = [0i8; 256];
-    let mut star_buf = [0i8; 41];
-
-    // Copy star name into buffer
-    let c_star = CString::new(star).unwrap();
-    let bytes = c_star.as_bytes_with_nul();
-    for (i, &b) in bytes.iter().enumerate().take(40) {
-        star_buf[i] = b as i8;
-    }
+    let mut star_buf = star_buffer(star)?;
 
     let status =
         unsafe { f(star_buf.as_mut_ptr(), &mut mag, serr.as_mut_ptr()) };
+    check_flags(CalculationFlags::empty(), status, &serr)?;
 
     let star_name = unsafe { CStr::from_ptr(star_buf.as_ptr()) }
         .to_string_lossy()
         .into_owned();
-    let serr = unsafe { CStr::from_ptr(serr.as_ptr()) }
-        .to_string_lossy()
-        .into_owned();
 
-    FixStarMagResult {
+    Ok(FixStarMagResult {
         magnitude: mag,
         star_name,
-        serr,
-        status,
-    }
+    })
 }
 
 /// Wrapper for `swe_fixstar_mag`
-pub fn fixstar_mag(star: &str) -> FixStarMagResult {
+pub fn fixstar_mag(star: &str) -> Result<FixStarMagResult, SweError> {
     call_fixstar_mag_fn(raw::swe_fixstar_mag, star)
 }
 
 /// Wrapper for `swe_fixstar2_mag`
-pub fn fixstar2_mag(star: &str) -> FixStarMagResult {
+pub fn fixstar2_mag(star: &str) -> Result<FixStarMagResult, SweError> {
     call_fixstar_mag_fn(raw::swe_fixstar2_mag, star)
 }
 
@@ -113,37 +118,29 @@ fn call_fixstar_fn(
     f: unsafe extern "C" fn(*mut i8, f64, i32, *mut f64, *mut i8) -> i32,
     star: &str,
     tjd: f64,
-    iflag: i32,
-) -> FixStarResult {
+    iflag: CalculationFlags,
+) -> Result<FixStarResult, SweError> {
+    iflag.validate()?;
     let mut xx: [f64; 6] = [0.0; 6];
     let mut serr = [0i8; 256];
-    let mut star_buf = [0i8; 41];
-
-    // Copy star name into buffer
-    let c_star = CString::new(star).unwrap();
-    let bytes = c_star.as_bytes_with_nul();
-    for (i, &b) in bytes.iter().enumerate().take(40) {
-        star_buf[i] = b as i8;
-    }
+    let mut star_buf = star_buffer(star)?;
 
     let status = unsafe {
         f(
             star_buf.as_mut_ptr(),
             tjd,
-            iflag,
+            iflag.bits(),
             xx.as_mut_ptr(),
             serr.as_mut_ptr(),
         )
     };
+    let warning = check_flags(iflag, status, &serr)?;
 
     let star_name = unsafe { CStr::from_ptr(star_buf.as_ptr()) }
         .to_string_lossy()
         .into_owned();
-    let serr = unsafe { CStr::from_ptr(serr.as_ptr()) }
-        .to_string_lossy()
-        .into_owned();
 
-    FixStarResult {
+    Ok(FixStarResult {
         longitude: xx[0],
         latitude: xx[1],
         distance_au: xx[2],
@@ -151,114 +148,129 @@ fn call_fixstar_fn(
         speed_latitude: xx[4],
         speed_distance_au: xx[5],
         star_name,
-        serr,
-        status,
-    }
+        flags: CalculationFlags::from_status(status),
+        warning,
+    })
 }
 
 /// Wrapper for `swe_fixstar_ut`
-pub fn fixstar_ut(star: &str, tjd_ut: f64, iflag: i32) -> FixStarResult {
+pub fn fixstar_ut(
+    star: &str,
+    tjd_ut: f64,
+    iflag: CalculationFlags,
+) -> Result<FixStarResult, SweError> {
     call_fixstar_fn(raw::swe_fixstar_ut, star, tjd_ut, iflag)
 }
 
+/// Topocentric position of a fixed star for an observer
+pub fn fixstar_ut_topo(
+    star: &str,
+    tjd_ut: f64,
+    iflag: CalculationFlags,
+    observer: &Observer,
+) -> Result<FixStarResult, SweError> {
+    with_observer(observer, || {
+        fixstar_ut(
+            star,
+            tjd_ut,
+            iflag | CalculationFlags::TOPOCENTRIC_POSITIONS,
+        )
+    })
+}
+
 /// Wrapper for `swe_fixstar`
-pub fn fixstar(star: &str, tjd_et: f64, iflag: i32) -> FixStarResult {
+pub fn fixstar(
+    star: &str,
+    tjd_et: f64,
+    iflag: CalculationFlags,
+) -> Result<FixStarResult, SweError> {
     call_fixstar_fn(raw::swe_fixstar, star, tjd_et, iflag)
 }
 
 /// Wrapper for `swe_fixstar2_ut`
-pub fn fixstar2_ut(star: &str, tjd_ut: f64, iflag: i32) -> FixStarResult {
+pub fn fixstar2_ut(
+    star: &str,
+    tjd_ut: f64,
+    iflag: CalculationFlags,
+) -> Result<FixStarResult, SweError> {
     call_fixstar_fn(raw::swe_fixstar2_ut, star, tjd_ut, iflag)
 }
 
 /// Wrapper for `swe_fixstar2`
-pub fn fixstar2(star: &str, tjd_et: f64, iflag: i32) -> FixStarResult {
+pub fn fixstar2(
+    star: &str,
+    tjd_et: f64,
+    iflag: CalculationFlags,
+) -> Result<FixStarResult, SweError> {
     call_fixstar_fn(raw::swe_fixstar2, star, tjd_et, iflag)
 }
 
-pub fn calc_ut(tjd_ut: f64, ipl: Bodies, iflag: i32) -> CalcUtResult {
+/// Position of a body, Bodies::SouthNode is computed opposite to
+/// Bodies::TrueNode
+pub fn calc_ut(
+    tjd_ut: f64,
+    ipl: Bodies,
+    iflag: CalculationFlags,
+) -> Result<CalcUtResult, SweError> {
+    iflag.validate()?;
     let mut xx: [f64; 6] = [0.0; 6];
-    let mut serr = [0; 255];
-    let result;
-    result = unsafe {
-        let p_xx = xx.as_mut_ptr();
-        let p_serr = serr.as_mut_ptr();
-        let status;
-        if ipl == Bodies::SouthNode {
-            status = raw::swe_calc_ut(
-                tjd_ut,
-                Bodies::TrueNode as i32,
-                iflag,
-                p_xx,
-                p_serr,
-            );
+    let mut serr = [0; 256];
+    let status = unsafe {
+        let ipl = if ipl == Bodies::SouthNode {
+            Bodies::TrueNode
         } else {
-            status = raw::swe_calc_ut(tjd_ut, ipl as i32, iflag, p_xx, p_serr);
-        }
-        let s_serr = CString::from(CStr::from_ptr(p_serr))
-            .to_str()
-            .unwrap()
-            .to_string();
-        if ipl == Bodies::SouthNode {
-            xx[0] += 180.0;
-            if xx[0] >= 360.0 {
-                xx[0] -= 360.0;
-            }
-        }
-        CalcUtResult {
-            longitude: xx[0],
-            latitude: xx[1],
-            distance_au: xx[2],
-            speed_longitude: xx[3],
-            speed_latitude: xx[4],
-            speed_distance_au: xx[5],
-            serr: s_serr,
-            status,
-        }
+            ipl
+        };
+        raw::swe_calc_ut(
+            tjd_ut,
+            ipl as i32,
+            iflag.bits(),
+            xx.as_mut_ptr(),
+            serr.as_mut_ptr(),
+        )
     };
-    result
+    let warning = check_flags(iflag, status, &serr)?;
+    if ipl == Bodies::SouthNode {
+        xx[0] += 180.0;
+        if xx[0] >= 360.0 {
+            xx[0] -= 360.0;
+        }
+    }
+    Ok(CalcUtResult {
+        longitude: xx[0],
+        latitude: xx[1],
+        distance_au: xx[2],
+        speed_longitude: xx[3],
+        speed_latitude: xx[4],
+        speed_distance_au: xx[5],
+        flags: CalculationFlags::from_status(status),
+        warning,
+    })
+}
+
+/// Topocentric position of a body for an observer
+pub fn calc_ut_topo(
+    tjd_ut: f64,
+    ipl: Bodies,
+    iflag: CalculationFlags,
+    observer: &Observer,
+) -> Result<CalcUtResult, SweError> {
+    with_observer(observer, || {
+        calc_ut(tjd_ut, ipl, iflag | CalculationFlags::TOPOCENTRIC_POSITIONS)
+    })
 }
 
 pub fn calc_ut_declination(
     tjd_ut: f64,
     body: Bodies,
-    iflag: i32,
-) -> DeclinationResult {
-    let mut xx: [f64; 6] = [0.0; 6];
-    let mut serr = [0; 255];
-    let result;
-    result = unsafe {
-        let p_xx = xx.as_mut_ptr();
-        let p_serr = serr.as_mut_ptr();
-        let status;
-        if body == Bodies::SouthNode {
-            status = raw::swe_calc_ut(
-                tjd_ut,
-                Bodies::TrueNode as i32,
-                iflag,
-                p_xx,
-                p_serr,
-            );
-        } else {
-            status = raw::swe_calc_ut(tjd_ut, body as i32, iflag, p_xx, p_serr);
-        }
-        let s_serr = CString::from(CStr::from_ptr(p_serr))
-            .to_str()
-            .unwrap()
-            .to_string();
-        if body == Bodies::SouthNode {
-            xx[0] += 180.0;
-            if xx[0] >= 360.0 {
-                xx[0] -= 360.0;
-            }
-        }
-        DeclinationResult {
-            declination: xx[1],
-            serr: s_serr,
-            status,
-        }
-    };
-    result
+    iflag: CalculationFlags,
+) -> Result<DeclinationResult, SweError> {
+    let result = calc_ut(tjd_ut, body, iflag)?;
+    Ok(DeclinationResult {
+        declination: result.latitude,
+        flags: result.flags,
+        warning: result.warning,
+    })
 }
 
 /// Fortuna Part
@@ -267,137 +279,56 @@ pub fn calc_ut_fp(
     tjd_ut: f64,
     geolat: f64,
     geolong: f64,
-    hsys: char,
-    iflag: i32,
-) -> CalcUtResult {
-    let ipl = Bodies::FortunaPart;
-    let mut xx: [f64; 6] = [0.0; 6];
-    let mut serr = [0; 255];
-    let result = unsafe {
-        let p_xx = xx.as_mut_ptr();
-        let p_serr = serr.as_mut_ptr();
-        let status = raw::swe_calc_ut(tjd_ut, ipl as i32, iflag, p_xx, p_serr);
-        let s_serr = CString::from(CStr::from_ptr(p_serr))
-            .to_str()
-            .unwrap()
-            .to_string();
-        let mut xx_sun: [f64; 6] = [0.0; 6];
-        let mut xx_moon: [f64; 6] = [0.0; 6];
-        let p_xx_sun = xx_sun.as_mut_ptr();
-        let p_serr_sun = serr.as_mut_ptr();
-        let _status_sun = raw::swe_calc_ut(
-            tjd_ut,
-            Bodies::Sun as i32,
-            iflag,
-            p_xx_sun,
-            p_serr_sun,
-        );
-        let p_xx_moon = xx_moon.as_mut_ptr();
-        let p_serr_moon = serr.as_mut_ptr();
-        let _status_moon = raw::swe_calc_ut(
-            tjd_ut,
-            Bodies::Moon as i32,
-            iflag,
-            p_xx_moon,
-            p_serr_moon,
-        );
-        let _s_serr_sun = CString::from(CStr::from_ptr(p_serr_sun))
-            .to_str()
-            .unwrap()
-            .to_string();
-        let _s_serr_moon = CString::from(CStr::from_ptr(p_serr_moon))
-            .to_str()
-            .unwrap()
-            .to_string();
-        let result_houses =
-            swerust::handler_swe14::houses(tjd_ut, geolat, geolong, hsys);
-        let asc_lon = result_houses.cusps[1];
-        let mc_lon = result_houses.cusps[10];
-        let mc_lat = 0.0;
-        let compute_sun = eq_coords(xx_sun[0], xx_sun[1]);
-        let compute_mc = eq_coords(mc_lon, mc_lat);
-        let sw_is_diurnal = is_above_horizon(
-            compute_sun.0,
-            compute_sun.1,
-            compute_mc.0,
-            compute_mc.1,
-        );
-        /*println!(
-            "sw_is_diurnal: {}asc: {}moon: {}sun: {}",
-            sw_is_diurnal.clone(),
-            asc_lon.clone(),
-            xx_moon[0].clone(),
-            xx_sun[0].clone()
-        );*/
-        let mut lon = if sw_is_diurnal {
-            asc_lon + xx_moon[0] - xx_sun[0]
-        } else {
-            asc_lon + xx_sun[0] - xx_moon[0]
-        };
-        let mut done = false;
-        while !done {
-            if lon < 0.0 {
-                lon += 360.0;
-            } else {
-                done = true;
-            }
-        }
-        done = false;
-        while !done {
-            if lon >= 360.0 {
-                lon -= 360.0;
-            } else {
-                done = true;
-            }
-        }
-        CalcUtResult {
-            longitude: lon,
-            latitude: xx[1],
-            distance_au: xx[2],
-            speed_longitude: xx[3],
-            speed_latitude: xx[4],
-            speed_distance_au: xx[5],
-            serr: s_serr,
-            status,
-        }
-    };
-    result
+    hsys: HouseSystem,
+    iflag: CalculationFlags,
+) -> Result<CalcUtResult, SweError> {
+    let sun = calc_ut(tjd_ut, Bodies::Sun, iflag)?;
+    let moon = calc_ut(tjd_ut, Bodies::Moon, iflag)?;
+    // the Ascendant in the same zodiac as the Sun and the Moon
+    let result_houses = swerust::handler_swe14::houses_with_flag(
+        tjd_ut, iflag, geolat, geolong, hsys,
+    )?;
+    let asc_lon = result_houses.ascmc[0];
+    let armc = result_houses.ascmc[2];
+    fortuna_part(tjd_ut, asc_lon, armc, geolat, &sun, &moon, iflag)
 }
 
-/// Converts from ecliptical to equatorial coordinates.
-fn eq_coords(lon: f64, lat: f64) -> (f64, f64) {
-    // Convert to radian
-    let lambda = lon.to_radians();
-    let beta = lat.to_radians();
-    let epson = (23.44_f64).to_radians(); // the earth inclinaison
-
-    // Declinaison in radian
-    let decl = (epson.sin() * lambda.sin() * beta.cos()
-        + epson.cos() * beta.sin())
-    .asin();
-
-    // Equatorial distance
-    let ed = ((lambda.cos() * beta.cos()) / decl.cos()).acos();
-
-    // RA in radian
-    let mut ra = if lon < 100.0 {
-        ed
+/// Part of Fortune from an Ascendant, the Sun and the Moon, reversed when the
+/// Sun is outside its diurnal semi-arc around the MC (given by the ARMC)
+pub(crate) fn fortuna_part(
+    tjd_ut: f64,
+    asc_lon: f64,
+    armc: f64,
+    geolat: f64,
+    sun: &CalcUtResult,
+    moon: &CalcUtResult,
+    iflag: CalculationFlags,
+) -> Result<CalcUtResult, SweError> {
+    // the horizon test needs the tropical Sun
+    let tropical = iflag - CalculationFlags::SIDEREAL_POSITIONS;
+    let tropical_sun = calc_ut(tjd_ut, Bodies::Sun, tropical)?;
+    let obliquity = true_obliquity(tjd_ut)?;
+    let compute_sun = cotrans(
+        [tropical_sun.longitude, tropical_sun.latitude, 1.0],
+        -obliquity,
+    );
+    let sw_is_diurnal =
+        is_above_horizon(compute_sun[0], compute_sun[1], armc, geolat);
+    let lon = if sw_is_diurnal {
+        asc_lon + moon.longitude - sun.longitude
     } else {
-        (360.0_f64).to_radians() - ed
+        asc_lon + sun.longitude - moon.longitude
     };
-
-    // Correctness of RA if longitude is close to 0° or 180° in a radius of 5°
-    if closest_distance(lon, 0.0).abs() < 5.0
-        || closest_distance(lon, 180.0).abs() < 5.0
-    {
-        let a = ra.sin() * decl.cos();
-        let b =
-            epson.cos() * lambda.sin() * beta.cos() - epson.sin() * beta.sin();
-        if (a - b).abs() > 0.0003 {
-            ra = (360.0_f64).to_radians() - ra;
-        }
-    }
-    (ra.to_degrees(), decl.to_degrees())
+    Ok(CalcUtResult {
+        longitude: lon.rem_euclid(360.0),
+        latitude: 0.0,
+        distance_au: 0.0,
+        speed_longitude: 0.0,
+        speed_latitude: 0.0,
+        speed_distance_au: 0.0,
+        flags: sun.flags,
+        warning: sun.warning.clone().or_else(|| moon.warning.clone()),
+    })
 }
 
 /// Boolean if is above horizon
@@ -432,42 +363,50 @@ fn closest_distance(angle1: f64, angle2: f64) -> f64 {
     znorm(angle2 - angle1)
 }
 
-/// Normalize angle between -180° and 180°
-fn znorm(mut angle: f64) -> f64 {
-    angle %= 360.0;
+/// Normalize angle into (-180°, 180°]
+fn znorm(angle: f64) -> f64 {
+    let angle = angle.rem_euclid(360.0);
     if angle <= 180.0 {
         angle
     } else {
-        angle - 180.0
+        angle - 360.0
     }
 }
 
-/// Set sidereal mode
-/// sid_mode: The sidereal ayanamsha mode from Ayanamashas constants defined in constants.rs
-/// t0: The reference epoch (typically 0.0, unless using a custom ayanamsha).
-/// ayan_t0: The ayanamsha value at t0 (used if defining a custom ayanamsha).
-pub fn set_sidereal_mode_ext(sid_mode: i32, t0: f64, ayan_t0: f64) {
+/// Set sidereal mode with options, only called by crate::ephemeris which
+/// records it for CalculationFlags::validate
+/// ayanamsha: The sidereal mode, Ayanamsha::User for a custom ayanamsha
+/// bits: Projection and precession options of the sidereal mode
+pub(crate) fn set_sidereal_mode(ayanamsha: Ayanamsha, bits: SiderealBits) {
+    let (t0, ayan_t0) = match ayanamsha {
+        Ayanamsha::User { t0, ayan_t0 } => (t0, ayan_t0),
+        _ => (0.0, 0.0),
+    };
     unsafe {
         swe_set_sid_mode(
-            sid_mode as c_int,
+            (ayanamsha.sid_mode() | bits.bits()) as c_int,
             t0 as c_double,
             ayan_t0 as c_double,
         );
     }
 }
 
-/// Set sidereal mode
-/// sid_mode: The sidereal ayanamsha mode from Ayanamashas constants defined in constants.rs
-pub fn set_sidereal_mode(ayanamsha: i32) {
+/// Set the position of the observer for CalculationFlags::TOPOCENTRIC_POSITIONS,
+/// only called by crate::ephemeris
+pub(crate) fn set_topo(observer: &Observer) {
     unsafe {
-        swe_set_sid_mode(ayanamsha as c_int, 0.0 as c_double, 0.0 as c_double);
+        raw::swe_set_topo(
+            observer.longitude as c_double,
+            observer.latitude as c_double,
+            observer.altitude as c_double,
+        );
     }
 }
 
-/// Allows to get Ayanamsha name based on constant
-pub fn get_ayanamsha_name(isidmode: i32) -> Option<String> {
+/// Allows to get Ayanamsha name, None for Ayanamsha::User
+pub fn get_ayanamsha_name(ayanamsha: Ayanamsha) -> Option<String> {
     unsafe {
-        let c_str_ptr = swe_get_ayanamsa_name(isidmode);
+        let c_str_ptr = swe_get_ayanamsa_name(ayanamsha.sid_mode());
         if c_str_ptr.is_null() {
             return None;
         }
@@ -478,26 +417,81 @@ pub fn get_ayanamsha_name(isidmode: i32) -> Option<String> {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct AyanamshaResult {
+    pub ayanamsha: f64, // degrees
+    pub warning: Option<SweWarning>,
+}
+
+/// Ayanamsha of the sidereal mode applied by an Ephemeris at a julian day
+/// (UT). It is the true ayanamsha, including nutation, unless
+/// CalculationFlags::MEAN_EQUINOX_OF_DATE_NO_NUTATION is given for the mean
+/// one; the ephemeris is chosen by iflag as for calc_ut.
+pub fn get_ayanamsa_ex_ut(
+    tjd_ut: f64,
+    iflag: CalculationFlags,
+) -> Result<AyanamshaResult, SweError> {
+    get_ayanamsa(tjd_ut, iflag, raw::swe_get_ayanamsa_ex_ut)
+}
+
+/// Ayanamsha of the sidereal mode applied by an Ephemeris at a julian day (ET)
+pub fn get_ayanamsa_ex(
+    tjd_et: f64,
+    iflag: CalculationFlags,
+) -> Result<AyanamshaResult, SweError> {
+    get_ayanamsa(tjd_et, iflag, raw::swe_get_ayanamsa_ex)
+}
+
+fn get_ayanamsa(
+    tjd: f64,
+    iflag: CalculationFlags,
+    function: unsafe extern "C" fn(f64, c_int, *mut f64, *mut c_char) -> c_int,
+) -> Result<AyanamshaResult, SweError> {
+    iflag.validate()?;
+    if !sidereal_mode_applied() {
+        return Err(SweError::InvalidArgument(
+            "ayanamsha requested without a sidereal mode, use \
+             Ephemeris::ayanamsha"
+                .to_string(),
+        ));
+    }
+    let mut ayanamsha = 0.0;
+    let mut serr = [0; 256];
+    let status = unsafe {
+        function(tjd, iflag.bits(), &mut ayanamsha, serr.as_mut_ptr())
+    };
+    Ok(AyanamshaResult {
+        ayanamsha,
+        warning: check_flags(iflag, status, &serr)?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         constants::{Ayanamsha, CalculationFlags},
         ensure_ephemeris_initialized,
+        ephemeris::with_sidereal_mode,
         sweconst::Calendar,
         swerust::handler_swe02::version,
     };
     use assert_approx_eq::assert_approx_eq;
+    use strum::IntoEnumIterator;
     use swerust::handler_swe08::{utc_time_zone, utc_to_jd};
 
+    const GALACTIC_CENTER: Option<Ayanamsha> =
+        Some(Ayanamsha::GalacticCenterMulaWilhelm);
+
     #[test]
     pub fn test_calc_ut_jupiter() {
         let _ = ensure_ephemeris_initialized();
         let date = get_test_date_time();
         let result =
-            calc_ut(date, Bodies::Jupiter, CalculationFlags::SPEED_PRECISION);
+            calc_ut(date, Bodies::Jupiter, CalculationFlags::SPEED_PRECISION)
+                .unwrap();
         println!("result: {:?}", result);
-        println!("Version {}", version());
+        println!("Version {}", version().unwrap());
         assert_approx_eq!(result.longitude, 78.40564471604982);
         assert_approx_eq!(result.latitude, -0.6995066677839287);
         assert_approx_eq!(result.distance_au, 4.123775036812139);
@@ -510,13 +504,8 @@ mod tests {
     pub fn test_jupiter_sidereal_calculations() {
         let _ = ensure_ephemeris_initialized();
         let date = get_test_date_time();
-        set_sidereal_mode_ext(
-            Ayanamsha::GALACTIC_CENTER_MULA_WILHELM,
-            0.0,
-            0.0,
-        );
         let flags = CalculationFlags::SIDEREAL_POSITIONS
-            + CalculationFlags::SPEED_PRECISION;
+            | CalculationFlags::SPEED_PRECISION;
         let expected_result = CalcUtResult {
             longitude: 58.003363748316765,
             latitude: -0.699506667783935,
@@ -524,22 +513,92 @@ mod tests {
             speed_longitude: -0.12303827375908057,
             speed_latitude: 0.001370052420148823,
             speed_distance_au: -0.004590422001902981,
-            status: 65858,
-            serr: "".to_string(),
+            flags: CalculationFlags::SWISS_EPHEMERIS
+                | CalculationFlags::MEAN_EQUINOX_OF_DATE_NO_NUTATION
+                | CalculationFlags::SPEED_PRECISION
+                | CalculationFlags::SIDEREAL_POSITIONS,
+            warning: None,
         };
-        let actual_result = calc_ut(date, Bodies::Jupiter, flags);
+        let actual_result = with_sidereal_mode(GALACTIC_CENTER, || {
+            calc_ut(date, Bodies::Jupiter, flags)
+        })
+        .unwrap();
 
         assert_eq!(expected_result, actual_result);
     }
 
+    #[test]
+    pub fn test_calc_ut_fortuna_part_diurnal() {
+        let _ = ensure_ephemeris_initialized();
+        let date = get_test_date_time();
+        let (lat, lng) = (43.084128, 25.5919228);
+        let flags = CalculationFlags::SPEED_PRECISION;
+        // the sun is still above the horizon at 16:10 local time
+        let sun = calc_ut(date, Bodies::Sun, flags).unwrap();
+        let moon = calc_ut(date, Bodies::Moon, flags).unwrap();
+        let asc = swerust::handler_swe14::houses(
+            date,
+            lat,
+            lng,
+            HouseSystem::Placidus,
+        )
+        .unwrap()
+        .cusps[1];
+
+        let result =
+            calc_ut_fp(date, lat, lng, HouseSystem::Placidus, flags).unwrap();
+
+        let expected = (asc + moon.longitude - sun.longitude).rem_euclid(360.0);
+        assert_approx_eq!(result.longitude, expected);
+    }
+
+    #[test]
+    pub fn test_calc_ut_fortuna_part_nocturnal() {
+        let _ = ensure_ephemeris_initialized();
+        // 22:10 local time, the sun is below the horizon
+        let date = get_test_date_time() + 0.25;
+        let (lat, lng) = (43.084128, 25.5919228);
+        let flags = CalculationFlags::SPEED_PRECISION;
+        let sun = calc_ut(date, Bodies::Sun, flags).unwrap();
+        let moon = calc_ut(date, Bodies::Moon, flags).unwrap();
+        let asc = swerust::handler_swe14::houses(
+            date,
+            lat,
+            lng,
+            HouseSystem::Placidus,
+        )
+        .unwrap()
+        .cusps[1];
+
+        let result =
+            calc_ut_fp(date, lat, lng, HouseSystem::Placidus, flags).unwrap();
+
+        let expected = (asc + sun.longitude - moon.longitude).rem_euclid(360.0);
+        assert_approx_eq!(result.longitude, expected);
+    }
+
+    #[test]
+    pub fn test_closest_distance_wraps_around() {
+        assert_approx_eq!(closest_distance(10.0, 300.0), -70.0);
+        assert_approx_eq!(closest_distance(300.0, 10.0), 70.0);
+        assert_approx_eq!(closest_distance(350.0, -350.0), 20.0);
+        assert_approx_eq!(znorm(-300.0), 60.0);
+        assert_approx_eq!(znorm(180.0), 180.0);
+        assert_approx_eq!(znorm(-180.0), 180.0);
+        // the sun 70° of RA west of the MC is above the equator's horizon
+        assert!(is_above_horizon(300.0, 0.0, 10.0, 0.0));
+        assert!(!is_above_horizon(250.0, 0.0, 10.0, 0.0));
+    }
+
     #[test]
     pub fn test_calc_ut_chiron() {
         let _ = ensure_ephemeris_initialized();
         let date = get_test_date_time();
         let result =
-            calc_ut(date, Bodies::Chiron, CalculationFlags::SPEED_PRECISION);
+            calc_ut(date, Bodies::Chiron, CalculationFlags::SPEED_PRECISION)
+                .unwrap();
         println!("result: {:?}", result);
-        println!("Version {}", version());
+        println!("Version {}", version().unwrap());
         assert_approx_eq!(result.longitude, 19.653590911537975);
         assert_approx_eq!(result.latitude, 1.0159864815728494);
         assert_approx_eq!(result.distance_au, 17.838917895481703);
@@ -552,13 +611,8 @@ mod tests {
     pub fn test_chiron_sidereal_calculations() {
         let _ = ensure_ephemeris_initialized();
         let date = get_test_date_time();
-        set_sidereal_mode_ext(
-            Ayanamsha::GALACTIC_CENTER_MULA_WILHELM,
-            0.0,
-            0.0,
-        );
         let flags = CalculationFlags::SIDEREAL_POSITIONS
-            + CalculationFlags::SPEED_PRECISION;
+            | CalculationFlags::SPEED_PRECISION;
         let expected_result = CalcUtResult {
             longitude: 359.2513099438049,
             latitude: 1.0159864815728492,
@@ -566,10 +620,16 @@ mod tests {
             speed_longitude: -0.03233629889300552,
             speed_latitude: -0.0018536526687435602,
             speed_distance_au: 0.010816164807525466,
-            status: 65858,
-            serr: "".to_string(),
+            flags: CalculationFlags::SWISS_EPHEMERIS
+                | CalculationFlags::MEAN_EQUINOX_OF_DATE_NO_NUTATION
+                | CalculationFlags::SPEED_PRECISION
+                | CalculationFlags::SIDEREAL_POSITIONS,
+            warning: None,
         };
-        let actual_result = calc_ut(date, Bodies::Chiron, flags);
+        let actual_result = with_sidereal_mode(GALACTIC_CENTER, || {
+            calc_ut(date, Bodies::Chiron, flags)
+        })
+        .unwrap();
 
         assert_eq!(actual_result, expected_result);
     }
@@ -582,34 +642,178 @@ mod tests {
             test_date_time,
             Bodies::Jupiter,
             CalculationFlags::EQUATORIAL_POSITIONS,
-        );
+        )
+        .unwrap();
         assert_approx_eq!(result.declination, 22.235712853294377);
     }
 
+    #[test]
+    fn test_calc_ut_illegal_flags() {
+        let _ = ensure_ephemeris_initialized();
+        let date = get_test_date_time();
+        let flags = CalculationFlags::JPL_EPHEMERIS
+            | CalculationFlags::MOSHIER_EPHEMERIS;
+        assert!(matches!(
+            calc_ut(date, Bodies::Sun, flags),
+            Err(SweError::InvalidArgument(_))
+        ));
+        let flags = CalculationFlags::HELIOCENTRIC_POSITIONS
+            | CalculationFlags::TOPOCENTRIC_POSITIONS;
+        assert!(calc_ut(date, Bodies::Mars, flags).is_err());
+
+        // a sidereal mode is only applied inside its scope
+        let flags = CalculationFlags::SIDEREAL_POSITIONS;
+        assert!(calc_ut(date, Bodies::Sun, flags).is_err());
+        let lahiri = Some(Ayanamsha::Lahiri);
+        let sun =
+            with_sidereal_mode(lahiri, || calc_ut(date, Bodies::Sun, flags));
+        assert!(sun.is_ok());
+        assert!(calc_ut(date, Bodies::Sun, flags).is_err());
+    }
+
+    #[test]
+    fn test_calc_ut_topo() {
+        let _ = ensure_ephemeris_initialized();
+        let date = get_test_date_time();
+        let observer = Observer::new(43.084128, 25.5919228, 265.0);
+
+        std::thread::spawn(move || {
+            // no observer set on a new thread
+            let flags = CalculationFlags::TOPOCENTRIC_POSITIONS;
+            assert!(calc_ut(date, Bodies::Moon, flags).is_err());
+
+            let moon =
+                calc_ut(date, Bodies::Moon, CalculationFlags::empty()).unwrap();
+            let topo_moon = calc_ut_topo(
+                date,
+                Bodies::Moon,
+                CalculationFlags::empty(),
+                &observer,
+            )
+            .unwrap();
+            assert!(topo_moon
+                .flags
+                .contains(CalculationFlags::TOPOCENTRIC_POSITIONS));
+            // the lunar parallax reaches about one degree
+            let parallax = (moon.longitude - topo_moon.longitude).abs();
+            let parallax = parallax.min(360.0 - parallax);
+            assert!(parallax > 0.01 && parallax < 1.1);
+            // at most one earth radius away from the geocentric distance
+            let difference = (topo_moon.distance_au - moon.distance_au).abs();
+            assert!(difference > 0.0 && difference < 4.3e-5);
+
+            let spica =
+                fixstar_ut("Spica", date, CalculationFlags::empty()).unwrap();
+            let topo_spica = fixstar_ut_topo(
+                "Spica",
+                date,
+                CalculationFlags::empty(),
+                &observer,
+            )
+            .unwrap();
+            assert!((spica.longitude - topo_spica.longitude).abs() < 1e-3);
+        })
+        .join()
+        .unwrap();
+    }
+
     #[test]
     pub fn test_get_ayanamsha_name() {
         let expected_result =
             Some("Dhruva/Gal.Center/Mula (Wilhelm)".to_string());
 
         let actual_result =
-            get_ayanamsha_name(Ayanamsha::GALACTIC_CENTER_MULA_WILHELM);
+            get_ayanamsha_name(Ayanamsha::GalacticCenterMulaWilhelm);
 
         assert_eq!(actual_result, expected_result);
     }
 
+    #[test]
+    fn test_ayanamsha_sid_modes() {
+        let predefined: Vec<Ayanamsha> = Ayanamsha::iter()
+            .filter(|a| !matches!(a, Ayanamsha::User { .. }))
+            .collect();
+        assert_eq!(predefined.len(), 47);
+        for (i, ayanamsha) in predefined.iter().enumerate() {
+            assert_eq!(ayanamsha.sid_mode(), i as i32);
+            assert_eq!(Ayanamsha::from_sid_mode(i as i32).unwrap(), *ayanamsha);
+            assert!(ayanamsha.name().is_some());
+        }
+        assert_eq!(
+            Ayanamsha::KrishnamurtiSenthilathiban.name().unwrap(),
+            "Krishnamurti-Senthilathiban"
+        );
+        assert_eq!(Ayanamsha::LahiriIcrc.name().unwrap(), "Lahiri ICRC");
+        let user = Ayanamsha::User {
+            t0: 2451545.0,
+            ayan_t0: 24.0,
+        };
+        assert_eq!(user.sid_mode(), 255);
+        assert_eq!(user.name(), None);
+        assert!(Ayanamsha::from_sid_mode(255).is_err());
+    }
+
+    #[test]
+    fn test_get_ayanamsa_ex_ut() {
+        let _ = ensure_ephemeris_initialized();
+        let date = get_test_date_time();
+        let flags = CalculationFlags::SWISS_EPHEMERIS;
+        let mean = flags | CalculationFlags::MEAN_EQUINOX_OF_DATE_NO_NUTATION;
+        assert!(get_ayanamsa_ex_ut(date, flags).is_err());
+
+        with_sidereal_mode(Some(Ayanamsha::Lahiri), || {
+            let true_ayanamsha = get_ayanamsa_ex_ut(date, flags).unwrap();
+            let mean_ayanamsha = get_ayanamsa_ex_ut(date, mean).unwrap();
+            assert_eq!(true_ayanamsha.warning, None);
+            // Lahiri was about 24°12' in 2024
+            assert!((mean_ayanamsha.ayanamsha - 24.2).abs() < 0.02);
+            // they differ by the nutation in longitude, the third value of
+            // Bodies::EclNut
+            let nutation = calc_ut(date, Bodies::EclNut, flags).unwrap();
+            assert_approx_eq!(
+                true_ayanamsha.ayanamsha - mean_ayanamsha.ayanamsha,
+                nutation.distance_au,
+                1e-6
+            );
+            // the true ayanamsha separates tropical and sidereal positions
+            let tropical = calc_ut(date, Bodies::Sun, flags).unwrap();
+            let sidereal = calc_ut(
+                date,
+                Bodies::Sun,
+                flags | CalculationFlags::SIDEREAL_POSITIONS,
+            )
+            .unwrap();
+            assert_approx_eq!(
+                tropical.longitude - sidereal.longitude,
+                true_ayanamsha.ayanamsha,
+                1e-6
+            );
+            let tjd_et = date + swerust::handler_swe08::deltat(date);
+            let et = get_ayanamsa_ex(tjd_et, flags).unwrap();
+            assert_approx_eq!(et.ayanamsha, true_ayanamsha.ayanamsha, 1e-6);
+        });
+
+        let user = Ayanamsha::User {
+            t0: 2451545.0,
+            ayan_t0: 24.0,
+        };
+        with_sidereal_mode(Some(user), || {
+            let user = get_ayanamsa_ex(2451545.0, mean).unwrap();
+            assert_approx_eq!(user.ayanamsha, 24.0, 1e-9);
+        });
+    }
+
     #[test]
     pub fn test_fixed_star_calculations_ut() {
         let _ = ensure_ephemeris_initialized();
         let date_jd = get_test_date_time();
-        set_sidereal_mode_ext(
-            Ayanamsha::GALACTIC_CENTER_MULA_WILHELM,
-            0.0,
-            0.0,
-        );
         let flags = CalculationFlags::SIDEREAL_POSITIONS
-            + CalculationFlags::SPEED_PRECISION;
+            | CalculationFlags::SPEED_PRECISION;
 
-        let result = fixstar_ut("Sirius", date_jd, flags);
+        let result = with_sidereal_mode(GALACTIC_CENTER, || {
+            fixstar_ut("Sirius", date_jd, flags)
+        })
+        .unwrap();
 
         let expected = FixStarResult {
             longitude: 84.02736235076539,
@@ -619,8 +823,11 @@ mod tests {
             speed_latitude: -4.694828181700263e-5,
             speed_distance_au: -0.012382055171557799,
             star_name: "Sirius,alCMa".to_string(),
-            serr: "".to_string(),
-            status: 65858,
+            flags: CalculationFlags::SWISS_EPHEMERIS
+                | CalculationFlags::MEAN_EQUINOX_OF_DATE_NO_NUTATION
+                | CalculationFlags::SPEED_PRECISION
+                | CalculationFlags::SIDEREAL_POSITIONS,
+            warning: None,
         };
 
         assert_fixstar(&result, &expected, 1e-6);
@@ -630,15 +837,13 @@ mod tests {
     pub fn test_fixed_star_calculations_ut_2() {
         let _ = ensure_ephemeris_initialized();
         let date_jd = get_test_date_time();
-        set_sidereal_mode_ext(
-            Ayanamsha::GALACTIC_CENTER_MULA_WILHELM,
-            0.0,
-            0.0,
-        );
         let flags = CalculationFlags::SIDEREAL_POSITIONS
-            + CalculationFlags::SPEED_PRECISION;
+            | CalculationFlags::SPEED_PRECISION;
 
-        let actul_result = fixstar2_ut("Sirius", date_jd, flags);
+        let actul_result = with_sidereal_mode(GALACTIC_CENTER, || {
+            fixstar2_ut("Sirius", date_jd, flags)
+        })
+        .unwrap();
 
         let expected = FixStarResult {
             longitude: 84.02736235076539,
@@ -648,8 +853,11 @@ mod tests {
             speed_latitude: -4.694828181700263e-5,
             speed_distance_au: -0.012382055171557799,
             star_name: "Sirius,alCMa".to_string(),
-            serr: "".to_string(),
-            status: 65858,
+            flags: CalculationFlags::SWISS_EPHEMERIS
+                | CalculationFlags::MEAN_EQUINOX_OF_DATE_NO_NUTATION
+                | CalculationFlags::SPEED_PRECISION
+                | CalculationFlags::SIDEREAL_POSITIONS,
+            warning: None,
         };
 
         assert_fixstar(&actul_result, &expected, 1e-6);
@@ -659,15 +867,13 @@ mod tests {
     pub fn test_fixed_star_calculations() {
         let _ = ensure_ephemeris_initialized();
         let date_jd = get_test_date_time();
-        set_sidereal_mode_ext(
-            Ayanamsha::GALACTIC_CENTER_MULA_WILHELM,
-            0.0,
-            0.0,
-        );
         let flags = CalculationFlags::SIDEREAL_POSITIONS
-            + CalculationFlags::SPEED_PRECISION;
+            | CalculationFlags::SPEED_PRECISION;
 
-        let result = fixstar("Sirius", date_jd, flags);
+        let result = with_sidereal_mode(GALACTIC_CENTER, || {
+            fixstar("Sirius", date_jd, flags)
+        })
+        .unwrap();
 
         let expected = FixStarResult {
             longitude: 84.02736224169713,
@@ -677,8 +883,9 @@ mod tests {
             speed_latitude: -4.695718165011796e-5,
             speed_distance_au: -0.012473006252649577,
             star_name: "Sirius,alCMa".to_string(),
-            serr: "".to_string(),
-            status: 65792,
+            flags: CalculationFlags::SPEED_PRECISION
+                | CalculationFlags::SIDEREAL_POSITIONS,
+            warning: None,
         };
 
         assert_fixstar(&result, &expected, 1e-6);
@@ -688,15 +895,13 @@ mod tests {
     pub fn test_fixed_star_calculations_2() {
         let _ = ensure_ephemeris_initialized();
         let date_jd = get_test_date_time();
-        set_sidereal_mode_ext(
-            Ayanamsha::GALACTIC_CENTER_MULA_WILHELM,
-            0.0,
-            0.0,
-        );
         let flags = CalculationFlags::SIDEREAL_POSITIONS
-            + CalculationFlags::SPEED_PRECISION;
+            | CalculationFlags::SPEED_PRECISION;
 
-        let result = fixstar2("Sirius", date_jd, flags);
+        let result = with_sidereal_mode(GALACTIC_CENTER, || {
+            fixstar2("Sirius", date_jd, flags)
+        })
+        .unwrap();
 
         let expected = FixStarResult {
             longitude: 84.02736224169713,
@@ -706,8 +911,9 @@ mod tests {
             speed_latitude: -4.695718165011796e-5,
             speed_distance_au: -0.012473006252649577,
             star_name: "Sirius,alCMa".to_string(),
-            serr: "".to_string(),
-            status: 65792,
+            flags: CalculationFlags::SPEED_PRECISION
+                | CalculationFlags::SIDEREAL_POSITIONS,
+            warning: None,
         };
 
         assert_fixstar(&result, &expected, 1e-6);
@@ -720,10 +926,8 @@ mod tests {
         let expected_result = FixStarMagResult {
             magnitude: -1.46,
             star_name: "Sirius,alCMa".to_owned(),
-            serr: "".to_owned(),
-            status: 0,
         };
-        let result = fixstar_mag("Sirius");
+        let result = fixstar_mag("Sirius").unwrap();
 
         assert_eq!(result, expected_result);
     }
@@ -735,10 +939,8 @@ mod tests {
         let expected_result = FixStarMagResult {
             magnitude: -1.46,
             star_name: "Sirius,alCMa".to_owned(),
-            serr: "".to_owned(),
-            status: 0,
         };
-        let result = fixstar2_mag("Sirius");
+        let result = fixstar2_mag("Sirius").unwrap();
 
         assert_eq!(result, expected_result);
     }
@@ -753,7 +955,8 @@ mod tests {
             utc_time_zone.min[0],
             utc_time_zone.sec[0],
             Calendar::Gregorian,
-        );
+        )
+        .unwrap();
         jd.julian_day_ut
     }
 
@@ -780,7 +983,7 @@ mod tests {
             "speed_distance_au mismatch"
         );
         assert_eq!(a.star_name, b.star_name);
-        assert_eq!(a.serr, b.serr);
-        assert_eq!(a.status, b.status);
+        assert_eq!(a.warning, b.warning);
+        assert_eq!(a.flags, b.flags);
     }
 }
@@ -1,27 +1,68 @@
+use crate::constants::CalculationFlags;
+use crate::ephemeris::with_observer;
+use crate::error::{ check_flags, SweError, SweWarning };
 use crate::raw::{ self, swe_rise_trans };
-use crate::sweconst::Bodies;
-use std::ffi::{ CStr, CString };
+use crate::sweconst::{ Bodies, Observer };
+use std::os::raw::c_char;
 use std::ptr;
 
-pub struct CalculationMethodsRiseTransit;
+pub mod eclipse;
+pub mod heliacal;
+pub mod horizon;
+
+bitflags! {
+    /// Event and options of rise_trans, exactly one event combined with | and any options
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct CalculationMethodsRiseTransit: i32 {
+        const RISE = 1;
+        const SET = 2;
+        const UPPER_MERIDIAN_TRANSIT = 4; // upper meridian transit (southern for northern geo. latitudes)
+        const LOWER_MERIDIAN_TRANSIT = 8; // lower meridian transit (northern, below the horizon)
+        // Below calculation methods can be used in conjunction with RISE & SET.
+        const DISC_CENTER = 256; // for rising or setting of disc center
+        const DISC_BOTTOM = 8192; // for rising or setting of lower limb of disc
+        const GEOCENTRIC = 128; // use geocentric (rather than topocentric) position of object and ignore its ecliptic latitude
+        const NO_REFRACTION = 512; // if refraction is not to be considered
+        const CIVIL_TWILIGHT = 1024; // in order to calculate civil twilight
+        const NAUTICAL_TWILIGHT = 2048; // in order to calculate nautical twilight
+        const ASTRONOMICAL_TWILIGHT = 4096; // in order to calculate astronomical twilight
+        const FIXED_DISC_SIZE = 16 * 1024; // neglect the effect of distance on disc size
+        // Consider Sun rising and setting when the center of solar disk is exactly over horizon. Also
+        // ignores atmospheric refraction and uses geocentric rather than topocentric calculations.
+        const HINDU_RISING = Self::DISC_CENTER.bits() | Self::NO_REFRACTION.bits() | Self::GEOCENTRIC.bits(); // risings according to Hindu astrology
+    }
+}
 
 impl CalculationMethodsRiseTransit {
-    pub const RISE: i32 = 1;
-    pub const SET: i32 = 2;
-    pub const UPPER_MERIDIAN_TRANSIT: i32 = 4; // upper meridian transit (southern for northern geo. latitudes)
-    pub const LOWER_MERIDIAN_TRANSIT: i32 = 8; // lower meridian transit (northern, below the horizon)
-    // Below calculation methods can be used in conjunction with RISE & SET.
-    pub const DISC_CENTER: i32 = 256; // for rising or setting of disc center
-    pub const DISC_BOTTOM: i32 = 8192; // for rising or setting of lower limb of disc
-    pub const GEOCENTRIC: i32 = 128; // use geocentric (rather than topocentric) position of object and ignore its ecliptic latitude
-    pub const NO_REFRACTION: i32 = 512; // if refraction is not to be considered
-    pub const CIVIL_TWILIGHT: i32 = 1024; // in order to calculate civil twilight
-    pub const NAUTICAL_TWILIGHT: i32 = 2048; // in order to calculate nautical twilight
-    pub const ASTRONOMICAL_TWILIGHT: i32 = 4096; // in order to calculate astronomical twilight
-    pub const FIXED_DISC_SIZE: i32 = 16 * 1024; // neglect the effect of distance on disc size
-    // Consider Sun rising and setting when the center of solar disk is exactly over horizon. Also
-    // ignores atmospheric refraction and uses geocentric rather than topocentric calculations.
-    pub const HINDU_RISING: i32 = 256 + 512 + 128; // Composition of DISC_CENTER + NO_REFRACTION + GEOCENTRIC risings according to Hindu astrology
+    const EVENTS: CalculationMethodsRiseTransit = CalculationMethodsRiseTransit::RISE
+        .union(CalculationMethodsRiseTransit::SET)
+        .union(CalculationMethodsRiseTransit::UPPER_MERIDIAN_TRANSIT)
+        .union(CalculationMethodsRiseTransit::LOWER_MERIDIAN_TRANSIT);
+
+    /// Rejects methods without an event or with several events, and twilights
+    /// or disc options with a meridian transit
+    pub fn validate(self) -> Result<(), SweError> {
+        let events = self & CalculationMethodsRiseTransit::EVENTS;
+        if events.bits().count_ones() != 1 {
+            return Err(
+                SweError::InvalidArgument(
+                    format!("exactly one of RISE, SET or a meridian transit is required: {:?}", self)
+                )
+            );
+        }
+        let transit = events.intersects(
+            CalculationMethodsRiseTransit::UPPER_MERIDIAN_TRANSIT |
+                CalculationMethodsRiseTransit::LOWER_MERIDIAN_TRANSIT
+        );
+        if transit && !(self - events).is_empty() {
+            return Err(
+                SweError::InvalidArgument(
+                    format!("options only apply to RISE and SET: {:?}", self)
+                )
+            );
+        }
+        Ok(())
+    }
 }
 
 /*
@@ -34,69 +75,83 @@ pub struct PhenoUtResult {
     pub elongation_of_planet: f64,
     pub apparent_dimaeter_of_disc: f64,
     pub apparent_magnitude: f64,
-    pub status: i32,
-    pub serr: String,
+    pub flags: CalculationFlags,
+    pub warning: Option<SweWarning>,
 }
 
-pub fn pheno_ut(tjd_ut: f64, ipl: Bodies, iflag: i32) -> PhenoUtResult {
+pub fn pheno_ut(
+    tjd_ut: f64,
+    ipl: Bodies,
+    iflag: CalculationFlags
+) -> Result<PhenoUtResult, SweError> {
+    iflag.validate()?;
     let mut attr: [f64; 20] = [0.0; 20];
-    let mut serr = [0; 255];
-    let result = unsafe {
-        let p_attr = attr.as_mut_ptr();
-        let p_serr = serr.as_mut_ptr();
-        let status = raw::swe_pheno_ut(tjd_ut, ipl as i32, iflag, p_attr, p_serr);
-        let s_serr = CString::from(CStr::from_ptr(p_serr)).to_str().unwrap().to_string();
-        PhenoUtResult {
-            phase_angle: attr[0],
-            phase_illuminated: attr[1],
-            elongation_of_planet: attr[2],
-            apparent_dimaeter_of_disc: attr[3],
-            apparent_magnitude: attr[4],
-            serr: s_serr,
-            status,
-        }
+    let mut serr = [0; 256];
+    let status = unsafe {
+        raw::swe_pheno_ut(tjd_ut, ipl as i32, iflag.bits(), attr.as_mut_ptr(), serr.as_mut_ptr())
     };
-    result
+    let warning = check_flags(iflag, status, &serr)?;
+    Ok(PhenoUtResult {
+        phase_angle: attr[0],
+        phase_illuminated: attr[1],
+        elongation_of_planet: attr[2],
+        apparent_dimaeter_of_disc: attr[3],
+        apparent_magnitude: attr[4],
+        flags: CalculationFlags::from_status(status),
+        warning,
+    })
+}
+
+/// Phenomena of a body seen by an observer (topocentric)
+pub fn pheno_ut_topo(
+    tjd_ut: f64,
+    ipl: Bodies,
+    iflag: CalculationFlags,
+    observer: &Observer
+) -> Result<PhenoUtResult, SweError> {
+    with_observer(observer, || {
+        pheno_ut(tjd_ut, ipl, iflag | CalculationFlags::TOPOCENTRIC_POSITIONS)
+    })
 }
 
 /// Calculates rising, setting and meridian transits
+/// Returns SweError::NotFound when the body is circumpolar. A message about
+/// the ephemeris used is ignored, calc_ut returns it as a warning.
 pub fn rise_trans(
     tjd_ut: f64,
     planet: Bodies,
     latitude: f64,
     longitude: f64,
     altitude: f64,
-    calculation_flag: i32,
-    calculation_method: i32
-) -> Result<f64, String> {
+    calculation_flag: CalculationFlags,
+    calculation_method: CalculationMethodsRiseTransit
+) -> Result<f64, SweError> {
+    calculation_flag.validate()?;
+    calculation_method.validate()?;
     let geopos = [longitude, latitude, altitude]; // Observer's position
     let mut tret = 0.0; // Will store rise and set times
-    let error_buffer = [0i8; 256]; // Error buffer
-
-    unsafe {
-        let pointer_to_error_buffer = error_buffer.as_ptr() as *mut i8;
+    let mut error_buffer = [0 as c_char; 256]; // Error buffer
 
+    let status = unsafe {
         swe_rise_trans(
             tjd_ut,
             planet as i32,
             ptr::null(),
-            calculation_flag,
-            calculation_method,
+            calculation_flag.bits(),
+            calculation_method.bits(),
             geopos.as_ptr(),
             0.0, // estimated by underlying C function
             0.0, // estimated by underlying C function
             &mut tret,
-            pointer_to_error_buffer
+            error_buffer.as_mut_ptr()
+        )
+    };
+    if status == -2 {
+        return Err(
+            SweError::NotFound(format!("no rise or set of {}, the body is circumpolar", planet))
         );
-
-        let error_buffer_str = CString::from(CStr::from_ptr(pointer_to_error_buffer))
-            .to_str()
-            .unwrap()
-            .to_string();
-        if !error_buffer_str.is_empty() {
-            return Err(error_buffer_str);
-        }
     }
+    check_flags(calculation_flag, status, &error_buffer)?;
 
     Ok(tret)
 }
@@ -108,13 +163,13 @@ pub fn calculate_rise_and_set(
     latitude: f64,
     longitude: f64,
     altitude: f64,
-    calculation_flag: i32,
-    additional_calculation_methods: i32
-) -> Result<(f64, f64), String> {
-    let rise_flag = CalculationMethodsRiseTransit::RISE + additional_calculation_methods;
-    let set_flag = CalculationMethodsRiseTransit::SET + additional_calculation_methods;
+    calculation_flag: CalculationFlags,
+    additional_calculation_methods: CalculationMethodsRiseTransit
+) -> Result<(f64, f64), SweError> {
+    let rise_flag = CalculationMethodsRiseTransit::RISE | additional_calculation_methods;
+    let set_flag = CalculationMethodsRiseTransit::SET | additional_calculation_methods;
 
-    let rising_result = rise_trans(
+    let rising_jd = rise_trans(
         julian_day_utc,
         planet,
         latitude,
@@ -122,9 +177,9 @@ pub fn calculate_rise_and_set(
         altitude,
         calculation_flag,
         rise_flag
-    );
+    )?;
 
-    let setting_result = rise_trans(
+    let setting_jd = rise_trans(
         julian_day_utc,
         planet,
         latitude,
@@ -132,21 +187,7 @@ pub fn calculate_rise_and_set(
         altitude,
         calculation_flag,
         set_flag
-    );
-
-    let rising_jd = match rising_result {
-        Ok(rising) => rising,
-        Err(err) => {
-            return Err(err);
-        }
-    };
-
-    let setting_jd = match setting_result {
-        Ok(setting) => setting,
-        Err(err) => {
-            return Err(err);
-        }
-    };
+    )?;
 
     Ok((rising_jd, setting_jd))
 }
@@ -186,14 +227,14 @@ mod tests {
 
     #[test]
     pub fn test_calculate_rise_and_set_for_sun() {
-        let julian_day = utc_to_jd(2025, 3, 14, 0, 0, 0.0, Calendar::Gregorian);
+        let julian_day = utc_to_jd(2025, 3, 14, 0, 0, 0.0, Calendar::Gregorian).unwrap();
         let julian_day_utc = julian_day.julian_day_ut;
         let planet = Bodies::Sun;
         let latitude = 43.084128;
         let longitude = 25.5919228;
         let altitude = 265.0;
         let calculation_flag = CalculationFlags::SWISS_EPHEMERIS;
-        let additional_calculation_methods = 0;
+        let additional_calculation_methods = CalculationMethodsRiseTransit::empty();
         let expected_result = (2460748.688380363, 2460749.182606324);
 
         let actual_result = calculate_rise_and_set(
@@ -212,4 +253,58 @@ mod tests {
         assert!(actual_result.is_ok());
         assert_eq!(actual_result.unwrap(), expected_result);
     }
+
+    #[test]
+    pub fn test_rise_trans_illegal_methods() {
+        let julian_day_utc = 2460748.9208356882;
+        let rise_trans_with = |calculation_method| {
+            rise_trans(
+                julian_day_utc,
+                Bodies::Sun,
+                43.084128,
+                25.5919228,
+                265.0,
+                CalculationFlags::SWISS_EPHEMERIS,
+                calculation_method
+            )
+        };
+
+        assert!(
+            matches!(
+                rise_trans_with(CalculationMethodsRiseTransit::RISE | CalculationMethodsRiseTransit::SET),
+                Err(SweError::InvalidArgument(_))
+            )
+        );
+        assert!(rise_trans_with(CalculationMethodsRiseTransit::DISC_CENTER).is_err());
+        assert!(
+            rise_trans_with(
+                CalculationMethodsRiseTransit::UPPER_MERIDIAN_TRANSIT |
+                    CalculationMethodsRiseTransit::CIVIL_TWILIGHT
+            ).is_err()
+        );
+        assert!(
+            rise_trans_with(
+                CalculationMethodsRiseTransit::RISE | CalculationMethodsRiseTransit::HINDU_RISING
+            ).is_ok()
+        );
+    }
+
+    #[test]
+    pub fn test_pheno_ut_topo() {
+        let julian_day_utc = 2460748.9208356882;
+        let observer = Observer::new(43.084128, 25.5919228, 265.0);
+
+        let moon = pheno_ut(julian_day_utc, Bodies::Moon, CalculationFlags::empty()).unwrap();
+        let topo_moon = pheno_ut_topo(
+            julian_day_utc,
+            Bodies::Moon,
+            CalculationFlags::empty(),
+            &observer
+        ).unwrap();
+
+        // the distance to the observer changes the size of the disc by up to 2%
+        let ratio = topo_moon.apparent_dimaeter_of_disc / moon.apparent_dimaeter_of_disc;
+        assert!(ratio != 1.0 && (ratio - 1.0).abs() < 0.02);
+        assert!((topo_moon.phase_illuminated - moon.phase_illuminated).abs() < 0.01);
+    }
 }
@@ -0,0 +1,923 @@
+use crate::constants::CalculationFlags;
+use crate::error::{serr_message, SweError};
+use crate::raw;
+use std::os::raw::c_char;
+use std::ptr;
+
+/*
+ * 7. Eclipses
+ *
+ * Solar eclipses: global search (swe_sol_eclipse_when_glob), local search
+ * (swe_sol_eclipse_when_loc), geographic position of the central line at a
+ * given time (swe_sol_eclipse_where) and local circumstances at a given time
+ * (swe_sol_eclipse_how).
+ *
+ * Lunar eclipses: global search (swe_lun_eclipse_when), search of the next
+ * eclipse visible from a place (swe_lun_eclipse_when_loc) and circumstances
+ * at a given time (swe_lun_eclipse_how).
+ */
+
+/// Eclipse type bits returned by (and passed to) the eclipse functions
+pub struct EclipseFlags;
+
+impl EclipseFlags {
+    pub const CENTRAL: i32 = 1;
+    pub const NONCENTRAL: i32 = 2;
+    pub const TOTAL: i32 = 4;
+    pub const ANNULAR: i32 = 8;
+    pub const PARTIAL: i32 = 16;
+    pub const HYBRID: i32 = 32; // annular-total
+    pub const PENUMBRAL: i32 = 64;
+    pub const ALL_TYPES_SOLAR: i32 = 1 | 2 | 4 | 8 | 16 | 32;
+    pub const ALL_TYPES_LUNAR: i32 = 4 | 16 | 64;
+    pub const VISIBLE: i32 = 128;
+    pub const MAX_VISIBLE: i32 = 256;
+    pub const FIRST_CONTACT_VISIBLE: i32 = 512; // begin of partial eclipse
+    pub const SECOND_CONTACT_VISIBLE: i32 = 1024; // begin of total eclipse
+    pub const THIRD_CONTACT_VISIBLE: i32 = 2048; // end of total eclipse
+    pub const FOURTH_CONTACT_VISIBLE: i32 = 4096; // end of partial eclipse
+    pub const PENUMBRAL_BEGIN_VISIBLE: i32 = 8192; // lunar eclipses only
+    pub const PENUMBRAL_END_VISIBLE: i32 = 16384; // lunar eclipses only
+}
+
+/// Kind of a solar eclipse
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter)]
+pub enum SolarEclipseKind {
+    Total,
+    Annular,
+    Partial,
+    Hybrid,
+}
+
+impl SolarEclipseKind {
+    /// Eclipse type bit of the kind, usable as search filter
+    pub fn flag(self) -> i32 {
+        match self {
+            SolarEclipseKind::Total => EclipseFlags::TOTAL,
+            SolarEclipseKind::Annular => EclipseFlags::ANNULAR,
+            SolarEclipseKind::Partial => EclipseFlags::PARTIAL,
+            SolarEclipseKind::Hybrid => EclipseFlags::HYBRID,
+        }
+    }
+
+    /// Kind from the bits returned by the C functions, None if no eclipse
+    pub fn from_flags(retflag: i32) -> Option<SolarEclipseKind> {
+        // Hybrid eclipses are also flagged total or annular at some places,
+        // so it has to be checked first
+        if retflag & EclipseFlags::HYBRID != 0 {
+            Some(SolarEclipseKind::Hybrid)
+        } else if retflag & EclipseFlags::TOTAL != 0 {
+            Some(SolarEclipseKind::Total)
+        } else if retflag & EclipseFlags::ANNULAR != 0 {
+            Some(SolarEclipseKind::Annular)
+        } else if retflag & EclipseFlags::PARTIAL != 0 {
+            Some(SolarEclipseKind::Partial)
+        } else {
+            None
+        }
+    }
+}
+
+/// Attributes of a solar eclipse (attr[0..11] of the C functions)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolarEclipseAttributes {
+    pub magnitude: f64, // fraction of solar diameter covered by moon
+    pub diameter_ratio: f64, // ratio of lunar diameter to solar one
+    pub obscuration: f64, // fraction of solar disc covered by moon
+    pub core_shadow_diameter_km: f64,
+    pub sun_azimuth: f64,
+    pub sun_true_altitude: f64,
+    pub sun_apparent_altitude: f64,
+    pub moon_elongation: f64, // angular distance of moon from sun in degrees
+    pub magnitude_nasa: f64,
+    pub saros_series: i32,
+    pub saros_member: i32,
+}
+
+impl SolarEclipseAttributes {
+    fn from_attr(attr: &[f64; 20]) -> SolarEclipseAttributes {
+        SolarEclipseAttributes {
+            magnitude: attr[0],
+            diameter_ratio: attr[1],
+            obscuration: attr[2],
+            core_shadow_diameter_km: attr[3],
+            sun_azimuth: attr[4],
+            sun_true_altitude: attr[5],
+            sun_apparent_altitude: attr[6],
+            moon_elongation: attr[7],
+            magnitude_nasa: attr[8],
+            saros_series: attr[9] as i32,
+            saros_member: attr[10] as i32,
+        }
+    }
+}
+
+/// Solar eclipse found anywhere on earth
+/// Contact times are Julian days UT, None when the phase does not occur
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlobalSolarEclipse {
+    pub kind: SolarEclipseKind,
+    pub central: bool,
+    pub maximum: f64,
+    pub local_noon: Option<f64>,
+    pub begin: Option<f64>,
+    pub end: Option<f64>,
+    pub totality_begin: Option<f64>,
+    pub totality_end: Option<f64>,
+    pub center_line_begin: Option<f64>,
+    pub center_line_end: Option<f64>,
+    pub greatest_eclipse: GeoPoint, // where the maximum is seen
+    pub attributes: SolarEclipseAttributes, // at greatest eclipse
+}
+
+/// Solar eclipse seen from a geographic position
+/// Contact times are Julian days UT, None when the contact does not occur
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalSolarEclipse {
+    pub kind: SolarEclipseKind,
+    pub visibility: i32, // EclipseFlags::*_VISIBLE bits
+    pub maximum: f64,
+    pub first_contact: Option<f64>,
+    pub second_contact: Option<f64>,
+    pub third_contact: Option<f64>,
+    pub fourth_contact: Option<f64>,
+    pub sunrise: Option<f64>, // between first and fourth contact
+    pub sunset: Option<f64>,  // between first and fourth contact
+    pub attributes: SolarEclipseAttributes, // at local maximum
+}
+
+/// Geographic position, longitude east positive, latitude north positive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    pub longitude: f64,
+    pub latitude: f64,
+}
+
+/// Solar eclipse at a given time: kind and position of the central line
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolarEclipseWhere {
+    pub kind: Option<SolarEclipseKind>,
+    pub central: bool,
+    pub position: GeoPoint,
+    pub attributes: SolarEclipseAttributes,
+}
+
+/// Point of the path of the central line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CentralLinePoint {
+    pub julian_day_ut: f64,
+    pub position: GeoPoint,
+}
+
+/// Zero is returned by the C library for phases that do not occur
+fn contact_time(tjd: f64) -> Option<f64> {
+    if tjd == 0.0 {
+        None
+    } else {
+        Some(tjd)
+    }
+}
+
+fn swe_error(serr: &[c_char]) -> SweError {
+    match serr_message(serr) {
+        Ok(message) => SweError::from_serr(&message),
+        Err(e) => e,
+    }
+}
+
+fn not_found() -> SweError {
+    SweError::NotFound("no eclipse found".to_string())
+}
+
+/// Next (or previous) solar eclipse anywhere on earth
+/// eclipse_kind: None for any kind of eclipse
+pub fn sol_eclipse_when_glob(
+    tjd_start: f64,
+    iflag: CalculationFlags,
+    eclipse_kind: Option<SolarEclipseKind>,
+    backward: bool,
+) -> Result<GlobalSolarEclipse, SweError> {
+    iflag.validate()?;
+    let mut tret = [0.0; 10];
+    let mut serr = [0 as c_char; 256];
+    let ifltype = eclipse_kind.map_or(0, |k| k.flag());
+    let retflag = unsafe {
+        raw::swe_sol_eclipse_when_glob(
+            tjd_start,
+            iflag.bits(),
+            ifltype,
+            tret.as_mut_ptr(),
+            backward as i32,
+            serr.as_mut_ptr(),
+        )
+    };
+    if retflag < 0 {
+        return Err(swe_error(&serr));
+    }
+    let kind = SolarEclipseKind::from_flags(retflag).ok_or_else(not_found)?;
+    let maximum = sol_eclipse_where(tret[0], iflag)?;
+    Ok(GlobalSolarEclipse {
+        kind,
+        central: retflag & EclipseFlags::CENTRAL != 0,
+        maximum: tret[0],
+        local_noon: contact_time(tret[1]),
+        begin: contact_time(tret[2]),
+        end: contact_time(tret[3]),
+        totality_begin: contact_time(tret[4]),
+        totality_end: contact_time(tret[5]),
+        center_line_begin: contact_time(tret[6]),
+        center_line_end: contact_time(tret[7]),
+        greatest_eclipse: maximum.position,
+        attributes: maximum.attributes,
+    })
+}
+
+/// Next (or previous) solar eclipse visible from a geographic position
+pub fn sol_eclipse_when_loc(
+    tjd_start: f64,
+    iflag: CalculationFlags,
+    latitude: f64,
+    longitude: f64,
+    altitude: f64,
+    backward: bool,
+) -> Result<LocalSolarEclipse, SweError> {
+    iflag.validate()?;
+    let geopos = [longitude, latitude, altitude];
+    let mut tret = [0.0; 10];
+    let mut attr = [0.0; 20];
+    let mut serr = [0 as c_char; 256];
+    let retflag = unsafe {
+        raw::swe_sol_eclipse_when_loc(
+            tjd_start,
+            iflag.bits(),
+            geopos.as_ptr(),
+            tret.as_mut_ptr(),
+            attr.as_mut_ptr(),
+            backward as i32,
+            serr.as_mut_ptr(),
+        )
+    };
+    if retflag < 0 {
+        return Err(swe_error(&serr));
+    }
+    let kind = SolarEclipseKind::from_flags(retflag).ok_or_else(not_found)?;
+    Ok(LocalSolarEclipse {
+        kind,
+        visibility: retflag
+            & (EclipseFlags::VISIBLE
+                | EclipseFlags::MAX_VISIBLE
+                | EclipseFlags::FIRST_CONTACT_VISIBLE
+                | EclipseFlags::SECOND_CONTACT_VISIBLE
+                | EclipseFlags::THIRD_CONTACT_VISIBLE
+                | EclipseFlags::FOURTH_CONTACT_VISIBLE),
+        maximum: tret[0],
+        first_contact: contact_time(tret[1]),
+        second_contact: contact_time(tret[2]),
+        third_contact: contact_time(tret[3]),
+        fourth_contact: contact_time(tret[4]),
+        sunrise: contact_time(tret[5]),
+        sunset: contact_time(tret[6]),
+        attributes: SolarEclipseAttributes::from_attr(&attr),
+    })
+}
+
+/// Position of the central line (or of the maximum, for non central
+/// eclipses) and attributes of a solar eclipse at a given time
+pub fn sol_eclipse_where(
+    tjd_ut: f64,
+    iflag: CalculationFlags,
+) -> Result<SolarEclipseWhere, SweError> {
+    iflag.validate()?;
+    let mut geopos = [0.0; 10];
+    let mut attr = [0.0; 20];
+    let mut serr = [0 as c_char; 256];
+    let retflag = unsafe {
+        raw::swe_sol_eclipse_where(
+            tjd_ut,
+            iflag.bits(),
+            geopos.as_mut_ptr(),
+            attr.as_mut_ptr(),
+            serr.as_mut_ptr(),
+        )
+    };
+    if retflag < 0 {
+        return Err(swe_error(&serr));
+    }
+    Ok(SolarEclipseWhere {
+        kind: SolarEclipseKind::from_flags(retflag),
+        central: retflag & EclipseFlags::CENTRAL != 0,
+        position: GeoPoint {
+            longitude: geopos[0],
+            latitude: geopos[1],
+        },
+        attributes: SolarEclipseAttributes::from_attr(&attr),
+    })
+}
+
+/// Local circumstances of a solar eclipse at a given time and place
+/// Ok(None) if no eclipse is visible from the position at that time
+pub fn sol_eclipse_how(
+    tjd_ut: f64,
+    iflag: CalculationFlags,
+    latitude: f64,
+    longitude: f64,
+    altitude: f64,
+) -> Result<Option<(SolarEclipseKind, SolarEclipseAttributes)>, SweError> {
+    iflag.validate()?;
+    let geopos = [longitude, latitude, altitude];
+    let mut attr = [0.0; 20];
+    let mut serr = [0 as c_char; 256];
+    let retflag = unsafe {
+        raw::swe_sol_eclipse_how(
+            tjd_ut,
+            iflag.bits(),
+            geopos.as_ptr(),
+            attr.as_mut_ptr(),
+            serr.as_mut_ptr(),
+        )
+    };
+    if retflag < 0 {
+        return Err(swe_error(&serr));
+    }
+    Ok(SolarEclipseKind::from_flags(retflag)
+        .map(|kind| (kind, SolarEclipseAttributes::from_attr(&attr))))
+}
+
+/// Geographic path of the central line, sampled every `step_minutes`
+/// between center line begin and end
+/// Empty for non central eclipses
+pub fn sol_eclipse_central_line(
+    eclipse: &GlobalSolarEclipse,
+    iflag: CalculationFlags,
+    step_minutes: f64,
+) -> Result<Vec<CentralLinePoint>, SweError> {
+    let (begin, end) =
+        match (eclipse.center_line_begin, eclipse.center_line_end) {
+            (Some(begin), Some(end)) if eclipse.central => (begin, end),
+            _ => return Ok(Vec::new()),
+        };
+    if step_minutes <= 0.0 {
+        return Err(SweError::InvalidArgument(
+            "step_minutes must be positive".to_string(),
+        ));
+    }
+    let step = step_minutes / (24.0 * 60.0);
+    let mut path = Vec::new();
+    let mut tjd = begin;
+    while tjd < end {
+        let w = sol_eclipse_where(tjd, iflag)?;
+        if w.central {
+            path.push(CentralLinePoint {
+                julian_day_ut: tjd,
+                position: w.position,
+            });
+        }
+        tjd += step;
+    }
+    let w = sol_eclipse_where(end, iflag)?;
+    if w.central {
+        path.push(CentralLinePoint {
+            julian_day_ut: end,
+            position: w.position,
+        });
+    }
+    Ok(path)
+}
+
+/// Kind of a lunar eclipse
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter)]
+pub enum LunarEclipseKind {
+    Total,
+    Partial,
+    Penumbral,
+}
+
+impl LunarEclipseKind {
+    /// Eclipse type bit of the kind, usable as search filter
+    pub fn flag(self) -> i32 {
+        match self {
+            LunarEclipseKind::Total => EclipseFlags::TOTAL,
+            LunarEclipseKind::Partial => EclipseFlags::PARTIAL,
+            LunarEclipseKind::Penumbral => EclipseFlags::PENUMBRAL,
+        }
+    }
+
+    /// Kind from the bits returned by the C functions, None if no eclipse
+    pub fn from_flags(retflag: i32) -> Option<LunarEclipseKind> {
+        if retflag & EclipseFlags::TOTAL != 0 {
+            Some(LunarEclipseKind::Total)
+        } else if retflag & EclipseFlags::PARTIAL != 0 {
+            Some(LunarEclipseKind::Partial)
+        } else if retflag & EclipseFlags::PENUMBRAL != 0 {
+            Some(LunarEclipseKind::Penumbral)
+        } else {
+            None
+        }
+    }
+}
+
+/// Lunar eclipse
+/// Contact times are Julian days UT, None when the phase does not occur
+/// Magnitudes are taken at maximum eclipse
+#[derive(Debug, Clone, PartialEq)]
+pub struct LunarEclipse {
+    pub kind: LunarEclipseKind,
+    pub maximum: f64,
+    pub penumbral_begin: Option<f64>,
+    pub partial_begin: Option<f64>,
+    pub total_begin: Option<f64>,
+    pub total_end: Option<f64>,
+    pub partial_end: Option<f64>,
+    pub penumbral_end: Option<f64>,
+    pub umbral_magnitude: f64,
+    pub penumbral_magnitude: f64,
+    pub saros_series: i32,
+    pub saros_member: i32,
+}
+
+impl LunarEclipse {
+    fn new(
+        kind: LunarEclipseKind,
+        tret: &[f64; 10],
+        attr: &[f64; 20],
+    ) -> LunarEclipse {
+        LunarEclipse {
+            kind,
+            maximum: tret[0],
+            penumbral_begin: contact_time(tret[6]),
+            partial_begin: contact_time(tret[2]),
+            total_begin: contact_time(tret[4]),
+            total_end: contact_time(tret[5]),
+            partial_end: contact_time(tret[3]),
+            penumbral_end: contact_time(tret[7]),
+            umbral_magnitude: attr[0],
+            penumbral_magnitude: attr[1],
+            saros_series: attr[9] as i32,
+            saros_member: attr[10] as i32,
+        }
+    }
+}
+
+/// Lunar eclipse seen from a geographic position
+/// Phases that happen while the Moon is below the horizon are None
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalLunarEclipse {
+    pub eclipse: LunarEclipse,
+    pub visibility: i32,       // EclipseFlags::*_VISIBLE bits
+    pub moonrise: Option<f64>, // if it occurs during the eclipse
+    pub moonset: Option<f64>,  // if it occurs during the eclipse
+    pub moon_azimuth: f64,     // at (visible) maximum
+    pub moon_true_altitude: f64,
+    pub moon_apparent_altitude: f64,
+    pub moon_above_horizon: bool,
+}
+
+/// Circumstances of a lunar eclipse at a given time and place
+#[derive(Debug, Clone, PartialEq)]
+pub struct LunarEclipseCircumstances {
+    pub kind: Option<LunarEclipseKind>, // None if there is no eclipse
+    pub umbral_magnitude: f64,
+    pub penumbral_magnitude: f64,
+    pub moon_azimuth: f64,
+    pub moon_true_altitude: f64,
+    pub moon_apparent_altitude: f64,
+    pub moon_above_horizon: bool,
+    pub distance_from_opposition: f64,
+}
+
+/// Next (or previous) lunar eclipse
+/// eclipse_kind: None for any kind of eclipse
+pub fn lun_eclipse_when(
+    tjd_start: f64,
+    iflag: CalculationFlags,
+    eclipse_kind: Option<LunarEclipseKind>,
+    backward: bool,
+) -> Result<LunarEclipse, SweError> {
+    iflag.validate()?;
+    let mut tret = [0.0; 10];
+    let mut attr = [0.0; 20];
+    let mut serr = [0 as c_char; 256];
+    let ifltype = eclipse_kind.map_or(0, |k| k.flag());
+    let retflag = unsafe {
+        raw::swe_lun_eclipse_when(
+            tjd_start,
+            iflag.bits(),
+            ifltype,
+            tret.as_mut_ptr(),
+            backward as i32,
+            serr.as_mut_ptr(),
+        )
+    };
+    if retflag < 0 {
+        return Err(swe_error(&serr));
+    }
+    let kind = LunarEclipseKind::from_flags(retflag).ok_or_else(not_found)?;
+    // Magnitudes at maximum, geopos is not needed for them
+    let retflag = unsafe {
+        raw::swe_lun_eclipse_how(
+            tret[0],
+            iflag.bits(),
+            ptr::null(),
+            attr.as_mut_ptr(),
+            serr.as_mut_ptr(),
+        )
+    };
+    if retflag < 0 {
+        return Err(swe_error(&serr));
+    }
+    Ok(LunarEclipse::new(kind, &tret, &attr))
+}
+
+/// Next (or previous) lunar eclipse visible from a geographic position
+pub fn lun_eclipse_when_loc(
+    tjd_start: f64,
+    iflag: CalculationFlags,
+    latitude: f64,
+    longitude: f64,
+    altitude: f64,
+    backward: bool,
+) -> Result<LocalLunarEclipse, SweError> {
+    iflag.validate()?;
+    let geopos = [longitude, latitude, altitude];
+    let mut tret = [0.0; 10];
+    let mut attr = [0.0; 20];
+    let mut serr = [0 as c_char; 256];
+    let retflag = unsafe {
+        raw::swe_lun_eclipse_when_loc(
+            tjd_start,
+            iflag.bits(),
+            geopos.as_ptr(),
+            tret.as_mut_ptr(),
+            attr.as_mut_ptr(),
+            backward as i32,
+            serr.as_mut_ptr(),
+        )
+    };
+    if retflag < 0 {
+        return Err(swe_error(&serr));
+    }
+    let kind = LunarEclipseKind::from_flags(retflag).ok_or_else(not_found)?;
+    Ok(LocalLunarEclipse {
+        eclipse: LunarEclipse::new(kind, &tret, &attr),
+        visibility: retflag
+            & (EclipseFlags::VISIBLE
+                | EclipseFlags::MAX_VISIBLE
+                | EclipseFlags::FIRST_CONTACT_VISIBLE
+                | EclipseFlags::SECOND_CONTACT_VISIBLE
+                | EclipseFlags::THIRD_CONTACT_VISIBLE
+                | EclipseFlags::FOURTH_CONTACT_VISIBLE
+                | EclipseFlags::PENUMBRAL_BEGIN_VISIBLE
+                | EclipseFlags::PENUMBRAL_END_VISIBLE),
+        moonrise: contact_time(tret[8]),
+        moonset: contact_time(tret[9]),
+        moon_azimuth: attr[4],
+        moon_true_altitude: attr[5],
+        moon_apparent_altitude: attr[6],
+        moon_above_horizon: attr[6] > 0.0,
+    })
+}
+
+/// Circumstances of a lunar eclipse at a given time and place
+pub fn lun_eclipse_how(
+    tjd_ut: f64,
+    iflag: CalculationFlags,
+    latitude: f64,
+    longitude: f64,
+    altitude: f64,
+) -> Result<LunarEclipseCircumstances, SweError> {
+    iflag.validate()?;
+    let geopos = [longitude, latitude, altitude];
+    let mut attr = [0.0; 20];
+    let mut serr = [0 as c_char; 256];
+    let retflag = unsafe {
+        raw::swe_lun_eclipse_how(
+            tjd_ut,
+            iflag.bits(),
+            geopos.as_ptr(),
+            attr.as_mut_ptr(),
+            serr.as_mut_ptr(),
+        )
+    };
+    if retflag < 0 {
+        return Err(swe_error(&serr));
+    }
+    Ok(LunarEclipseCircumstances {
+        kind: LunarEclipseKind::from_flags(retflag),
+        umbral_magnitude: attr[0],
+        penumbral_magnitude: attr[1],
+        moon_azimuth: attr[4],
+        moon_true_altitude: attr[5],
+        moon_apparent_altitude: attr[6],
+        moon_above_horizon: attr[6] > 0.0,
+        distance_from_opposition: attr[7],
+    })
+}
+
+/// Iterator over successive lunar eclipses, see `lun_eclipses`
+#[derive(Debug, Clone)]
+pub struct LunarEclipseIter {
+    tjd: f64,
+    iflag: CalculationFlags,
+    eclipse_kind: Option<LunarEclipseKind>,
+    backward: bool,
+    done: bool,
+}
+
+impl Iterator for LunarEclipseIter {
+    type Item = Result<LunarEclipse, SweError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = lun_eclipse_when(
+            self.tjd,
+            self.iflag,
+            self.eclipse_kind,
+            self.backward,
+        );
+        match &result {
+            // Two lunar eclipses are at least a lunation apart, so one day
+            // past the maximum is safe for the next search
+            Ok(eclipse) if self.backward => self.tjd = eclipse.maximum - 1.0,
+            Ok(eclipse) => self.tjd = eclipse.maximum + 1.0,
+            Err(_) => self.done = true,
+        }
+        Some(result)
+    }
+}
+
+/// Walks forward (or backward) through lunar eclipses from a start date
+/// The iterator is endless, stop it with `take` or `take_while`
+pub fn lun_eclipses(
+    tjd_start: f64,
+    iflag: CalculationFlags,
+    eclipse_kind: Option<LunarEclipseKind>,
+    backward: bool,
+) -> LunarEclipseIter {
+    LunarEclipseIter {
+        tjd: tjd_start,
+        iflag,
+        eclipse_kind,
+        backward,
+        done: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::CalculationFlags, ensure_ephemeris_initialized,
+        sweconst::Calendar, swerust::handler_swe08::julday,
+    };
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    pub fn test_sol_eclipse_when_glob_total_eclipse_2024() {
+        let _ = ensure_ephemeris_initialized();
+        let start = julday(2024, 1, 1, 0.0, Calendar::Gregorian);
+
+        let result = sol_eclipse_when_glob(
+            start,
+            CalculationFlags::SWISS_EPHEMERIS,
+            None,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(result.kind, SolarEclipseKind::Total);
+        assert!(result.central);
+        assert_approx_eq!(result.maximum, 2460409.2620414216, 1e-6);
+        assert_approx_eq!(result.begin.unwrap(), 2460409.1543619735, 1e-6);
+        assert_approx_eq!(result.end.unwrap(), 2460409.3695437848, 1e-6);
+        assert_approx_eq!(
+            result.greatest_eclipse.longitude,
+            -104.15419028719273,
+            1e-4
+        );
+        assert_approx_eq!(
+            result.greatest_eclipse.latitude,
+            25.285524752542727,
+            1e-4
+        );
+        assert_approx_eq!(
+            result.attributes.magnitude_nasa,
+            1.0574758568287457,
+            1e-6
+        );
+        assert_eq!(result.attributes.saros_series, 139);
+        assert_eq!(result.attributes.saros_member, 30);
+    }
+
+    #[test]
+    pub fn test_sol_eclipse_when_glob_backward_annular() {
+        let _ = ensure_ephemeris_initialized();
+        let start = julday(2024, 1, 1, 0.0, Calendar::Gregorian);
+
+        let result = sol_eclipse_when_glob(
+            start,
+            CalculationFlags::SWISS_EPHEMERIS,
+            Some(SolarEclipseKind::Annular),
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(result.kind, SolarEclipseKind::Annular);
+        assert!(result.maximum < start);
+    }
+
+    #[test]
+    pub fn test_sol_eclipse_when_loc_dallas() {
+        let _ = ensure_ephemeris_initialized();
+        let start = julday(2024, 1, 1, 0.0, Calendar::Gregorian);
+
+        let result = sol_eclipse_when_loc(
+            start,
+            CalculationFlags::SWISS_EPHEMERIS,
+            32.7767,
+            -96.797,
+            139.0,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(result.kind, SolarEclipseKind::Total);
+        assert!(result.visibility & EclipseFlags::MAX_VISIBLE != 0);
+        assert!(result.second_contact.is_some());
+        assert!(result.sunrise.is_none());
+        assert_approx_eq!(result.maximum, 2460409.279618405, 1e-6);
+    }
+
+    #[test]
+    pub fn test_sol_eclipse_how_not_visible() {
+        let _ = ensure_ephemeris_initialized();
+        let tjd = 2460409.2620414216;
+
+        let result = sol_eclipse_how(
+            tjd,
+            CalculationFlags::SWISS_EPHEMERIS,
+            43.084128,
+            25.5919228,
+            265.0,
+        )
+        .unwrap();
+
+        assert!(result.is_none());
+    }
+
+    #[test]
+    pub fn test_sol_eclipse_central_line() {
+        let _ = ensure_ephemeris_initialized();
+        let start = julday(2024, 1, 1, 0.0, Calendar::Gregorian);
+        let eclipse = sol_eclipse_when_glob(
+            start,
+            CalculationFlags::SWISS_EPHEMERIS,
+            None,
+            false,
+        )
+        .unwrap();
+
+        let path = sol_eclipse_central_line(
+            &eclipse,
+            CalculationFlags::SWISS_EPHEMERIS,
+            10.0,
+        )
+        .unwrap();
+
+        assert!(path.len() > 10);
+        assert!(path.first().unwrap().position.longitude < -130.0);
+        assert!(path.last().unwrap().position.longitude > -50.0);
+    }
+
+    #[test]
+    pub fn test_lun_eclipse_when_total_eclipse_2025() {
+        let _ = ensure_ephemeris_initialized();
+        let start = julday(2025, 1, 1, 0.0, Calendar::Gregorian);
+
+        let result = lun_eclipse_when(
+            start,
+            CalculationFlags::SWISS_EPHEMERIS,
+            None,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(result.kind, LunarEclipseKind::Total);
+        assert_approx_eq!(result.maximum, 2460748.7908112835, 1e-6);
+        assert_approx_eq!(
+            result.total_begin.unwrap(),
+            2460748.7681311197,
+            1e-6
+        );
+        assert_approx_eq!(result.total_end.unwrap(), 2460748.81350111, 1e-6);
+        assert_approx_eq!(result.umbral_magnitude, 1.1780582156204729, 1e-6);
+        assert_approx_eq!(result.penumbral_magnitude, 2.25973026653161, 1e-6);
+        assert_eq!(result.saros_series, 123);
+    }
+
+    #[test]
+    pub fn test_lun_eclipse_when_loc() {
+        let _ = ensure_ephemeris_initialized();
+        let start = julday(2025, 4, 1, 0.0, Calendar::Gregorian);
+
+        let result = lun_eclipse_when_loc(
+            start,
+            CalculationFlags::SWISS_EPHEMERIS,
+            43.084128,
+            25.5919228,
+            265.0,
+            false,
+        )
+        .unwrap();
+
+        // The 2025-09-07 total eclipse, the Moon rises during the partial phase
+        assert_eq!(result.eclipse.kind, LunarEclipseKind::Total);
+        assert_approx_eq!(result.eclipse.maximum, 2460926.258220985, 1e-6);
+        assert!(result.eclipse.partial_begin.is_none());
+        assert!(result.eclipse.total_begin.is_some());
+        assert!(result.visibility & EclipseFlags::SECOND_CONTACT_VISIBLE != 0);
+        assert!(result.visibility & EclipseFlags::THIRD_CONTACT_VISIBLE != 0);
+        assert!(result.moonset.is_none());
+
+        // at maximum the Moon is well above the horizon, 1.5 hours after
+        // moonrise
+        let moonrise = result.moonrise.unwrap();
+        assert!(result.eclipse.maximum - moonrise > 1.0 / 24.0);
+        assert!(result.moon_apparent_altitude > 10.0);
+        let maximum = lun_eclipse_how(
+            result.eclipse.maximum,
+            CalculationFlags::SWISS_EPHEMERIS,
+            43.084128,
+            25.5919228,
+            265.0,
+        )
+        .unwrap();
+        assert_eq!(maximum.kind, Some(LunarEclipseKind::Total));
+        assert!(maximum.moon_above_horizon);
+        assert_approx_eq!(
+            maximum.moon_apparent_altitude,
+            result.moon_apparent_altitude,
+            1e-6
+        );
+    }
+
+    #[test]
+    pub fn test_lun_eclipse_how() {
+        let _ = ensure_ephemeris_initialized();
+        let start = julday(2025, 1, 1, 0.0, Calendar::Gregorian);
+        let eclipse = lun_eclipse_when(
+            start,
+            CalculationFlags::SWISS_EPHEMERIS,
+            None,
+            false,
+        )
+        .unwrap();
+
+        let result = lun_eclipse_how(
+            eclipse.maximum,
+            CalculationFlags::SWISS_EPHEMERIS,
+            43.084128,
+            25.5919228,
+            265.0,
+        )
+        .unwrap();
+
+        // Total eclipse, but the Moon is below the horizon of the observer
+        assert_eq!(result.kind, None);
+        assert!(!result.moon_above_horizon);
+        assert_approx_eq!(result.umbral_magnitude, 1.1780582156204729, 1e-6);
+    }
+
+    #[test]
+    pub fn test_lun_eclipses_forward_and_backward() {
+        let _ = ensure_ephemeris_initialized();
+        let start = julday(2025, 1, 1, 0.0, Calendar::Gregorian);
+
+        let forward: Vec<LunarEclipse> =
+            lun_eclipses(start, CalculationFlags::SWISS_EPHEMERIS, None, false)
+                .take(4)
+                .collect::<Result<_, _>>()
+                .unwrap();
+        let backward: Vec<LunarEclipse> =
+            lun_eclipses(start, CalculationFlags::SWISS_EPHEMERIS, None, true)
+                .take(2)
+                .collect::<Result<_, _>>()
+                .unwrap();
+
+        let kinds: Vec<LunarEclipseKind> =
+            forward.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                LunarEclipseKind::Total,
+                LunarEclipseKind::Total,
+                LunarEclipseKind::Total,
+                LunarEclipseKind::Partial
+            ]
+        );
+        assert_approx_eq!(forward[1].maximum, 2460926.258220985, 1e-6);
+        assert_approx_eq!(backward[0].maximum, 2460571.6140924064, 1e-6);
+        assert_eq!(backward[1].kind, LunarEclipseKind::Penumbral);
+    }
+}
@@ -0,0 +1,597 @@
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+use crate::constants::CalculationFlags;
+use crate::error::{serr_message, SweError};
+use crate::raw;
+use crate::sweconst::{Bodies, Observer};
+
+/*
+ * 7. Heliacal events and visibility
+ *
+ * Heliacal risings and settings (swe_heliacal_ut), the visibility
+ * conditions around them (swe_heliacal_pheno_ut) and the limiting magnitude
+ * of the sky at a given time (swe_vis_limit_mag), following the models of
+ * swehel.c.
+ *
+ * The visibility depends on the place, the atmosphere and the eyes of the
+ * observer, gathered in HeliacalConditions. The event of the Moon searched by
+ * EveningFirst is the first crescent after the new moon, MorningLast the last
+ * crescent before it.
+ */
+
+/// Heliacal event (TypeEvent of swe_heliacal_ut)
+#[derive(
+    Debug, Clone, Copy, PartialEq, Display, EnumIter, Serialize, Deserialize,
+)]
+pub enum HeliacalEvent {
+    HeliacalRising = 1,  // morning first
+    HeliacalSetting = 2, // evening last
+    EveningFirst = 3,
+    MorningLast = 4,
+    AcronychalRising = 5, // rising at sunset, outer planets and stars
+    AcronychalSetting = 6, // setting at sunrise (cosmical setting)
+}
+
+bitflags! {
+    /// Options of the heliacal functions
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct HeliacalFlags: i32 {
+        const LONG_SEARCH = 128; // search over more than 5 synodic periods
+        const HIGH_PRECISION = 256;
+        const NO_DETAILS = 1024; // only the beginning of visibility
+        const SEARCH_1_PERIOD = 2048; // search within one synodic period
+        const VISLIM_DARK = 4096; // limiting magnitude without the Sun
+        const VISLIM_NOMOON = 8192; // limiting magnitude without the Moon
+    }
+}
+
+/// SE_HELFLAG_OPTICAL_PARAMS, set when an instrument is used
+const OPTICAL_PARAMS: i32 = 512;
+
+/// Body or fixed star of the heliacal functions
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeliacalObject {
+    Body(Bodies),
+    Star(String), // name of sefstars.txt, e.g. "Sirius"
+}
+
+impl HeliacalObject {
+    /// Name understood by swehel.c
+    fn name(&self) -> Result<CString, SweError> {
+        let name = match self {
+            HeliacalObject::Body(body) => match body {
+                Bodies::Sun => "sun",
+                Bodies::Moon => "moon",
+                Bodies::Mercury => "mercury",
+                Bodies::Venus => "venus",
+                Bodies::Mars => "mars",
+                Bodies::Jupiter => "jupiter",
+                Bodies::Saturn => "saturn",
+                Bodies::Uranus => "uranus",
+                Bodies::Neptune => "neptune",
+                _ => {
+                    return Err(SweError::InvalidBody(format!(
+                        "no heliacal events for {}",
+                        body
+                    )))
+                },
+            },
+            HeliacalObject::Star(star) => star.as_str(),
+        };
+        // swehel.c copies the name into buffers of 256 bytes
+        if name.len() > 255 {
+            return Err(SweError::InvalidArgument(
+                "star is longer than 255 bytes".to_string(),
+            ));
+        }
+        CString::new(name).map_err(|_| {
+            SweError::InvalidArgument("star contains a nul byte".to_string())
+        })
+    }
+}
+
+/// State of the atmosphere (datm[4])
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AtmosphericConditions {
+    pub pressure: f64, // mbar (hPa), 0 = estimate from the eye height
+    pub temperature: f64, // degrees Celsius
+    pub relative_humidity: f64, // %
+    // meteorological range in km if >= 1, total extinction coefficient if
+    // between 0 and 1, -1 to compute the extinction from the other values
+    pub visibility_range: f64,
+}
+
+impl Default for AtmosphericConditions {
+    fn default() -> Self {
+        AtmosphericConditions {
+            pressure: 1013.25,
+            temperature: 15.0,
+            relative_humidity: 40.0,
+            visibility_range: 40.0,
+        }
+    }
+}
+
+/// Optical aid of the observer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Optics {
+    NakedEye,
+    Instrument {
+        binocular: bool,
+        magnification: f64,
+        aperture_mm: f64,
+        transmission: f64, // 0 .. 1
+    },
+}
+
+/// Eyes of the observer (dobs[6])
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Eyesight {
+    pub age: f64,           // years, 23 is the optimum
+    pub snellen_ratio: f64, // visual acuity, 1 is normal
+    pub optics: Optics,
+}
+
+impl Default for Eyesight {
+    /// Experienced sky observer of ancient times, naked eye
+    fn default() -> Self {
+        Eyesight {
+            age: 36.0,
+            snellen_ratio: 1.0,
+            optics: Optics::NakedEye,
+        }
+    }
+}
+
+/// Place, atmosphere and eyes of an observation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeliacalConditions {
+    pub location: Observer, // altitude is the eye height above sea level
+    pub atmosphere: AtmosphericConditions,
+    pub eyesight: Eyesight,
+}
+
+impl HeliacalConditions {
+    /// Default atmosphere and eyesight at a place
+    pub fn new(location: Observer) -> HeliacalConditions {
+        HeliacalConditions {
+            location,
+            atmosphere: AtmosphericConditions::default(),
+            eyesight: Eyesight::default(),
+        }
+    }
+
+    fn geopos(&self) -> [f64; 3] {
+        self.location.geopos()
+    }
+
+    fn datm(&self) -> [f64; 4] {
+        let atmosphere = &self.atmosphere;
+        [
+            atmosphere.pressure,
+            atmosphere.temperature,
+            atmosphere.relative_humidity,
+            atmosphere.visibility_range,
+        ]
+    }
+
+    fn dobs(&self) -> [f64; 6] {
+        let eyesight = &self.eyesight;
+        match eyesight.optics {
+            Optics::NakedEye => {
+                [eyesight.age, eyesight.snellen_ratio, 0.0, 0.0, 0.0, 0.0]
+            },
+            Optics::Instrument {
+                binocular,
+                magnification,
+                aperture_mm,
+                transmission,
+            } => [
+                eyesight.age,
+                eyesight.snellen_ratio,
+                if binocular { 1.0 } else { 0.0 },
+                magnification,
+                aperture_mm,
+                transmission,
+            ],
+        }
+    }
+
+    /// helflag of the C functions
+    fn helflag(
+        &self,
+        iflag: CalculationFlags,
+        helflag: HeliacalFlags,
+    ) -> Result<i32, SweError> {
+        iflag.validate()?;
+        let ephemerides = CalculationFlags::JPL_EPHEMERIS
+            | CalculationFlags::SWISS_EPHEMERIS
+            | CalculationFlags::MOSHIER_EPHEMERIS;
+        if !ephemerides.contains(iflag) {
+            return Err(SweError::InvalidArgument(format!(
+                "only the ephemeris can be selected for heliacal events: {:?}",
+                iflag
+            )));
+        }
+        let optical = match self.eyesight.optics {
+            Optics::NakedEye => 0,
+            Optics::Instrument { .. } => OPTICAL_PARAMS,
+        };
+        Ok(iflag.bits() | helflag.bits() | optical)
+    }
+}
+
+/// Visibility period of a heliacal event, Julian days UT
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeliacalResult {
+    pub event: HeliacalEvent,
+    pub start_visibility: f64,
+    pub optimum_visibility: Option<f64>, // None with NO_DETAILS
+    pub end_visibility: Option<f64>,
+}
+
+/// Visibility of an object at a time (darr[0..28] of swe_heliacal_pheno_ut)
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeliacalPhenomena {
+    pub object_altitude: f64, // topocentric, unrefracted
+    pub object_apparent_altitude: f64,
+    pub object_geocentric_altitude: f64,
+    pub object_azimuth: f64,
+    pub sun_altitude: f64,
+    pub sun_azimuth: f64,
+    pub topocentric_arcus_visionis: f64,
+    pub arcus_visionis: f64,          // geocentric
+    pub azimuth_difference: f64,      // object - Sun
+    pub elongation_in_longitude: f64, // object - Sun
+    pub extinction_coefficient: f64,
+    pub min_topocentric_arcus_visionis: f64,
+    pub first_visibility: f64, // Julian day UT
+    pub best_visibility: f64,
+    pub last_visibility: f64,
+    pub best_visibility_yallop: f64,
+    pub moon_crescent_width: f64, // degrees
+    pub yallop_q: f64,
+    pub yallop_criterion: f64,
+    pub parallax: f64,
+    pub magnitude: f64,
+    pub object_rise_set: f64, // Julian day UT
+    pub sun_rise_set: f64,
+    pub lag: f64, // days between the rise or set of the object and the Sun
+    pub visibility_duration: f64, // days
+    pub moon_crescent_length: f64, // degrees
+    pub elongation: f64,
+    pub illumination: f64, // %
+}
+
+/// Limiting magnitude of the sky at the position of an object
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VisibilityLimit {
+    pub limiting_magnitude: f64,
+    pub object_altitude: f64,
+    pub object_azimuth: f64,
+    pub sun_altitude: f64,
+    pub sun_azimuth: f64,
+    pub moon_altitude: f64,
+    pub moon_azimuth: f64,
+    pub object_magnitude: f64,
+    pub scotopic: bool,   // night vision
+    pub near_limit: bool, // near the limit of photopic and scotopic vision
+}
+
+impl VisibilityLimit {
+    pub fn is_visible(&self) -> bool {
+        self.object_magnitude < self.limiting_magnitude
+    }
+}
+
+/// Next heliacal event of an object after tjd_start (Julian day UT)
+pub fn heliacal_ut(
+    tjd_start: f64,
+    object: &HeliacalObject,
+    event: HeliacalEvent,
+    conditions: &HeliacalConditions,
+    iflag: CalculationFlags,
+    helflag: HeliacalFlags,
+) -> Result<HeliacalResult, SweError> {
+    let helflag = conditions.helflag(iflag, helflag)?;
+    let mut name = object.name()?.into_bytes_with_nul();
+    let mut geopos = conditions.geopos();
+    let mut datm = conditions.datm();
+    let mut dobs = conditions.dobs();
+    let mut dret = [0.0; 50];
+    let mut serr = [0 as c_char; 256];
+    let status = unsafe {
+        raw::swe_heliacal_ut(
+            tjd_start,
+            geopos.as_mut_ptr(),
+            datm.as_mut_ptr(),
+            dobs.as_mut_ptr(),
+            name.as_mut_ptr() as *mut c_char,
+            event as i32,
+            helflag,
+            dret.as_mut_ptr(),
+            serr.as_mut_ptr(),
+        )
+    };
+    if status < 0 {
+        return Err(SweError::from_status(status, &serr_message(&serr)?));
+    }
+    let time = |t: f64| if t > 0.0 { Some(t) } else { None };
+    Ok(HeliacalResult {
+        event,
+        start_visibility: dret[0],
+        optimum_visibility: time(dret[1]),
+        end_visibility: time(dret[2]),
+    })
+}
+
+/// Visibility conditions of an object at a time (Julian day UT) for a
+/// heliacal event
+pub fn heliacal_pheno_ut(
+    tjd_ut: f64,
+    object: &HeliacalObject,
+    event: HeliacalEvent,
+    conditions: &HeliacalConditions,
+    iflag: CalculationFlags,
+    helflag: HeliacalFlags,
+) -> Result<HeliacalPhenomena, SweError> {
+    let helflag = conditions.helflag(iflag, helflag)?;
+    let mut name = object.name()?.into_bytes_with_nul();
+    let mut geopos = conditions.geopos();
+    let mut datm = conditions.datm();
+    let mut dobs = conditions.dobs();
+    let mut darr = [0.0; 50];
+    let mut serr = [0 as c_char; 256];
+    let status = unsafe {
+        raw::swe_heliacal_pheno_ut(
+            tjd_ut,
+            geopos.as_mut_ptr(),
+            datm.as_mut_ptr(),
+            dobs.as_mut_ptr(),
+            name.as_mut_ptr() as *mut c_char,
+            event as i32,
+            helflag,
+            darr.as_mut_ptr(),
+            serr.as_mut_ptr(),
+        )
+    };
+    if status < 0 {
+        return Err(SweError::from_status(status, &serr_message(&serr)?));
+    }
+    Ok(HeliacalPhenomena {
+        object_altitude: darr[0],
+        object_apparent_altitude: darr[1],
+        object_geocentric_altitude: darr[2],
+        object_azimuth: darr[3],
+        sun_altitude: darr[4],
+        sun_azimuth: darr[5],
+        topocentric_arcus_visionis: darr[6],
+        arcus_visionis: darr[7],
+        azimuth_difference: darr[8],
+        elongation_in_longitude: darr[9],
+        extinction_coefficient: darr[10],
+        min_topocentric_arcus_visionis: darr[11],
+        first_visibility: darr[12],
+        best_visibility: darr[13],
+        last_visibility: darr[14],
+        best_visibility_yallop: darr[15],
+        moon_crescent_width: darr[16],
+        yallop_q: darr[17],
+        yallop_criterion: darr[18],
+        parallax: darr[19],
+        magnitude: darr[20],
+        object_rise_set: darr[21],
+        sun_rise_set: darr[22],
+        lag: darr[23],
+        visibility_duration: darr[24],
+        moon_crescent_length: darr[25],
+        elongation: darr[26],
+        illumination: darr[27],
+    })
+}
+
+/// Limiting magnitude at the position of an object at a time (Julian day
+/// UT). Returns SweError::NotFound when the object is below the horizon.
+pub fn vis_limit_mag(
+    tjd_ut: f64,
+    object: &HeliacalObject,
+    conditions: &HeliacalConditions,
+    iflag: CalculationFlags,
+    helflag: HeliacalFlags,
+) -> Result<VisibilityLimit, SweError> {
+    let helflag = conditions.helflag(iflag, helflag)?;
+    let mut name = object.name()?.into_bytes_with_nul();
+    let mut geopos = conditions.geopos();
+    let mut datm = conditions.datm();
+    let mut dobs = conditions.dobs();
+    let mut dret = [0.0; 10];
+    let mut serr = [0 as c_char; 256];
+    let status = unsafe {
+        raw::swe_vis_limit_mag(
+            tjd_ut,
+            geopos.as_mut_ptr(),
+            datm.as_mut_ptr(),
+            dobs.as_mut_ptr(),
+            name.as_mut_ptr() as *mut c_char,
+            helflag,
+            dret.as_mut_ptr(),
+            serr.as_mut_ptr(),
+        )
+    };
+    match status {
+        -2 => Err(SweError::NotFound(format!(
+            "{:?} is below the horizon",
+            object
+        ))),
+        s if s < 0 => Err(SweError::from_status(s, &serr_message(&serr)?)),
+        _ => Ok(VisibilityLimit {
+            limiting_magnitude: dret[0],
+            object_altitude: dret[1],
+            object_azimuth: dret[2],
+            sun_altitude: dret[3],
+            sun_azimuth: dret[4],
+            moon_altitude: dret[5],
+            moon_azimuth: dret[6],
+            object_magnitude: dret[7],
+            scotopic: status & 1 != 0,
+            near_limit: status & 2 != 0,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ensure_ephemeris_initialized;
+    use crate::sweconst::Calendar;
+    use crate::swerust::handler_swe08::julday;
+
+    fn cairo() -> HeliacalConditions {
+        HeliacalConditions::new(Observer::new(30.0444, 31.2357, 23.0))
+    }
+
+    #[test]
+    fn test_first_crescent() {
+        let _ = ensure_ephemeris_initialized();
+        // new moon of April 8, 2024 (total solar eclipse)
+        let new_moon = julday(2024, 4, 8, 18.3, Calendar::Gregorian);
+        let crescent = heliacal_ut(
+            new_moon,
+            &HeliacalObject::Body(Bodies::Moon),
+            HeliacalEvent::EveningFirst,
+            &cairo(),
+            CalculationFlags::SWISS_EPHEMERIS,
+            HeliacalFlags::empty(),
+        )
+        .unwrap();
+
+        // seen in the evening of April 9
+        let april_9 = julday(2024, 4, 9, 12.0, Calendar::Gregorian);
+        assert!(crescent.start_visibility > april_9);
+        assert!(crescent.start_visibility < april_9 + 0.5);
+
+        let pheno = heliacal_pheno_ut(
+            crescent.start_visibility,
+            &HeliacalObject::Body(Bodies::Moon),
+            HeliacalEvent::EveningFirst,
+            &cairo(),
+            CalculationFlags::SWISS_EPHEMERIS,
+            HeliacalFlags::empty(),
+        )
+        .unwrap();
+        // a thin crescent above the horizon after sunset
+        assert!(pheno.object_altitude > 0.0);
+        assert!(pheno.sun_altitude < 0.0);
+        assert!(pheno.illumination > 0.0 && pheno.illumination < 5.0);
+    }
+
+    #[test]
+    fn test_heliacal_rising_of_sirius() {
+        let _ = ensure_ephemeris_initialized();
+        let start = julday(2024, 6, 1, 0.0, Calendar::Gregorian);
+        let rising = heliacal_ut(
+            start,
+            &HeliacalObject::Star("Sirius".to_string()),
+            HeliacalEvent::HeliacalRising,
+            &cairo(),
+            CalculationFlags::SWISS_EPHEMERIS,
+            HeliacalFlags::NO_DETAILS,
+        )
+        .unwrap();
+
+        // early August at the latitude of Cairo, in the morning twilight
+        let august_1 = julday(2024, 8, 1, 0.0, Calendar::Gregorian);
+        assert!((rising.start_visibility - august_1).abs() < 10.0);
+        assert_eq!(rising.optimum_visibility, None);
+    }
+
+    #[test]
+    fn test_vis_limit_mag() {
+        let _ = ensure_ephemeris_initialized();
+        let evening = julday(2024, 1, 15, 19.0, Calendar::Gregorian);
+        let jupiter = HeliacalObject::Body(Bodies::Jupiter);
+        let limit = vis_limit_mag(
+            evening,
+            &jupiter,
+            &cairo(),
+            CalculationFlags::SWISS_EPHEMERIS,
+            HeliacalFlags::empty(),
+        )
+        .unwrap();
+
+        // Jupiter in the evening sky
+        assert!(limit.is_visible());
+        assert!(limit.object_magnitude < -2.0);
+        assert!(limit.sun_altitude < -18.0);
+
+        let mut eyes = cairo();
+        eyes.eyesight = Eyesight {
+            optics: Optics::Instrument {
+                binocular: true,
+                magnification: 7.0,
+                aperture_mm: 50.0,
+                transmission: 0.8,
+            },
+            ..Eyesight::default()
+        };
+        let binoculars = vis_limit_mag(
+            evening,
+            &jupiter,
+            &eyes,
+            CalculationFlags::SWISS_EPHEMERIS,
+            HeliacalFlags::empty(),
+        )
+        .unwrap();
+        assert!(binoculars.limiting_magnitude > limit.limiting_magnitude);
+
+        // below the horizon in the morning
+        assert!(matches!(
+            vis_limit_mag(
+                evening + 0.375,
+                &jupiter,
+                &cairo(),
+                CalculationFlags::SWISS_EPHEMERIS,
+                HeliacalFlags::empty(),
+            ),
+            Err(SweError::NotFound(_))
+        ));
+        assert!(vis_limit_mag(
+            evening,
+            &HeliacalObject::Body(Bodies::Sun),
+            &cairo(),
+            CalculationFlags::SWISS_EPHEMERIS,
+            HeliacalFlags::empty(),
+        )
+        .is_err());
+        assert!(matches!(
+            vis_limit_mag(
+                evening,
+                &HeliacalObject::Body(Bodies::Chiron),
+                &cairo(),
+                CalculationFlags::SWISS_EPHEMERIS,
+                HeliacalFlags::empty(),
+            ),
+            Err(SweError::InvalidBody(_))
+        ));
+        assert!(matches!(
+            vis_limit_mag(
+                evening,
+                &HeliacalObject::Star("x".repeat(256)),
+                &cairo(),
+                CalculationFlags::SWISS_EPHEMERIS,
+                HeliacalFlags::empty(),
+            ),
+            Err(SweError::InvalidArgument(_))
+        ));
+        assert!(vis_limit_mag(
+            evening,
+            &jupiter,
+            &cairo(),
+            CalculationFlags::SPEED_PRECISION,
+            HeliacalFlags::empty(),
+        )
+        .is_err());
+    }
+}
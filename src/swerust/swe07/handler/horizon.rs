@@ -0,0 +1,453 @@
+use crate::constants::CalculationFlags;
+use crate::ephemeris::exclusive;
+use crate::error::SweError;
+use crate::raw;
+use crate::sweconst::{Bodies, Observer};
+use crate::swerust::handler_swe03::{calc_ut_topo, fixstar_ut_topo};
+
+/*
+ * Horizontal coordinates
+ *
+ * Azimuth and altitude of a position given in ecliptic or equatorial
+ * coordinates (swe_azalt), the reverse transformation (swe_azalt_rev) and
+ * atmospheric refraction (swe_refrac_extended).
+ *
+ * The Swiss Ephemeris measures the azimuth from the south point, clockwise
+ * via west. Use `HorizontalPosition::azimuth_from_north` for the usual
+ * compass bearing.
+ *
+ * swe_azalt takes the lapse rate from a setting of the library
+ * (swe_set_lapse_rate); azalt sets it only for its own call and puts the
+ * default back, while holding the lock of the C library.
+ */
+
+/// Default lapse rate of the C library (SE_LAPSE_RATE), in K/m
+const DEFAULT_LAPSE_RATE: f64 = 0.0065;
+
+/// Coordinate system of the position given to `azalt` or returned by
+/// `azalt_rev`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HorizonFrame {
+    Ecliptic = 0,   // SE_ECL2HOR / SE_HOR2ECL
+    Equatorial = 1, // SE_EQU2HOR / SE_HOR2EQU
+}
+
+/// Direction of the refraction calculation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RefractionDirection {
+    TrueToApparent = 0, // SE_TRUE_TO_APP
+    ApparentToTrue = 1, // SE_APP_TO_TRUE
+}
+
+/// Atmospheric conditions at the observer, used for refraction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Atmosphere {
+    pub pressure: f64, // mbar (hPa), 0 = estimate from observer altitude
+    pub temperature: f64, // degrees Celsius
+    pub lapse_rate: f64, // dT/dh in K/m
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Atmosphere {
+            pressure: 0.0,
+            temperature: 15.0,
+            lapse_rate: DEFAULT_LAPSE_RATE,
+        }
+    }
+}
+
+impl Atmosphere {
+    /// Pressure to use for an observer at `altitude` meters. When no
+    /// pressure is configured it is estimated the same way swe_azalt does.
+    fn pressure_at(&self, altitude: f64) -> f64 {
+        if self.pressure == 0.0 {
+            1013.25 * (1.0 - 0.0065 * altitude / 288.0).powf(5.255)
+        } else {
+            self.pressure
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HorizontalPosition {
+    pub azimuth: f64, // degrees from south, clockwise via west
+    pub true_altitude: f64,
+    pub apparent_altitude: f64, // true altitude corrected for refraction
+}
+
+impl HorizontalPosition {
+    /// Azimuth measured from north, clockwise via east
+    pub fn azimuth_from_north(&self) -> f64 {
+        (self.azimuth + 180.0) % 360.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Refraction {
+    pub true_altitude: f64,
+    pub apparent_altitude: f64,
+    pub refraction: f64,
+    pub horizon_dip: f64,
+}
+
+impl Refraction {
+    /// The library leaves both altitudes equal when the body is below the
+    /// horizon
+    pub fn is_above_horizon(&self) -> bool {
+        self.true_altitude != self.apparent_altitude
+    }
+}
+
+/// Wrapper for `swe_azalt`
+///
+/// `longitude` and `latitude` are ecliptic longitude and latitude, or right
+/// ascension and declination when `frame` is `HorizonFrame::Equatorial`.
+pub fn azalt(
+    tjd_ut: f64,
+    frame: HorizonFrame,
+    observer: &Observer,
+    atmosphere: &Atmosphere,
+    longitude: f64,
+    latitude: f64,
+) -> HorizontalPosition {
+    let geopos = observer.geopos();
+    let xin = [longitude, latitude, 1.0];
+    let mut xaz = [0.0; 3];
+    exclusive(|| unsafe {
+        raw::swe_set_lapse_rate(atmosphere.lapse_rate);
+        raw::swe_azalt(
+            tjd_ut,
+            frame as i32,
+            geopos.as_ptr(),
+            atmosphere.pressure,
+            atmosphere.temperature,
+            xin.as_ptr(),
+            xaz.as_mut_ptr(),
+        );
+        raw::swe_set_lapse_rate(DEFAULT_LAPSE_RATE);
+    });
+    HorizontalPosition {
+        azimuth: xaz[0],
+        true_altitude: xaz[1],
+        apparent_altitude: xaz[2],
+    }
+}
+
+/// Wrapper for `swe_azalt_rev`
+///
+/// Returns (longitude, latitude) in the requested frame for an azimuth
+/// (from south, clockwise via west) and a true altitude.
+pub fn azalt_rev(
+    tjd_ut: f64,
+    frame: HorizonFrame,
+    observer: &Observer,
+    azimuth: f64,
+    true_altitude: f64,
+) -> (f64, f64) {
+    let geopos = observer.geopos();
+    let xin = [azimuth, true_altitude];
+    let mut xout = [0.0; 2];
+    unsafe {
+        raw::swe_azalt_rev(
+            tjd_ut,
+            frame as i32,
+            geopos.as_ptr(),
+            xin.as_ptr(),
+            xout.as_mut_ptr(),
+        );
+    }
+    (xout[0], xout[1])
+}
+
+/// Wrapper for `swe_refrac_extended`
+pub fn refrac_extended(
+    altitude: f64,
+    observer_altitude: f64,
+    atmosphere: &Atmosphere,
+    direction: RefractionDirection,
+) -> Refraction {
+    let mut dret = [0.0; 20];
+    unsafe {
+        raw::swe_refrac_extended(
+            altitude,
+            observer_altitude,
+            atmosphere.pressure_at(observer_altitude),
+            atmosphere.temperature,
+            atmosphere.lapse_rate,
+            direction as i32,
+            dret.as_mut_ptr(),
+        );
+    }
+    Refraction {
+        true_altitude: dret[0],
+        apparent_altitude: dret[1],
+        refraction: dret[2],
+        horizon_dip: dret[3],
+    }
+}
+
+/// Azimuth and altitude of a planet or other body
+///
+/// The position is taken from `calc_ut_topo` in equatorial coordinates, so
+/// the parallax of the Moon is included and a sidereal flag in `iflag` has
+/// no effect on the result.
+pub fn body_azalt(
+    tjd_ut: f64,
+    body: Bodies,
+    iflag: CalculationFlags,
+    observer: &Observer,
+    atmosphere: &Atmosphere,
+) -> Result<HorizontalPosition, SweError> {
+    let iflag = iflag.difference(CalculationFlags::SIDEREAL_POSITIONS)
+        | CalculationFlags::EQUATORIAL_POSITIONS;
+    let position = calc_ut_topo(tjd_ut, body, iflag, observer)?;
+    Ok(azalt(
+        tjd_ut,
+        HorizonFrame::Equatorial,
+        observer,
+        atmosphere,
+        position.longitude,
+        position.latitude,
+    ))
+}
+
+/// Azimuth and altitude of a fixed star, from its topocentric position
+pub fn fixstar_azalt(
+    tjd_ut: f64,
+    star: &str,
+    iflag: CalculationFlags,
+    observer: &Observer,
+    atmosphere: &Atmosphere,
+) -> Result<HorizontalPosition, SweError> {
+    let iflag = iflag.difference(CalculationFlags::SIDEREAL_POSITIONS)
+        | CalculationFlags::EQUATORIAL_POSITIONS;
+    let position = fixstar_ut_topo(star, tjd_ut, iflag, observer)?;
+    Ok(azalt(
+        tjd_ut,
+        HorizonFrame::Equatorial,
+        observer,
+        atmosphere,
+        position.longitude,
+        position.latitude,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ensure_ephemeris_initialized;
+    use crate::sweconst::Calendar;
+    use crate::swerust::handler_swe03::calc_ut;
+    use crate::swerust::handler_swe08::{utc_time_zone, utc_to_jd};
+
+    fn test_jd() -> f64 {
+        let utc = utc_time_zone(2024, 11, 21, 16, 10, 0.0, 2.0);
+        utc_to_jd(
+            utc.year[0],
+            utc.month[0],
+            utc.day[0],
+            utc.hour[0],
+            utc.min[0],
+            utc.sec[0],
+            Calendar::Gregorian,
+        )
+        .unwrap()
+        .julian_day_ut
+    }
+
+    fn bulgaria() -> Observer {
+        Observer::new(43.084128, 25.5919228, 265.0)
+    }
+
+    #[test]
+    fn test_body_azalt_sun() {
+        let _ = ensure_ephemeris_initialized();
+        let position = body_azalt(
+            test_jd(),
+            Bodies::Sun,
+            CalculationFlags::SWISS_EPHEMERIS,
+            &bulgaria(),
+            &Atmosphere::default(),
+        )
+        .unwrap();
+
+        // the solar parallax lowers the Sun by about 9"
+        assert!((position.azimuth - 56.051024459).abs() < 1e-6);
+        assert!((position.true_altitude - 5.249897172).abs() < 1e-6);
+        assert!((position.apparent_altitude - 5.397014346).abs() < 1e-6);
+        assert!((position.azimuth_from_north() - 236.051024459).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_body_azalt_moon_parallax() {
+        let _ = ensure_ephemeris_initialized();
+        let tjd_ut = test_jd();
+        let observer = bulgaria();
+        let atmosphere = Atmosphere::default();
+        let flags = CalculationFlags::SWISS_EPHEMERIS;
+        let position =
+            body_azalt(tjd_ut, Bodies::Moon, flags, &observer, &atmosphere)
+                .unwrap();
+
+        let equatorial = flags | CalculationFlags::EQUATORIAL_POSITIONS;
+        let topocentric =
+            calc_ut_topo(tjd_ut, Bodies::Moon, equatorial, &observer).unwrap();
+        let expected = azalt(
+            tjd_ut,
+            HorizonFrame::Equatorial,
+            &observer,
+            &atmosphere,
+            topocentric.longitude,
+            topocentric.latitude,
+        );
+        assert!((position.azimuth - expected.azimuth).abs() < 1e-9);
+        assert!((position.true_altitude - expected.true_altitude).abs() < 1e-9);
+
+        // the geocentric Moon is higher by the parallax, up to about 1°
+        let geocentric = calc_ut(tjd_ut, Bodies::Moon, equatorial).unwrap();
+        let geocentric = azalt(
+            tjd_ut,
+            HorizonFrame::Equatorial,
+            &observer,
+            &atmosphere,
+            geocentric.longitude,
+            geocentric.latitude,
+        );
+        let parallax = geocentric.true_altitude - position.true_altitude;
+        assert!(parallax > 0.1 && parallax < 1.1, "{}", parallax);
+    }
+
+    #[test]
+    fn test_azalt_restores_lapse_rate() {
+        let _ = ensure_ephemeris_initialized();
+        // the lapse rate changes the refraction between the horizon and the
+        // dip of the horizon seen from a mountain
+        let observer = Observer::new(43.084128, 25.5919228, 2000.0);
+        let (longitude, latitude) =
+            azalt_rev(test_jd(), HorizonFrame::Ecliptic, &observer, 0.0, -2.05);
+        let position = |atmosphere: &Atmosphere| {
+            azalt(
+                test_jd(),
+                HorizonFrame::Ecliptic,
+                &observer,
+                atmosphere,
+                longitude,
+                latitude,
+            )
+        };
+        let default = position(&Atmosphere::default());
+        let inversion = position(&Atmosphere {
+            lapse_rate: -0.01,
+            ..Atmosphere::default()
+        });
+        assert_ne!(inversion.apparent_altitude, default.apparent_altitude);
+
+        // the library setting is the default again for other functions
+        let geopos = observer.geopos();
+        let xin = [longitude, latitude, 1.0];
+        let mut xaz = [0.0; 3];
+        unsafe {
+            raw::swe_azalt(
+                test_jd(),
+                HorizonFrame::Ecliptic as i32,
+                geopos.as_ptr(),
+                0.0,
+                15.0,
+                xin.as_ptr(),
+                xaz.as_mut_ptr(),
+            );
+        }
+        assert_eq!(xaz[2], default.apparent_altitude);
+    }
+
+    #[test]
+    fn test_azalt_rev_round_trip() {
+        let _ = ensure_ephemeris_initialized();
+        let tjd_ut = test_jd();
+        let observer = bulgaria();
+        let moon =
+            calc_ut(tjd_ut, Bodies::Moon, CalculationFlags::SWISS_EPHEMERIS)
+                .unwrap();
+        let position = azalt(
+            tjd_ut,
+            HorizonFrame::Ecliptic,
+            &observer,
+            &Atmosphere::default(),
+            moon.longitude,
+            moon.latitude,
+        );
+        let (longitude, latitude) = azalt_rev(
+            tjd_ut,
+            HorizonFrame::Ecliptic,
+            &observer,
+            position.azimuth,
+            position.true_altitude,
+        );
+
+        assert!((longitude - moon.longitude).abs() < 1e-8);
+        assert!((latitude - moon.latitude).abs() < 1e-8);
+    }
+
+    #[test]
+    fn test_fixstar_azalt() {
+        let _ = ensure_ephemeris_initialized();
+        let observer = bulgaria();
+        let atmosphere = Atmosphere::default();
+        let sirius = fixstar_azalt(
+            test_jd(),
+            "Sirius",
+            CalculationFlags::SWISS_EPHEMERIS,
+            &observer,
+            &atmosphere,
+        )
+        .unwrap();
+
+        assert!((sirius.azimuth - 215.026030352).abs() < 1e-6);
+        assert!((sirius.true_altitude + 59.709384381).abs() < 1e-6);
+        assert_eq!(sirius.apparent_altitude, sirius.true_altitude);
+
+        let unknown = fixstar_azalt(
+            test_jd(),
+            "NoSuchStar",
+            CalculationFlags::SWISS_EPHEMERIS,
+            &observer,
+            &atmosphere,
+        );
+        assert!(unknown.is_err());
+    }
+
+    #[test]
+    fn test_refrac_extended() {
+        let atmosphere = Atmosphere {
+            pressure: 1013.25,
+            temperature: 10.0,
+            lapse_rate: 0.0065,
+        };
+        let refraction = refrac_extended(
+            0.0,
+            0.0,
+            &atmosphere,
+            RefractionDirection::TrueToApparent,
+        );
+        assert!(refraction.is_above_horizon());
+        assert!((refraction.refraction - 0.48313157111686655).abs() < 1e-12);
+
+        let back = refrac_extended(
+            refraction.apparent_altitude,
+            0.0,
+            &atmosphere,
+            RefractionDirection::ApparentToTrue,
+        );
+        assert!(back.true_altitude.abs() < 1e-3);
+
+        let below = refrac_extended(
+            -20.0,
+            0.0,
+            &atmosphere,
+            RefractionDirection::TrueToApparent,
+        );
+        assert!(!below.is_above_horizon());
+        assert_eq!(below.refraction, 0.0);
+    }
+}
@@ -1,8 +1,13 @@
-use chrono::{ DateTime, FixedOffset, NaiveDate, TimeZone, Utc };
+use chrono::{ DateTime, Datelike, Duration, FixedOffset, NaiveDate, TimeZone, Timelike, Utc };
+use std::os::raw::c_char;
 
-use crate::raw::{ self, swe_revjul };
+use crate::constants::CalculationFlags;
+use crate::error::{ serr_message, warning, SweError, SweWarning };
+use crate::raw;
 use crate::sweconst::Calendar;
-use std::ffi::{ CStr, CString };
+
+#[cfg(feature = "timezone")]
+pub mod timezone;
 
 /*
  * 8. Date and time conversion functions
@@ -12,6 +17,157 @@ pub fn julday(year: i32, month: i32, day: i32, hour: f64, calendar: Calendar) ->
     result
 }
 
+/// Calendar date of a julian day, hour in decimal hours
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RevjulResult {
+    pub year: i32,
+    pub month: i32,
+    pub day: i32,
+    pub hour: f64,
+}
+
+/// Inverse of julday
+pub fn revjul(jd: f64, calendar: Calendar) -> RevjulResult {
+    let mut date = RevjulResult { year: 0, month: 0, day: 0, hour: 0.0 };
+    unsafe {
+        raw::swe_revjul(
+            jd,
+            calendar as i32,
+            &mut date.year,
+            &mut date.month,
+            &mut date.day,
+            &mut date.hour
+        );
+    }
+    date
+}
+
+/// Value of swe_set_delta_t_userdef() restoring the computed Delta T
+const DELTAT_AUTOMATIC: f64 = -1e-10;
+
+/// Delta T (ET - UT) in days, for the tidal acceleration of the ephemeris in
+/// use
+pub fn deltat(tjd_ut: f64) -> f64 {
+    unsafe { raw::swe_deltat(tjd_ut) }
+}
+
+/// Delta T (ET - UT) and the message of the C library when the ephemeris of
+/// iflag is not available
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeltaT {
+    pub days: f64,
+    pub warning: Option<SweWarning>,
+}
+
+impl DeltaT {
+    pub fn seconds(&self) -> f64 {
+        self.days * 86400.0
+    }
+}
+
+/// Delta T for the tidal acceleration of the ephemeris selected by iflag
+pub fn deltat_ex(tjd_ut: f64, iflag: CalculationFlags) -> Result<DeltaT, SweError> {
+    iflag.validate()?;
+    let mut serr = [0; 256];
+    let days = unsafe { raw::swe_deltat_ex(tjd_ut, iflag.bits(), serr.as_mut_ptr()) };
+    let message = serr_message(&serr)?;
+    Ok(DeltaT {
+        days,
+        warning: warning(iflag, iflag, &message),
+    })
+}
+
+/// Use a fixed Delta T in days instead of the computed one, or the computed
+/// one again with None. The setting is kept by the C library, per thread on
+/// Linux but for the whole process on macOS and Windows (sweodef.h), so it is
+/// only changed by crate::ephemeris under its lock, see Ephemeris::delta_t.
+pub(crate) fn set_delta_t_userdef(delta_t: Option<f64>) {
+    unsafe { raw::swe_set_delta_t_userdef(delta_t.unwrap_or(DELTAT_AUTOMATIC)) }
+}
+
+/// Sidereal time at Greenwich
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SiderealTime {
+    pub hours: f64,
+}
+
+impl SiderealTime {
+    pub fn degrees(&self) -> f64 {
+        self.hours * 15.0
+    }
+
+    /// Sidereal time at a geographic longitude (east positive) in degrees,
+    /// the ARMC of the house functions
+    pub fn armc(&self, geolon: f64) -> f64 {
+        (self.degrees() + geolon).rem_euclid(360.0)
+    }
+}
+
+/// Apparent sidereal time at Greenwich
+pub fn sidtime(tjd_ut: f64) -> SiderealTime {
+    SiderealTime {
+        hours: unsafe { raw::swe_sidtime(tjd_ut) },
+    }
+}
+
+/// Sidereal time at Greenwich with the obliquity of the ecliptic and the
+/// nutation in longitude given in degrees
+pub fn sidtime0(tjd_ut: f64, eps: f64, nut: f64) -> SiderealTime {
+    SiderealTime {
+        hours: unsafe { raw::swe_sidtime0(tjd_ut, eps, nut) },
+    }
+}
+
+/// Equation of time, local apparent time - local mean time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquationOfTime {
+    pub days: f64,
+}
+
+impl EquationOfTime {
+    pub fn minutes(&self) -> f64 {
+        self.days * 1440.0
+    }
+}
+
+pub fn time_equ(tjd_ut: f64) -> Result<EquationOfTime, SweError> {
+    let mut te = 0.0;
+    let mut serr = [0; 256];
+    let status = unsafe { raw::swe_time_equ(tjd_ut, &mut te, serr.as_mut_ptr()) };
+    if status < 0 {
+        return Err(SweError::from_status(status, &serr_message(&serr)?));
+    }
+    Ok(EquationOfTime { days: te })
+}
+
+/// Local mean time (julian day) to local apparent time at a geographic
+/// longitude (east positive)
+pub fn lmt_to_lat(tjd_lmt: f64, geolon: f64) -> Result<f64, SweError> {
+    let mut tjd_lat = 0.0;
+    let mut serr = [0; 256];
+    let status = unsafe {
+        raw::swe_lmt_to_lat(tjd_lmt, geolon, &mut tjd_lat, serr.as_mut_ptr())
+    };
+    if status < 0 {
+        return Err(SweError::from_status(status, &serr_message(&serr)?));
+    }
+    Ok(tjd_lat)
+}
+
+/// Local apparent time (julian day) to local mean time at a geographic
+/// longitude (east positive)
+pub fn lat_to_lmt(tjd_lat: f64, geolon: f64) -> Result<f64, SweError> {
+    let mut tjd_lmt = 0.0;
+    let mut serr = [0; 256];
+    let status = unsafe {
+        raw::swe_lat_to_lmt(tjd_lat, geolon, &mut tjd_lmt, serr.as_mut_ptr())
+    };
+    if status < 0 {
+        return Err(SweError::from_status(status, &serr_message(&serr)?));
+    }
+    Ok(tjd_lmt)
+}
+
 /// [0 -> jday / 1 -> utc]
 #[derive(Debug, Clone, PartialEq)]
 pub struct UtcTimeZoneResult {
@@ -75,9 +231,10 @@ pub fn utc_time_zone(
 pub struct UtcToJdResult {
     pub julian_day_et: f64,
     pub julian_day_ut: f64,
-    pub err: String, // To do in other file same struct
-    pub result: i32,
+    pub warning: Option<SweWarning>,
 }
+
+/// Returns SweError::InvalidArgument for an invalid date
 pub fn utc_to_jd(
     year: i32,
     month: i32,
@@ -86,13 +243,11 @@ pub fn utc_to_jd(
     min: i32,
     sec: f64,
     calendar: Calendar
-) -> UtcToJdResult {
+) -> Result<UtcToJdResult, SweError> {
     let mut dret = [0.0; 2];
-    let mut serr = [0; 255];
-    unsafe {
-        let p_dret = dret.as_mut_ptr();
-        let p_serr = serr.as_mut_ptr();
-        let result = raw::swe_utc_to_jd(
+    let mut serr = [0; 256];
+    let result = unsafe {
+        raw::swe_utc_to_jd(
             year,
             month,
             day,
@@ -100,44 +255,133 @@ pub fn utc_to_jd(
             min,
             sec,
             calendar as i32,
-            p_dret,
-            p_serr
-        );
-        let s_serr = CString::from(CStr::from_ptr(p_serr)).to_str().unwrap().to_string();
-        UtcToJdResult {
-            julian_day_et: dret[0],
-            julian_day_ut: dret[1],
-            err: s_serr,
-            result,
-        }
+            dret.as_mut_ptr(),
+            serr.as_mut_ptr()
+        )
+    };
+    let message = serr_message(&serr)?;
+    if result < 0 {
+        return Err(SweError::InvalidArgument(message));
     }
+    Ok(UtcToJdResult {
+        julian_day_et: dret[0],
+        julian_day_ut: dret[1],
+        warning: warning(CalculationFlags::empty(), CalculationFlags::empty(), &message),
+    })
 }
 
-pub fn julian_to_utc(jd: f64) -> DateTime<Utc> {
-    let mut year = 0;
-    let mut month = 0;
-    let mut day = 0;
-    let mut hour = 0.0;
+/// Calendar date and time of day in UTC, sec is 60.x during a leap second
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UtcDate {
+    pub year: i32,
+    pub month: i32,
+    pub day: i32,
+    pub hour: i32,
+    pub min: i32,
+    pub sec: f64,
+}
+
+/// UTC date of a julian day in ET (TT), inverse of utc_to_jd
+pub fn jdet_to_utc(tjd_et: f64, calendar: Calendar) -> UtcDate {
+    let mut date = UtcDate { year: 0, month: 0, day: 0, hour: 0, min: 0, sec: 0.0 };
+    unsafe {
+        raw::swe_jdet_to_utc(
+            tjd_et,
+            calendar as i32,
+            &mut date.year,
+            &mut date.month,
+            &mut date.day,
+            &mut date.hour,
+            &mut date.min,
+            &mut date.sec
+        );
+    }
+    date
+}
 
+/// UTC date of a julian day in UT (UT1), inverse of utc_to_jd
+pub fn jdut1_to_utc(tjd_ut: f64, calendar: Calendar) -> UtcDate {
+    let mut date = UtcDate { year: 0, month: 0, day: 0, hour: 0, min: 0, sec: 0.0 };
     unsafe {
-        swe_revjul(jd, 1, &mut year, &mut month, &mut day, &mut hour);
+        raw::swe_jdut1_to_utc(
+            tjd_ut,
+            calendar as i32,
+            &mut date.year,
+            &mut date.month,
+            &mut date.day,
+            &mut date.hour,
+            &mut date.min,
+            &mut date.sec
+        );
+    }
+    date
+}
+
+/// Julian day of a date and a time in decimal hours, like julday, but
+/// returns SweError::InvalidArgument for an invalid date
+pub fn date_conversion(
+    year: i32,
+    month: i32,
+    day: i32,
+    hour: f64,
+    calendar: Calendar
+) -> Result<f64, SweError> {
+    let c = match calendar {
+        Calendar::Julian => b'j',
+        Calendar::Gregorian => b'g',
+    };
+    let mut tjd = 0.0;
+    let status = unsafe {
+        raw::swe_date_conversion(year, month, day, hour, c as c_char, &mut tjd)
+    };
+    if status < 0 {
+        return Err(
+            SweError::InvalidArgument(format!("invalid date {}-{}-{} {}h", year, month, day, hour))
+        );
     }
-    convert_to_utc_time(year, month, day, hour)
+    Ok(tjd)
+}
+
+/*
+ * Conversions between chrono and julian days
+ *
+ * chrono keeps a leap second as the second 59 with more than 10^9
+ * nanoseconds, the C library as the second 60. A julian day has a precision
+ * of about 40 microseconds, the dates are rounded to the microsecond.
+ */
+
+/// Julian days in ET and UT of a date, leap seconds included
+pub fn datetime_to_jd<Tz: TimeZone>(datetime: &DateTime<Tz>) -> Result<UtcToJdResult, SweError> {
+    let utc = datetime.naive_utc();
+    let sec = (utc.second() as f64) + (utc.nanosecond() as f64) / 1e9;
+    utc_to_jd(
+        utc.year(),
+        utc.month() as i32,
+        utc.day() as i32,
+        utc.hour() as i32,
+        utc.minute() as i32,
+        sec,
+        Calendar::Gregorian
+    )
+}
+
+/// UTC date of a julian day in UT (UT1)
+pub fn julian_to_utc(jd: f64) -> Result<DateTime<Utc>, SweError> {
+    check_julian_day(jd)?;
+    utc_date_to_datetime(&jdut1_to_utc(jd, Calendar::Gregorian))
+}
+
+/// UTC date of a julian day in ET (TT)
+pub fn julian_et_to_utc(tjd_et: f64) -> Result<DateTime<Utc>, SweError> {
+    check_julian_day(tjd_et)?;
+    utc_date_to_datetime(&jdet_to_utc(tjd_et, Calendar::Gregorian))
 }
 
 pub fn julian_to_dt_with_offset(
     jd: f64,
     timezone_offset: f64
-) -> Result<DateTime<FixedOffset>, String> {
-    let mut year = 0;
-    let mut month = 0;
-    let mut day = 0;
-    let mut hour = 0.0;
-
-    unsafe {
-        swe_revjul(jd, 1, &mut year, &mut month, &mut day, &mut hour);
-    }
-    convert_to_dt_with_offset(year, month as u32, day as u32, hour, timezone_offset)
+) -> Result<DateTime<FixedOffset>, SweError> {
+    Ok(julian_to_utc(jd)?.with_timezone(&fixed_offset(timezone_offset)?))
 }
 
 pub fn convert_to_dt_with_offset(
@@ -146,52 +390,83 @@ pub fn convert_to_dt_with_offset(
     day: u32,
     hour: f64,
     timezone_offset: f64
-) -> Result<DateTime<FixedOffset>, String> {
-    // Extract integer and fractional part of the hour
-    let hour_int = hour as u32;
-    let minute = ((hour - (hour_int as f64)) * 60.0) as u32;
-    let second = (((hour - (hour_int as f64)) * 60.0 - (minute as f64)) * 60.0) as u32;
-
-    // Create a NaiveDateTime (which is timezone-independent)
-    let naive_date = NaiveDate::from_ymd_opt(year, month, day).ok_or_else(||
-        "Invalid date".to_string()
-    )?;
-    let naive_datetime = naive_date
-        .and_hms_opt(hour_int, minute, second)
-        .ok_or_else(|| "Invalid time".to_string())?;
-
-    // Convert to UTC DateTime
-    let utc_datetime = Utc.from_utc_datetime(&naive_datetime);
+) -> Result<DateTime<FixedOffset>, SweError> {
+    let utc_datetime = convert_to_utc_time(year, month as i32, day as i32, hour)?;
+    Ok(utc_datetime.with_timezone(&fixed_offset(timezone_offset)?))
+}
 
-    // Convert to local time using the provided offset (timezone_offset in hours)
-    let timezone = FixedOffset::east_opt((timezone_offset * 3600.0) as i32).ok_or_else(||
-        "Invalid timezone offset".to_string()
-    )?;
+/// Date and time in decimal hours (0 .. 24) as chrono, to the microsecond
+pub fn convert_to_utc_time(
+    year: i32,
+    month: i32,
+    day: i32,
+    hour: f64
+) -> Result<DateTime<Utc>, SweError> {
+    if !(0.0..24.0).contains(&hour) {
+        return Err(SweError::InvalidArgument(format!("invalid hour {}", hour)));
+    }
+    let microseconds = (hour * 3600.0 * 1e6).round() as i64;
+    let naive_datetime = naive_date(year, month, day)?
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.checked_add_signed(Duration::microseconds(microseconds)))
+        .ok_or_else(|| SweError::InvalidArgument(format!("invalid hour {}", hour)))?;
+    Ok(Utc.from_utc_datetime(&naive_datetime))
+}
 
-    Ok(utc_datetime.with_timezone(&timezone))
+fn check_julian_day(jd: f64) -> Result<(), SweError> {
+    if !jd.is_finite() {
+        return Err(SweError::InvalidArgument(format!("invalid julian day {}", jd)));
+    }
+    Ok(())
 }
 
-pub fn convert_to_utc_time(year: i32, month: i32, day: i32, hour: f64) -> DateTime<Utc> {
-    // Extract integer and fractional part of the hour
-    let hour_int = hour as u32;
-    let minute = ((hour - (hour_int as f64)) * 60.0) as u32;
-    let second = (((hour - (hour_int as f64)) * 60.0 - (minute as f64)) * 60.0) as u32;
+fn naive_date(year: i32, month: i32, day: i32) -> Result<NaiveDate, SweError> {
+    if month < 1 || day < 1 {
+        return Err(SweError::InvalidArgument(format!("invalid date {}-{}-{}", year, month, day)));
+    }
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).ok_or_else(||
+        SweError::InvalidArgument(format!("invalid date {}-{}-{}", year, month, day))
+    )
+}
 
-    // Create a NaiveDateTime (which is timezone-independent)
-    let naive_date = NaiveDate::from_ymd_opt(year, month as u32, day as u32)
-        .ok_or_else(|| "Invalid date".to_string())
-        .unwrap();
-    let naive_datetime = naive_date
-        .and_hms_opt(hour_int, minute, second)
-        .ok_or_else(|| "Invalid time".to_string())
-        .unwrap();
+fn fixed_offset(timezone_offset: f64) -> Result<FixedOffset, SweError> {
+    if !timezone_offset.is_finite() {
+        return Err(
+            SweError::InvalidArgument(format!("invalid timezone offset {}", timezone_offset))
+        );
+    }
+    FixedOffset::east_opt((timezone_offset * 3600.0).round() as i32).ok_or_else(||
+        SweError::InvalidArgument(format!("invalid timezone offset {}", timezone_offset))
+    )
+}
 
-    Utc.from_utc_datetime(&naive_datetime)
+fn utc_date_to_datetime(date: &UtcDate) -> Result<DateTime<Utc>, SweError> {
+    let invalid = || SweError::InvalidArgument(format!("invalid UTC date {:?}", date));
+    let microseconds = (date.sec * 1e6).round() as i64;
+    let start_of_minute = naive_date(date.year, date.month, date.day)?
+        .and_hms_opt(date.hour as u32, date.min as u32, 0)
+        .ok_or_else(invalid)?;
+    let naive_datetime = if date.sec >= 60.0 && microseconds < 61_000_000 {
+        // leap second
+        let nanoseconds = ((microseconds - 59_000_000) * 1000) as u32;
+        start_of_minute
+            .with_second(59)
+            .and_then(|datetime| datetime.with_nanosecond(nanoseconds))
+            .ok_or_else(invalid)?
+    } else {
+        // the minute after a leap second starts 61 seconds later
+        let leap_second = if date.sec >= 60.0 { 1_000_000 } else { 0 };
+        start_of_minute
+            .checked_add_signed(Duration::microseconds(microseconds - leap_second))
+            .ok_or_else(invalid)?
+    };
+    Ok(Utc.from_utc_datetime(&naive_datetime))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{ensure_ephemeris_initialized, Ephemeris};
 
     #[test]
     pub fn test_utc_time() {
@@ -228,32 +503,156 @@ mod tests {
         let expected_result = UtcToJdResult {
             julian_day_et: 2460748.9216340743,
             julian_day_ut: 2460748.9208356882,
-            err: "".to_owned(),
-            result: 0,
+            warning: None,
         };
 
-        let actual_result = utc_to_jd(year, month, day, hour, min, sec, Calendar::Gregorian);
+        let actual_result = utc_to_jd(year, month, day, hour, min, sec, Calendar::Gregorian).unwrap();
 
         assert_eq!(actual_result, expected_result);
     }
 
+    #[test]
+    pub fn test_utc_to_jd_invalid_date() {
+        let result = utc_to_jd(2025, 2, 30, 10, 6, 0.0, Calendar::Gregorian);
+
+        assert!(matches!(result, Err(SweError::InvalidArgument(_))));
+    }
+
     #[test]
     pub fn test_convert_to_local_time() {
         let year = 2025;
         let month = 3;
         let day = 14;
         let hour = 10.1001;
-        let expected_result = Utc.with_ymd_and_hms(
-            year,
-            month as u32,
-            day as u32,
-            10,
-            6,
-            0
-        ).unwrap();
+        let expected_result = Utc.with_ymd_and_hms(year, month as u32, day as u32, 10, 6, 0)
+            .unwrap()
+            .with_nanosecond(360_000_000)
+            .unwrap();
 
-        let actual_result = convert_to_utc_time(year, month, day, hour);
+        let actual_result = convert_to_utc_time(year, month, day, hour).unwrap();
 
         assert_eq!(actual_result, expected_result);
     }
+
+    #[test]
+    pub fn test_deltat_ex() {
+        let _ = ensure_ephemeris_initialized();
+        let j2000 = 2451545.0;
+        let delta_t = deltat_ex(j2000, CalculationFlags::SWISS_EPHEMERIS).unwrap();
+
+        // Delta T was 63.8 s in 2000
+        assert!((delta_t.seconds() - 63.8).abs() < 0.5);
+        assert!((delta_t.days - deltat(j2000)).abs() < 1e-9);
+        assert!(
+            deltat_ex(
+                j2000,
+                CalculationFlags::JPL_EPHEMERIS | CalculationFlags::SWISS_EPHEMERIS
+            ).is_err()
+        );
+
+        let fixed = Ephemeris::new().unwrap().delta_t(100.0 / 86400.0);
+        let delta_t = fixed.deltat_ex(j2000, CalculationFlags::SWISS_EPHEMERIS).unwrap();
+        assert!((delta_t.seconds() - 100.0).abs() < 1e-6);
+        assert!((fixed.with(|| Ok(deltat(j2000))).unwrap() * 86400.0 - 100.0).abs() < 1e-6);
+        // the computed Delta T is used again once the lock is released
+        assert!((deltat(j2000) * 86400.0 - 63.8).abs() < 0.5);
+    }
+
+    #[test]
+    pub fn test_sidereal_time() {
+        // mean sidereal time 18h41m50.5s at J2000 (noon UT), the equation of
+        // the equinoxes was -0.9s
+        let sidereal_time = sidtime(2451545.0);
+        assert!((sidereal_time.hours - 18.697374558 + 0.9 / 3600.0).abs() < 1e-4);
+        let armc = sidereal_time.degrees() + 100.0 - 360.0;
+        assert!((sidereal_time.armc(100.0) - armc).abs() < 1e-9);
+
+        // mean sidereal time without nutation
+        let mean = sidtime0(2451545.0, 23.4392911, 0.0);
+        assert!((mean.hours - 18.697374558).abs() < 1e-4);
+    }
+
+    #[test]
+    pub fn test_equation_of_time() {
+        // about +16.4 minutes at the beginning of November
+        let november_3 = julday(2024, 11, 3, 12.0, Calendar::Gregorian);
+        let te = time_equ(november_3).unwrap();
+        assert!((te.minutes() - 16.4).abs() < 0.2);
+
+        let lat = lmt_to_lat(november_3, 25.5919228).unwrap();
+        assert!(((lat - november_3) * 1440.0 - te.minutes()).abs() < 0.1);
+        let lmt = lat_to_lmt(lat, 25.5919228).unwrap();
+        assert!((lmt - november_3).abs() < 1e-6);
+    }
+
+    #[test]
+    pub fn test_convert_to_utc_time_invalid() {
+        assert!(convert_to_utc_time(2025, 2, 30, 10.0).is_err());
+        assert!(convert_to_utc_time(2025, -1, 1, 10.0).is_err());
+        assert!(convert_to_utc_time(2025, 3, 14, 24.0).is_err());
+        assert!(convert_to_utc_time(2025, 3, 14, f64::NAN).is_err());
+        assert!(julian_to_utc(f64::INFINITY).is_err());
+        assert!(julian_to_dt_with_offset(2460748.5, f64::NAN).is_err());
+    }
+
+    #[test]
+    pub fn test_date_conversion() {
+        let jd = date_conversion(2025, 3, 14, 10.1, Calendar::Gregorian).unwrap();
+        assert_eq!(jd, julday(2025, 3, 14, 10.1, Calendar::Gregorian));
+        assert!(matches!(
+            date_conversion(2025, 2, 30, 0.0, Calendar::Gregorian),
+            Err(SweError::InvalidArgument(_))
+        ));
+        // February 29 exists in 1900 only in the julian calendar
+        assert!(date_conversion(1900, 2, 29, 0.0, Calendar::Julian).is_ok());
+        assert!(date_conversion(1900, 2, 29, 0.0, Calendar::Gregorian).is_err());
+
+        let date = revjul(jd, Calendar::Gregorian);
+        assert_eq!((date.year, date.month, date.day), (2025, 3, 14));
+        assert!((date.hour - 10.1).abs() < 1e-8);
+    }
+
+    #[test]
+    pub fn test_round_trip_across_1972_leap_seconds() {
+        let _ = ensure_ephemeris_initialized();
+        let utc = |y, m, d, h, min, s, micro: u32| {
+            Utc.with_ymd_and_hms(y, m, d, h, min, s)
+                .unwrap()
+                .with_nanosecond(micro * 1000)
+                .unwrap()
+        };
+        // the first leap seconds were inserted at the end of June and December 1972
+        let dates = [
+            utc(1971, 12, 31, 23, 59, 59, 500_000),
+            utc(1972, 6, 30, 23, 59, 59, 250_000),
+            utc(1972, 6, 30, 23, 59, 59, 1_500_000),
+            utc(1972, 7, 1, 0, 0, 0, 750_000),
+            utc(1972, 12, 31, 23, 59, 59, 1_000_000),
+            utc(1972, 12, 31, 23, 59, 59, 1_999_000),
+            utc(1973, 1, 1, 0, 0, 0, 0),
+            utc(2024, 3, 14, 10, 6, 0, 123_456),
+        ];
+        for date in dates.iter() {
+            let jd = datetime_to_jd(date).unwrap();
+            for round_trip in [
+                julian_to_utc(jd.julian_day_ut).unwrap(),
+                julian_et_to_utc(jd.julian_day_et).unwrap(),
+            ] {
+                let error = round_trip.signed_duration_since(*date).num_microseconds().unwrap();
+                assert!(error.abs() <= 50, "{} -> {}", date, round_trip);
+                assert_eq!(round_trip.second(), date.second());
+            }
+        }
+
+        // the leap second is counted in ET
+        let before = datetime_to_jd(&utc(1972, 6, 30, 23, 59, 59, 0)).unwrap();
+        let after = datetime_to_jd(&utc(1972, 7, 1, 0, 0, 0, 0)).unwrap();
+        assert!(((after.julian_day_et - before.julian_day_et) * 86400.0 - 2.0).abs() < 1e-4);
+        let leap = jdet_to_utc(before.julian_day_et + 1.5 / 86400.0, Calendar::Gregorian);
+        assert_eq!((leap.year, leap.month, leap.day, leap.hour, leap.min), (1972, 6, 30, 23, 59));
+        assert!((leap.sec - 60.5).abs() < 1e-4);
+
+        // no leap second at the end of June 1973
+        assert!(datetime_to_jd(&utc(1973, 6, 30, 23, 59, 59, 1_500_000)).is_err());
+    }
 }
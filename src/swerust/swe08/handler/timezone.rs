@@ -0,0 +1,297 @@
+use chrono::{
+    DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, Offset,
+    TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::error::SweError;
+use crate::swerust::handler_swe08::{
+    datetime_to_jd, julian_to_utc, UtcToJdResult,
+};
+
+/*
+ * IANA time zones
+ *
+ * Local civil times are converted with the rules of a time zone of the tz
+ * database (e.g. "Europe/Sofia"), embedded in the crate by chrono-tz, so
+ * historical daylight saving time is taken into account without network
+ * access. Enabled by the cargo feature "timezone".
+ *
+ * When the clocks are put forward, the local times of the gap do not exist;
+ * when they are put back, the local times of the overlap happen twice. A
+ * DstPolicy tells how to resolve them.
+ */
+
+/// Resolution of local times in a daylight saving time gap or overlap
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum DstPolicy {
+    /// Reject non-existent and ambiguous local times
+    #[default]
+    Strict,
+    /// Earlier of the two possible instants: the first occurrence in an
+    /// overlap, the offset after the transition in a gap
+    Earlier,
+    /// Later of the two possible instants: the second occurrence in an
+    /// overlap, the offset before the transition in a gap (the local time
+    /// is moved forward by the length of the gap)
+    Later,
+}
+
+/// Time zone of the tz database
+pub fn parse_time_zone(zone: &str) -> Result<Tz, SweError> {
+    zone.parse::<Tz>().map_err(|_| {
+        SweError::InvalidArgument(format!("unknown time zone {}", zone))
+    })
+}
+
+/// Instant of a local civil time in a time zone, sec is 60.x during a leap
+/// second
+#[allow(clippy::too_many_arguments)]
+pub fn local_to_datetime(
+    year: i32,
+    month: i32,
+    day: i32,
+    hour: i32,
+    min: i32,
+    sec: f64,
+    zone: &str,
+    policy: DstPolicy,
+) -> Result<DateTime<Tz>, SweError> {
+    let tz = parse_time_zone(zone)?;
+    let local = naive_local(year, month, day, hour, min, sec)?;
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(datetime) => Ok(datetime),
+        LocalResult::Ambiguous(earlier, later) => match policy {
+            DstPolicy::Strict => Err(SweError::InvalidArgument(format!(
+                "{} is ambiguous in {}",
+                local, zone
+            ))),
+            DstPolicy::Earlier => Ok(earlier),
+            DstPolicy::Later => Ok(later),
+        },
+        LocalResult::None => {
+            // offsets on both sides of the transition
+            let before = tz
+                .offset_from_utc_datetime(&(local - Duration::days(1)))
+                .fix();
+            let after = tz
+                .offset_from_utc_datetime(&(local + Duration::days(1)))
+                .fix();
+            let offset = match policy {
+                DstPolicy::Strict => {
+                    return Err(SweError::InvalidArgument(format!(
+                        "{} does not exist in {}",
+                        local, zone
+                    )))
+                },
+                DstPolicy::Earlier => after,
+                DstPolicy::Later => before,
+            };
+            let utc =
+                local - Duration::seconds(offset.local_minus_utc() as i64);
+            Ok(tz.from_utc_datetime(&utc))
+        },
+    }
+}
+
+/// Julian days in ET and UT of a local civil time in a time zone
+#[allow(clippy::too_many_arguments)]
+pub fn local_to_jd(
+    year: i32,
+    month: i32,
+    day: i32,
+    hour: i32,
+    min: i32,
+    sec: f64,
+    zone: &str,
+    policy: DstPolicy,
+) -> Result<UtcToJdResult, SweError> {
+    let datetime =
+        local_to_datetime(year, month, day, hour, min, sec, zone, policy)?;
+    datetime_to_jd(&datetime)
+}
+
+/// Local civil time of a julian day in UT (UT1) in a time zone
+pub fn julian_to_local(jd: f64, zone: &str) -> Result<DateTime<Tz>, SweError> {
+    let tz = parse_time_zone(zone)?;
+    Ok(julian_to_utc(jd)?.with_timezone(&tz))
+}
+
+/// Instant of a UTC date in a time zone
+pub fn utc_to_local(
+    datetime: &DateTime<Utc>,
+    zone: &str,
+) -> Result<DateTime<Tz>, SweError> {
+    Ok(datetime.with_timezone(&parse_time_zone(zone)?))
+}
+
+fn naive_local(
+    year: i32,
+    month: i32,
+    day: i32,
+    hour: i32,
+    min: i32,
+    sec: f64,
+) -> Result<NaiveDateTime, SweError> {
+    let invalid = || {
+        SweError::InvalidArgument(format!(
+            "invalid local time {}-{}-{} {}:{}:{}",
+            year, month, day, hour, min, sec
+        ))
+    };
+    if month < 1
+        || day < 1
+        || hour < 0
+        || min < 0
+        || !(0.0..61.0).contains(&sec)
+    {
+        return Err(invalid());
+    }
+    // chrono keeps a leap second as the second 59 with more than 10^9 ns
+    let second = sec.min(59.0).floor();
+    let nanoseconds = ((sec - second) * 1e9).round().min(1_999_999_999.0);
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+        .and_then(|date| {
+            date.and_hms_nano_opt(
+                hour as u32,
+                min as u32,
+                second as u32,
+                nanoseconds as u32,
+            )
+        })
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sweconst::Calendar;
+    use crate::swerust::handler_swe08::utc_to_jd;
+    use chrono::Timelike;
+
+    fn assert_same_jd(actual: &UtcToJdResult, expected: &UtcToJdResult) {
+        assert!((actual.julian_day_ut - expected.julian_day_ut).abs() < 1e-9);
+        assert!((actual.julian_day_et - expected.julian_day_et).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_local_to_jd_with_historical_offsets() {
+        // Bulgaria was on summer time (UTC+3) in May 1990
+        let birth = local_to_jd(
+            1990,
+            5,
+            15,
+            8,
+            30,
+            0.0,
+            "Europe/Sofia",
+            DstPolicy::Strict,
+        )
+        .unwrap();
+        let expected =
+            utc_to_jd(1990, 5, 15, 5, 30, 0.0, Calendar::Gregorian).unwrap();
+        assert_same_jd(&birth, &expected);
+
+        // British Standard Time, UTC+1 all year from 1968 to 1971
+        let winter = local_to_jd(
+            1970,
+            1,
+            15,
+            12,
+            0,
+            0.0,
+            "Europe/London",
+            DstPolicy::Strict,
+        )
+        .unwrap();
+        let expected =
+            utc_to_jd(1970, 1, 15, 11, 0, 0.0, Calendar::Gregorian).unwrap();
+        assert_same_jd(&winter, &expected);
+
+        assert!(matches!(
+            local_to_jd(
+                1990,
+                5,
+                15,
+                8,
+                30,
+                0.0,
+                "Europe/Nowhere",
+                DstPolicy::Strict
+            ),
+            Err(SweError::InvalidArgument(_))
+        ));
+        assert!(local_to_jd(
+            1990,
+            2,
+            30,
+            8,
+            30,
+            0.0,
+            "Europe/Sofia",
+            DstPolicy::Strict
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_dst_gap_and_overlap() {
+        let sofia = |day, policy| {
+            local_to_datetime(2024, 3, day, 3, 30, 0.0, "Europe/Sofia", policy)
+        };
+        let utc_hour =
+            |datetime: DateTime<Tz>| datetime.with_timezone(&Utc).hour();
+
+        // the clocks went from 03:00 to 04:00 on March 31, 2024
+        assert!(sofia(31, DstPolicy::Strict).is_err());
+        let earlier = sofia(31, DstPolicy::Earlier).unwrap();
+        let later = sofia(31, DstPolicy::Later).unwrap();
+        assert_eq!(utc_hour(earlier), 0);
+        assert_eq!(utc_hour(later), 1);
+        assert_eq!(later.hour(), 4);
+        assert_eq!(later.minute(), 30);
+
+        // and back from 04:00 to 03:00 on October 27, 2024
+        let overlap = |policy| {
+            local_to_datetime(2024, 10, 27, 3, 30, 0.0, "Europe/Sofia", policy)
+        };
+        assert!(overlap(DstPolicy::Strict).is_err());
+        assert_eq!(utc_hour(overlap(DstPolicy::Earlier).unwrap()), 0);
+        assert_eq!(utc_hour(overlap(DstPolicy::Later).unwrap()), 1);
+        assert_eq!(overlap(DstPolicy::Later).unwrap().hour(), 3);
+    }
+
+    #[test]
+    fn test_julian_to_local() {
+        let jd = local_to_jd(
+            2024,
+            7,
+            1,
+            12,
+            15,
+            30.25,
+            "America/New_York",
+            DstPolicy::Strict,
+        )
+        .unwrap();
+        let local =
+            julian_to_local(jd.julian_day_ut, "America/New_York").unwrap();
+        let expected = local_to_datetime(
+            2024,
+            7,
+            1,
+            12,
+            15,
+            30.25,
+            "America/New_York",
+            DstPolicy::Strict,
+        )
+        .unwrap();
+        let error = local.signed_duration_since(expected).num_microseconds();
+        assert!(error.unwrap().abs() <= 50);
+        assert_eq!(local.hour(), 12);
+    }
+}
@@ -1,20 +1,26 @@
+use crate::constants::CalculationFlags;
+use crate::error::{c_string, serr_message, SweError, SweWarning};
 use crate::raw::{self, swe_houses_ex2};
+use crate::sweconst::{Bodies, HouseSystem, Object, Observer};
+use crate::swerust::handler_swe03::get_ayanamsa_ex_ut;
+use crate::swerust::handler_swe07::horizon::Atmosphere;
+use crate::swerust::handler_swe08::sidtime;
+use crate::swerust::handler_swe17::coordinates::true_obliquity;
+use crate::swerust::handler_swe17::degnorm;
+use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
-// use crate::sweconst::HouseSystem;
 use std::ffi::{c_char, CStr, CString};
 use std::os::raw::c_int;
+use std::ptr;
 
 /*
  * 14. House cusp calculation
  */
 
-pub fn house_name(hsys: char) -> String {
-    unsafe {
-        CString::from(CStr::from_ptr(raw::swe_house_name(hsys as c_int)))
-            .to_str()
-            .unwrap()
-            .to_string()
-    }
+pub fn house_name(hsys: HouseSystem) -> Result<String, SweError> {
+    c_string(unsafe {
+        CStr::from_ptr(raw::swe_house_name(hsys.to_char() as c_int))
+    })
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -23,7 +29,7 @@ pub struct HousesResult {
     // /* array for 13 (or 37 for system G) doubles */
     pub cusps: Vec<f64>,
     pub ascmc: [f64; 10],
-    pub result: i32,
+    pub warning: Option<SweWarning>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -32,14 +38,33 @@ pub struct HouseCalculationResult {
     pub ascmc: [f64; 8],        // Asc, MC, etc.
     pub cusp_speeds: [f64; 12], // House cusp motion
     pub ascmc_speeds: [f64; 8], // Angular point motion
+    pub warning: Option<SweWarning>,
 }
 
+/// The C library switches to Porphyry houses, and returns an error status,
+/// when the house system cannot be computed at the latitude
+fn porphyry_fallback(hsys: HouseSystem, status: i32) -> Option<SweWarning> {
+    if status < 0 {
+        Some(SweWarning::HouseSystemFallback {
+            requested: hsys,
+            used: HouseSystem::Porphyrius,
+        })
+    } else {
+        None
+    }
+}
+
+/// Houses at a date and a geographic position. Only an invalid latitude is an
+/// error: where the house system cannot be computed, within the polar circle
+/// or without the declination of the Sun for Sunshine houses, the C library
+/// returns Porphyry houses, reported by SweWarning::HouseSystemFallback.
 pub fn houses(
     tjd_ut: f64,
     geolat: f64,
     geolong: f64,
-    hsys: char,
-) -> HousesResult {
+    hsys: HouseSystem,
+) -> Result<HousesResult, SweError> {
+    check_latitude(geolat)?;
     let mut cusps = [0.0; 37];
     let mut ascmc = [0.0; 10];
     let result: i32 = unsafe {
@@ -50,25 +75,30 @@ pub fn houses(
             0, // 64 | (64 * 1024),
             geolat,
             geolong,
-            hsys as c_int,
+            hsys.to_char() as c_int,
             p_cuspsw,
             p_ascmc,
         )
     };
-    HousesResult {
+    Ok(HousesResult {
         cusps: cusps.to_vec(),
         ascmc,
-        result,
-    }
+        warning: porphyry_fallback(hsys, result),
+    })
 }
 
+/// Houses with calculation flags (sidereal houses with
+/// CalculationFlags::SIDEREAL_POSITIONS), failing as houses or for invalid
+/// flags
 pub fn houses_with_flag(
     tjd_ut: f64,
-    flag: i32,
+    flag: CalculationFlags,
     geolat: f64,
     geolong: f64,
-    hsys: char,
-) -> HousesResult {
+    hsys: HouseSystem,
+) -> Result<HousesResult, SweError> {
+    flag.validate()?;
+    check_latitude(geolat)?;
     let mut cusps = [0.0; 37];
     let mut ascmc = [0.0; 10];
     let result: i32 = unsafe {
@@ -76,73 +106,166 @@ pub fn houses_with_flag(
         let p_ascmc = ascmc.as_mut_ptr();
         raw::swe_houses_ex(
             tjd_ut,
-            flag,
+            flag.bits(),
             geolat,
             geolong,
-            hsys as c_int,
+            hsys.to_char() as c_int,
             p_cuspsw,
             p_ascmc,
         )
     };
-    HousesResult {
+    Ok(HousesResult {
         cusps: cusps.to_vec(),
         ascmc,
-        result,
-    }
+        warning: porphyry_fallback(hsys, result),
+    })
+}
+
+/// Houses from the sidereal time (ARMC, in degrees) and the obliquity of the
+/// ecliptic instead of a date, always tropical, failing as houses
+pub fn houses_armc(
+    armc: f64,
+    geolat: f64,
+    eps: f64,
+    hsys: HouseSystem,
+) -> Result<HousesResult, SweError> {
+    check_latitude(geolat)?;
+    let mut cusps = [0.0; 37];
+    let mut ascmc = [0.0; 10];
+    let result: i32 = unsafe {
+        raw::swe_houses_armc(
+            armc,
+            geolat,
+            eps,
+            hsys.to_char() as c_int,
+            cusps.as_mut_ptr(),
+            ascmc.as_mut_ptr(),
+        )
+    };
+    Ok(HousesResult {
+        cusps: cusps.to_vec(),
+        ascmc,
+        warning: porphyry_fallback(hsys, result),
+    })
 }
 
 pub fn calculate_houses_extended_with_speeds(
     jd_ut: f64,
-    iflag: i32,
+    iflag: CalculationFlags,
     lat: f64,
     lon: f64,
-    hsys: char,
-) -> Result<HouseCalculationResult, String> {
-    if hsys == 'G' {
-        return Err("GAUQUELIN_SECTORS is not implemented.".to_string());
-    }
+    hsys: HouseSystem,
+) -> Result<HouseCalculationResult, SweError> {
+    iflag.validate()?;
+    reject_gauquelin(hsys)?;
+    check_latitude(lat)?;
     let mut cusps = [0.0; 13]; // House cusps
     let mut ascmc = [0.0; 8]; // Angular points (Asc, MC, etc.)
     let mut cusp_speeds = [0.0; 13]; // Motion of house cusps
     let mut ascmc_speeds = [0.0; 8]; // Motion of angular points
-    let hsys_int = hsys as i32;
 
-    let mut serr = vec![0u8; 256]; // Error buffer
+    let mut serr = [0 as c_char; 256]; // Error buffer
 
     let res = unsafe {
         swe_houses_ex2(
             jd_ut,
-            iflag,
+            iflag.bits(),
             lat,
             lon,
-            hsys_int,
+            hsys.to_char() as c_int,
             cusps.as_mut_ptr(),
             ascmc.as_mut_ptr(),
             cusp_speeds.as_mut_ptr(),
             ascmc_speeds.as_mut_ptr(),
-            serr.as_mut_ptr() as *mut c_char,
+            serr.as_mut_ptr(),
         )
     };
+    // the message only tells about the switch to Porphyry houses
+    serr_message(&serr)?;
 
-    if res == -1 {
-        let err_msg = unsafe {
-            CStr::from_ptr(serr.as_ptr() as *const c_char)
-                .to_string_lossy()
-                .trim_end_matches('\0')
-                .to_string()
-        };
+    tracing::debug!("All House cusps: {:?}", cusps);
+    tracing::debug!("All House cusp speeds: {:?}", cusp_speeds);
 
-        if err_msg.is_empty() {
-            return Err(
-                "Unknown error occurred while calculating houses".to_string()
-            );
-        }
-        return Err(err_msg);
+    Ok(house_calculation_result(
+        hsys,
+        res,
+        cusps,
+        ascmc,
+        cusp_speeds,
+        ascmc_speeds,
+    ))
+}
+
+/// Houses with their speeds from the sidereal time (ARMC, in degrees) and
+/// the obliquity of the ecliptic, always tropical. The speeds are in degrees
+/// per day of the ARMC moving with the sidereal time.
+pub fn houses_armc_with_speeds(
+    armc: f64,
+    geolat: f64,
+    eps: f64,
+    hsys: HouseSystem,
+) -> Result<HouseCalculationResult, SweError> {
+    reject_gauquelin(hsys)?;
+    check_latitude(geolat)?;
+    let mut cusps = [0.0; 13];
+    let mut ascmc = [0.0; 8];
+    let mut cusp_speeds = [0.0; 13];
+    let mut ascmc_speeds = [0.0; 8];
+    let mut serr = [0 as c_char; 256];
+
+    let res = unsafe {
+        raw::swe_houses_armc_ex2(
+            armc,
+            geolat,
+            eps,
+            hsys.to_char() as c_int,
+            cusps.as_mut_ptr(),
+            ascmc.as_mut_ptr(),
+            cusp_speeds.as_mut_ptr(),
+            ascmc_speeds.as_mut_ptr(),
+            serr.as_mut_ptr(),
+        )
+    };
+    serr_message(&serr)?;
+
+    Ok(house_calculation_result(
+        hsys,
+        res,
+        cusps,
+        ascmc,
+        cusp_speeds,
+        ascmc_speeds,
+    ))
+}
+
+/// The C library does not check the latitude and computes meaningless cusps
+fn check_latitude(geolat: f64) -> Result<(), SweError> {
+    if !(-90.0..=90.0).contains(&geolat) {
+        return Err(SweError::InvalidArgument(format!(
+            "latitude {} is outside -90..90 degrees",
+            geolat
+        )));
     }
+    Ok(())
+}
 
-    tracing::debug!("All House cusps: {:?}", cusps);
-    tracing::debug!("All House cusp speeds: {:?}", cusp_speeds);
+fn reject_gauquelin(hsys: HouseSystem) -> Result<(), SweError> {
+    if hsys == HouseSystem::GauquelinSectors {
+        return Err(SweError::InvalidArgument(
+            "GAUQUELIN_SECTORS has 36 cusps, use gauquelin_sectors".to_string(),
+        ));
+    }
+    Ok(())
+}
 
+fn house_calculation_result(
+    hsys: HouseSystem,
+    status: i32,
+    cusps: [f64; 13],
+    ascmc: [f64; 8],
+    cusp_speeds: [f64; 13],
+    ascmc_speeds: [f64; 8],
+) -> HouseCalculationResult {
     let house_cusps: [f64; 12] = cusps[1..]
         .try_into()
         .expect("Failed to extract house cusps");
@@ -150,37 +273,287 @@ pub fn calculate_houses_extended_with_speeds(
         .try_into()
         .expect("Failed to extract cusp speeds");
 
-    Ok(HouseCalculationResult {
+    HouseCalculationResult {
         cusps: house_cusps,
         ascmc,
         cusp_speeds: house_speeds,
         ascmc_speeds,
-    })
+        warning: porphyry_fallback(hsys, status),
+    }
+}
+
+/// Position of a point in the houses: the house (1 to 12, or the sector 1
+/// to 36 for Gauquelin sectors) and how far into it the point is
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HousePosition {
+    pub house: i32,
+    pub position: f64, // 1.0 to 12.999 (36.999 for Gauquelin sectors)
+}
+
+impl HousePosition {
+    fn new(position: f64) -> HousePosition {
+        HousePosition {
+            house: position.floor() as i32,
+            position,
+        }
+    }
+
+    /// Part of the house already passed, from 0 to 1
+    pub fn fraction(&self) -> f64 {
+        self.position - self.position.floor()
+    }
+}
+
+/// House position of a point of tropical ecliptic longitude and latitude,
+/// from the ARMC (in degrees), the geographic latitude and the true obliquity
+/// of the ecliptic. Unlike a comparison of longitudes with the cusps, the
+/// latitude of the point is taken into account.
+pub fn house_pos(
+    armc: f64,
+    geolat: f64,
+    eps: f64,
+    hsys: HouseSystem,
+    longitude: f64,
+    latitude: f64,
+) -> Result<HousePosition, SweError> {
+    let mut xpin = [longitude, latitude];
+    let mut serr = [0 as c_char; 256];
+    let position = unsafe {
+        raw::swe_house_pos(
+            armc,
+            geolat,
+            eps,
+            hsys.to_char() as c_int,
+            xpin.as_mut_ptr(),
+            serr.as_mut_ptr(),
+        )
+    };
+    let message = serr_message(&serr)?;
+    if position < 1.0 {
+        return Err(SweError::from_serr(&message));
+    }
+    Ok(HousePosition::new(position))
+}
+
+/// House position of an object at a date (UT) and a geographic position.
+/// With SIDEREAL_POSITIONS in iflag the object is in the sidereal zodiac of
+/// the current sidereal mode, as computed by calc_ut with the same flags.
+pub fn object_house_position(
+    tjd_ut: f64,
+    iflag: CalculationFlags,
+    geolat: f64,
+    geolon: f64,
+    hsys: HouseSystem,
+    object: &Object,
+) -> Result<HousePosition, SweError> {
+    iflag.validate()?;
+    let mut longitude = object.longitude;
+    if iflag.contains(CalculationFlags::SIDEREAL_POSITIONS) {
+        // true ayanamsha, the tropical longitude includes nutation
+        let tropical = iflag
+            - CalculationFlags::SIDEREAL_POSITIONS
+            - CalculationFlags::MEAN_EQUINOX_OF_DATE_NO_NUTATION;
+        longitude += get_ayanamsa_ex_ut(tjd_ut, tropical)?.ayanamsha;
+    }
+    house_pos(
+        sidtime(tjd_ut).armc(geolon),
+        geolat,
+        true_obliquity(tjd_ut)?,
+        hsys,
+        degnorm(longitude),
+        object.latitude,
+    )
+}
+
+/// Gauquelin sectors, numbered clockwise from the Ascendant: sectors 1 to
+/// 18 are above the horizon, sector 10 starts at the MC
+#[derive(Debug, Clone, PartialEq)]
+pub struct GauquelinSectorsResult {
+    pub cusps: [f64; 36], // Sector cusps 1-36
+    pub ascmc: [f64; 8],
+    pub cusp_speeds: [f64; 36],
+    pub ascmc_speeds: [f64; 8],
+    pub warning: Option<SweWarning>,
+}
+
+/// Cusps of the 36 Gauquelin sectors, with their speeds
+pub fn gauquelin_sectors(
+    jd_ut: f64,
+    iflag: CalculationFlags,
+    lat: f64,
+    lon: f64,
+) -> Result<GauquelinSectorsResult, SweError> {
+    iflag.validate()?;
+    check_latitude(lat)?;
+    let mut cusps = [0.0; 37];
+    let mut ascmc = [0.0; 10];
+    let mut cusp_speeds = [0.0; 37];
+    let mut ascmc_speeds = [0.0; 10];
+    let mut serr = [0 as c_char; 256];
+    let hsys = HouseSystem::GauquelinSectors;
+
+    let res = unsafe {
+        swe_houses_ex2(
+            jd_ut,
+            iflag.bits(),
+            lat,
+            lon,
+            hsys.to_char() as c_int,
+            cusps.as_mut_ptr(),
+            ascmc.as_mut_ptr(),
+            cusp_speeds.as_mut_ptr(),
+            ascmc_speeds.as_mut_ptr(),
+            serr.as_mut_ptr(),
+        )
+    };
+    serr_message(&serr)?;
+
+    let mut result = GauquelinSectorsResult {
+        cusps: [0.0; 36],
+        ascmc: [0.0; 8],
+        cusp_speeds: [0.0; 36],
+        ascmc_speeds: [0.0; 8],
+        warning: porphyry_fallback(hsys, res),
+    };
+    result.cusps.copy_from_slice(&cusps[1..]);
+    result.cusp_speeds.copy_from_slice(&cusp_speeds[1..]);
+    result.ascmc.copy_from_slice(&ascmc[..8]);
+    result.ascmc_speeds.copy_from_slice(&ascmc_speeds[..8]);
+    Ok(result)
+}
+
+/// Method of gauquelin_sector (imeth of swe_gauquelin_sector)
+#[derive(
+    Debug, Clone, Copy, PartialEq, Display, EnumIter, Serialize, Deserialize,
+)]
+pub enum GauquelinMethod {
+    WithLatitude = 0,    // Placidus house position
+    WithoutLatitude = 1, // Placidus house position, ecliptic latitude 0
+    // From the rising and setting times
+    DiscCenter = 2,
+    DiscCenterWithRefraction = 3,
+    UpperLimb = 4,
+    UpperLimbWithRefraction = 5,
+}
+
+/// Gauquelin sector position (1 to 36.999) of a body for an observer.
+/// The atmosphere is used by the methods with refraction only.
+pub fn gauquelin_sector(
+    tjd_ut: f64,
+    body: Bodies,
+    iflag: CalculationFlags,
+    method: GauquelinMethod,
+    observer: &Observer,
+    atmosphere: &Atmosphere,
+) -> Result<f64, SweError> {
+    call_gauquelin_sector(
+        tjd_ut, body, None, iflag, method, observer, atmosphere,
+    )
+}
+
+/// Gauquelin sector position (1 to 36.999) of a fixed star for an observer
+pub fn gauquelin_sector_star(
+    tjd_ut: f64,
+    star: &str,
+    iflag: CalculationFlags,
+    method: GauquelinMethod,
+    observer: &Observer,
+    atmosphere: &Atmosphere,
+) -> Result<f64, SweError> {
+    call_gauquelin_sector(
+        tjd_ut,
+        Bodies::Sun,
+        Some(star),
+        iflag,
+        method,
+        observer,
+        atmosphere,
+    )
+}
+
+/// Copy a star name into a buffer large enough for `swe_fixstar`, which
+/// rewrites it in place as "name,bayer" (twice `SE_MAX_STNAME`).
+fn gauquelin_star_buffer(star: &str) -> Result<[c_char; 512], SweError> {
+    if star.len() > 255 {
+        return Err(SweError::InvalidArgument(
+            "star is longer than 255 bytes".to_string(),
+        ));
+    }
+    let c_star = CString::new(star).map_err(|_| {
+        SweError::InvalidArgument("star contains a nul byte".to_string())
+    })?;
+    let mut star_buf = [0 as c_char; 512];
+    for (i, &b) in c_star.as_bytes_with_nul().iter().enumerate() {
+        star_buf[i] = b as c_char;
+    }
+    Ok(star_buf)
+}
+
+fn call_gauquelin_sector(
+    tjd_ut: f64,
+    body: Bodies,
+    star: Option<&str>,
+    iflag: CalculationFlags,
+    method: GauquelinMethod,
+    observer: &Observer,
+    atmosphere: &Atmosphere,
+) -> Result<f64, SweError> {
+    iflag.validate()?;
+    let mut star = match star {
+        Some(star) => Some(gauquelin_star_buffer(star)?),
+        None => None,
+    };
+    let starname = match star {
+        Some(ref mut star) => star.as_mut_ptr(),
+        None => ptr::null_mut(),
+    };
+    let mut geopos = observer.geopos();
+    let mut sector = 0.0;
+    let mut serr = [0 as c_char; 256];
+    let status = unsafe {
+        raw::swe_gauquelin_sector(
+            tjd_ut,
+            body as i32,
+            starname,
+            iflag.bits(),
+            method as i32,
+            geopos.as_mut_ptr(),
+            atmosphere.pressure,
+            atmosphere.temperature,
+            &mut sector,
+            serr.as_mut_ptr(),
+        )
+    };
+    if status < 0 {
+        return Err(SweError::from_status(status, &serr_message(&serr)?));
+    }
+    Ok(sector)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        constants::{
-            Ayanamsha, CalculationFlags, HouseSystems, EPHEMERIS_PATH,
-        },
+        constants::{Ayanamsha, CalculationFlags, EPHEMERIS_PATH},
         ensure_ephemeris_initialized,
+        ephemeris::with_sidereal_mode,
         sweconst::Calendar,
         swerust::{
             handler_swe02::set_ephe_path,
-            handler_swe03::set_sidereal_mode,
+            handler_swe03::calc_ut,
             handler_swe08::{utc_time_zone, utc_to_jd},
+            handler_swe17::coordinates::true_obliquity,
         },
     };
+    use strum::IntoEnumIterator;
 
     #[test]
     pub fn test_house_calculation() {
         let lat = 43.084128;
         let lng = 25.5919228;
         let date = get_test_date_time();
-        let house_system = HouseSystems::PLACIDUS;
-        set_ephe_path(EPHEMERIS_PATH);
+        let house_system = HouseSystem::Placidus;
+        set_ephe_path(EPHEMERIS_PATH).unwrap();
         let flag = CalculationFlags::SPEED_PRECISION;
 
         let expected_result = HousesResult {
@@ -236,22 +609,41 @@ mod tests {
                 0.0,
                 0.0,
             ],
-            result: 0,
+            warning: None,
         };
 
         let actual_result =
-            houses_with_flag(date, flag, lat, lng, house_system);
+            houses_with_flag(date, flag, lat, lng, house_system).unwrap();
 
         assert_eq!(actual_result, expected_result);
     }
 
+    #[test]
+    pub fn test_houses_armc() {
+        let _ = ensure_ephemeris_initialized();
+        let lat = 43.084128;
+        let date = get_test_date_time();
+        let expected =
+            houses(date, lat, 25.5919228, HouseSystem::Placidus).unwrap();
+        let eps = true_obliquity(date).unwrap();
+
+        let actual =
+            houses_armc(expected.ascmc[2], lat, eps, HouseSystem::Placidus)
+                .unwrap();
+
+        for i in 1..=12 {
+            assert!((actual.cusps[i] - expected.cusps[i]).abs() < 1e-6);
+        }
+        assert!((actual.ascmc[0] - expected.ascmc[0]).abs() < 1e-6);
+    }
+
     #[test]
     pub fn test_house_calculation_extended_with_speeds() {
         let lat = 43.084128;
         let lng = 25.5919228;
         let date = get_test_date_time();
-        let house_system = HouseSystems::PLACIDUS;
-        set_ephe_path(EPHEMERIS_PATH);
+        let house_system = HouseSystem::Placidus;
+        set_ephe_path(EPHEMERIS_PATH).unwrap();
 
         let expected_result = HouseCalculationResult {
             cusps: [
@@ -302,11 +694,12 @@ mod tests {
                 520.5003314381933,
                 277.88707643085064,
             ],
+            warning: None,
         };
 
         let actual_result = calculate_houses_extended_with_speeds(
             date,
-            0,
+            CalculationFlags::empty(),
             lat,
             lng,
             house_system,
@@ -322,8 +715,8 @@ mod tests {
         let lat = 43.084128;
         let lng = 25.5919228;
         let date = get_test_date_time();
-        let house_system = HouseSystems::PLACIDUS;
-        set_sidereal_mode(Ayanamsha::GALACTIC_CENTER_MULA_WILHELM);
+        let house_system = HouseSystem::Placidus;
+        let ayanamsha = Some(Ayanamsha::GalacticCenterMulaWilhelm);
         let flag = CalculationFlags::SIDEREAL_POSITIONS;
 
         let expected_result = HouseCalculationResult {
@@ -375,15 +768,18 @@ mod tests {
                 520.5003314381933,
                 277.88707643085064,
             ],
+            warning: None,
         };
 
-        let actual_result = calculate_houses_extended_with_speeds(
-            date,
-            flag,
-            lat,
-            lng,
-            house_system,
-        );
+        let actual_result = with_sidereal_mode(ayanamsha, || {
+            calculate_houses_extended_with_speeds(
+                date,
+                flag,
+                lat,
+                lng,
+                house_system,
+            )
+        });
 
         assert!(actual_result.is_ok());
         assert_eq!(actual_result.unwrap(), expected_result);
@@ -395,8 +791,8 @@ mod tests {
         let lat = 43.084128;
         let lng = 25.5919228;
         let date = get_test_date_time();
-        let iflag = 0;
-        let hsys = 'G'; // Invalid house system
+        let iflag = CalculationFlags::empty();
+        let hsys = HouseSystem::GauquelinSectors;
 
         let result =
             calculate_houses_extended_with_speeds(date, iflag, lat, lng, hsys);
@@ -404,8 +800,275 @@ mod tests {
         assert!(result.is_err(), "Expected an error but got Ok");
         if let Err(err_msg) = result {
             println!("Error message: {}", err_msg);
-            assert!(!err_msg.is_empty(), "Error message should not be empty");
+            assert!(matches!(err_msg, SweError::InvalidArgument(_)));
+        }
+    }
+
+    #[test]
+    fn test_houses_within_polar_circle() {
+        let _ = ensure_ephemeris_initialized();
+        let date = get_test_date_time();
+
+        let result =
+            houses(date, 70.0, 25.5919228, HouseSystem::Placidus).unwrap();
+
+        assert_eq!(
+            result.warning,
+            Some(SweWarning::HouseSystemFallback {
+                requested: HouseSystem::Placidus,
+                used: HouseSystem::Porphyrius,
+            })
+        );
+    }
+
+    #[test]
+    fn test_houses_invalid_latitude() {
+        let _ = ensure_ephemeris_initialized();
+        let date = get_test_date_time();
+
+        for lat in [91.0, -90.5, f64::NAN] {
+            assert!(matches!(
+                houses(date, lat, 25.5919228, HouseSystem::Placidus),
+                Err(SweError::InvalidArgument(_))
+            ));
+            assert!(houses_armc(0.0, lat, 23.4, HouseSystem::Equal).is_err());
         }
+        assert!(houses(date, 90.0, 0.0, HouseSystem::Equal).is_ok());
+    }
+
+    #[test]
+    fn test_house_system() {
+        for hsys in HouseSystem::iter() {
+            assert_eq!(HouseSystem::from_char(hsys.to_char()).unwrap(), hsys);
+            assert!(!hsys.name().unwrap().is_empty());
+        }
+        assert_eq!(HouseSystem::iter().count(), 25);
+        assert_eq!(HouseSystem::from_char('A').unwrap(), HouseSystem::Equal);
+        assert_eq!(HouseSystem::from_char('J').unwrap(), HouseSystem::SavardA);
+        assert!(HouseSystem::from_char('Z').is_err());
+        assert_eq!(HouseSystem::Sripati.name().unwrap(), "Sripati");
+        assert_eq!(HouseSystem::PolichPage.name().unwrap(), "Polich/Page");
+        assert_eq!(HouseSystem::WholeSign.name().unwrap(), "equal/ whole sign");
+
+        let json = serde_json::to_string(&HouseSystem::SunshineMakransky);
+        assert_eq!(json.unwrap(), "\"i\"");
+        let hsys: HouseSystem = serde_json::from_str("\"T\"").unwrap();
+        assert_eq!(hsys, HouseSystem::PolichPage);
+        assert!(serde_json::from_str::<HouseSystem>("\"Z\"").is_err());
+    }
+
+    #[test]
+    fn test_houses_armc_with_speeds() {
+        let _ = ensure_ephemeris_initialized();
+        let jd = get_test_date_time();
+        let (lat, lon) = (43.084128, 25.5919228);
+        let expected = calculate_houses_extended_with_speeds(
+            jd,
+            CalculationFlags::SWISS_EPHEMERIS,
+            lat,
+            lon,
+            HouseSystem::Placidus,
+        )
+        .unwrap();
+        let eps = true_obliquity(jd).unwrap();
+        let actual = houses_armc_with_speeds(
+            expected.ascmc[2],
+            lat,
+            eps,
+            HouseSystem::Placidus,
+        )
+        .unwrap();
+        for (a, e) in actual.cusps.iter().zip(expected.cusps.iter()) {
+            assert!((a - e).abs() < 1e-6);
+        }
+        assert_eq!(actual.cusp_speeds.len(), 12);
+        for (a, e) in actual.cusp_speeds.iter().zip(&expected.cusp_speeds) {
+            assert!((a - e).abs() < 1e-3);
+        }
+        assert!(houses_armc_with_speeds(
+            expected.ascmc[2],
+            lat,
+            eps,
+            HouseSystem::GauquelinSectors
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_house_pos() {
+        let _ = ensure_ephemeris_initialized();
+        let jd = get_test_date_time();
+        let (lat, lon) = (43.084128, 25.5919228);
+        let houses = calculate_houses_extended_with_speeds(
+            jd,
+            CalculationFlags::SWISS_EPHEMERIS,
+            lat,
+            lon,
+            HouseSystem::Placidus,
+        )
+        .unwrap();
+        let armc = houses.ascmc[2];
+        let eps = true_obliquity(jd).unwrap();
+
+        // a cusp is at the start of its house
+        let fifth = house_pos(
+            armc,
+            lat,
+            eps,
+            HouseSystem::Placidus,
+            houses.cusps[4],
+            0.0,
+        )
+        .unwrap();
+        assert_eq!(fifth.house, 5);
+        assert!(fifth.fraction() < 1e-6);
+        let middle = degnorm(
+            houses.cusps[4] + degnorm(houses.cusps[5] - houses.cusps[4]) / 2.0,
+        );
+        let position =
+            house_pos(armc, lat, eps, HouseSystem::Placidus, middle, 0.0)
+                .unwrap();
+        assert_eq!(position.house, 5);
+
+        // near the Descendant, a point far south of the ecliptic has already
+        // set while its longitude is still in house 7
+        let descendant = degnorm(houses.ascmc[0] + 180.0);
+        let low = house_pos(
+            armc,
+            lat,
+            eps,
+            HouseSystem::Placidus,
+            descendant + 2.0,
+            0.0,
+        )
+        .unwrap();
+        let high = house_pos(
+            armc,
+            lat,
+            eps,
+            HouseSystem::Placidus,
+            descendant + 2.0,
+            -10.0,
+        )
+        .unwrap();
+        assert_eq!(low.house, 7);
+        assert_eq!(high.house, 6);
+
+        let moon = calc_ut(jd, Bodies::Moon, CalculationFlags::SPEED_PRECISION)
+            .unwrap();
+        let object = Object::new(
+            Bodies::Moon,
+            "Moon",
+            Bodies::Moon.object_type(),
+            moon.longitude,
+            moon.latitude,
+            moon.speed_longitude,
+        );
+        let position = object_house_position(
+            jd,
+            CalculationFlags::SPEED_PRECISION,
+            lat,
+            lon,
+            HouseSystem::Placidus,
+            &object,
+        )
+        .unwrap();
+        let expected = house_pos(
+            armc,
+            lat,
+            eps,
+            HouseSystem::Placidus,
+            moon.longitude,
+            moon.latitude,
+        )
+        .unwrap();
+        assert!((position.position - expected.position).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_gauquelin_sectors() {
+        let _ = ensure_ephemeris_initialized();
+        let jd = get_test_date_time();
+        let sectors = gauquelin_sectors(
+            jd,
+            CalculationFlags::SWISS_EPHEMERIS,
+            43.084128,
+            25.5919228,
+        )
+        .unwrap();
+        assert_eq!(sectors.warning, None);
+        // sector 1 starts at the Ascendant, sector 10 at the MC
+        assert!((sectors.cusps[0] - sectors.ascmc[0]).abs() < 1e-6);
+        assert!((sectors.cusps[9] - sectors.ascmc[1]).abs() < 1e-6);
+        assert!(sectors.cusps.iter().all(|c| (0.0..360.0).contains(c)));
+    }
+
+    #[test]
+    fn test_gauquelin_sector() {
+        let _ = ensure_ephemeris_initialized();
+        let jd = get_test_date_time();
+        let observer = Observer::new(43.084128, 25.5919228, 265.0);
+        let atmosphere = Atmosphere::default();
+        let flags = CalculationFlags::SWISS_EPHEMERIS;
+        let sector = |method| {
+            gauquelin_sector(
+                jd,
+                Bodies::Sun,
+                flags,
+                method,
+                &observer,
+                &atmosphere,
+            )
+            .unwrap()
+        };
+        // at 16:10 local time in November the Sun is about to set, at the
+        // end of sector 18
+        for method in GauquelinMethod::iter() {
+            let s = sector(method);
+            assert!(s > 17.0 && s < 18.5, "{}: {}", method, s);
+        }
+        assert!(
+            (sector(GauquelinMethod::WithLatitude)
+                - sector(GauquelinMethod::DiscCenter))
+            .abs()
+                < 1.0
+        );
+        let star = gauquelin_sector_star(
+            jd,
+            "Aldebaran",
+            flags,
+            GauquelinMethod::WithoutLatitude,
+            &observer,
+            &atmosphere,
+        )
+        .unwrap();
+        assert!((1.0..37.0).contains(&star));
+
+        // Canopus never rises at 70° north
+        let north = Observer::new(70.0, 25.0, 0.0);
+        assert!(matches!(
+            gauquelin_sector_star(
+                jd,
+                "Canopus",
+                flags,
+                GauquelinMethod::DiscCenter,
+                &north,
+                &atmosphere,
+            ),
+            Err(SweError::NotFound(_))
+        ));
+
+        assert!(matches!(
+            gauquelin_sector_star(
+                jd,
+                &"x".repeat(256),
+                flags,
+                GauquelinMethod::DiscCenter,
+                &observer,
+                &atmosphere,
+            ),
+            Err(SweError::InvalidArgument(_))
+        ));
     }
 
     pub fn get_test_date_time() -> f64 {
@@ -418,7 +1081,8 @@ mod tests {
             utc_time_zone.min[0],
             utc_time_zone.sec[0],
             Calendar::Gregorian,
-        );
+        )
+        .unwrap();
         jd.julian_day_ut
     }
 }
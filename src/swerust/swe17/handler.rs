@@ -2,10 +2,13 @@ extern crate math;
 use crate::raw;
 use crate::sweconst::Signs;
 use math::round;
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 // use std::ffi::{CStr, CString};
 // use std::os::raw::c_char;
 
+pub mod coordinates;
+
 /*
  * 17. Auxilliary functions
  */
@@ -17,7 +20,7 @@ pub fn radnorm(x: f64) -> f64 {
     unsafe { raw::swe_radnorm(x) }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SplitDegResult {
     pub print: String,
     pub deg: i32,
@@ -0,0 +1,390 @@
+use crate::constants::CalculationFlags;
+use crate::error::SweError;
+use crate::raw;
+use crate::sweconst::{Bodies, Observer};
+use crate::swerust::handler_swe03::{calc_ut, CalcUtResult, FixStarResult};
+use crate::swerust::handler_swe08::sidtime;
+use crate::swerust::handler_swe17::degnorm;
+
+/*
+ * Coordinate transformations
+ *
+ * Conversions between ecliptic, equatorial, horizontal and galactic
+ * coordinates built on swe_cotrans() and swe_cotrans_sp(). The obliquity is
+ * the true obliquity of the date taken from Bodies::EclNut, and the
+ * horizontal frame uses the apparent sidereal time of swe_sidtime().
+ *
+ * Every conversion goes through equatorial coordinates. The galactic frame is
+ * defined for J2000, so galactic conversions expect equatorial positions
+ * referred to J2000 (SEFLG_J2000 | SEFLG_EQUATORIAL); no precession is
+ * applied here.
+ */
+
+/// Rotation of the earth in degrees per day of UT
+const SIDEREAL_RATE: f64 = 360.985_647_366_29;
+/// Right ascension of the ascending node of the galactic equator (J2000)
+const GALACTIC_NODE_RA: f64 = 282.859_48;
+/// Galactic longitude of the ascending node of the galactic equator
+const GALACTIC_NODE_LONGITUDE: f64 = 32.931_92;
+/// Inclination of the galactic equator to the celestial equator
+const GALACTIC_INCLINATION: f64 = 62.871_75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter)]
+pub enum CoordinateSystem {
+    Ecliptic,   // longitude, latitude
+    Equatorial, // right ascension, declination
+    Horizontal, // azimuth from south via west, true altitude
+    Galactic,   // galactic longitude, galactic latitude
+}
+
+/// Polar coordinates with speeds, in the layout of the xx array of swe_calc
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SphericalPosition {
+    pub longitude: f64,
+    pub latitude: f64,
+    pub distance: f64,
+    pub speed_longitude: f64, // deg/day
+    pub speed_latitude: f64,  // deg/day
+    pub speed_distance: f64,
+}
+
+impl SphericalPosition {
+    pub fn new(longitude: f64, latitude: f64, distance: f64) -> Self {
+        SphericalPosition {
+            longitude,
+            latitude,
+            distance,
+            ..Default::default()
+        }
+    }
+
+    fn to_array(self) -> [f64; 6] {
+        [
+            self.longitude,
+            self.latitude,
+            self.distance,
+            self.speed_longitude,
+            self.speed_latitude,
+            self.speed_distance,
+        ]
+    }
+
+    fn from_array(x: [f64; 6]) -> Self {
+        SphericalPosition {
+            longitude: x[0],
+            latitude: x[1],
+            distance: x[2],
+            speed_longitude: x[3],
+            speed_latitude: x[4],
+            speed_distance: x[5],
+        }
+    }
+}
+
+impl From<&CalcUtResult> for SphericalPosition {
+    fn from(result: &CalcUtResult) -> Self {
+        SphericalPosition {
+            longitude: result.longitude,
+            latitude: result.latitude,
+            distance: result.distance_au,
+            speed_longitude: result.speed_longitude,
+            speed_latitude: result.speed_latitude,
+            speed_distance: result.speed_distance_au,
+        }
+    }
+}
+
+impl From<&FixStarResult> for SphericalPosition {
+    fn from(result: &FixStarResult) -> Self {
+        SphericalPosition {
+            longitude: result.longitude,
+            latitude: result.latitude,
+            distance: result.distance_au,
+            speed_longitude: result.speed_longitude,
+            speed_latitude: result.speed_latitude,
+            speed_distance: result.speed_distance_au,
+        }
+    }
+}
+
+/// Wrapper for `swe_cotrans`
+/// eps is positive for equator to ecliptic and negative for ecliptic to
+/// equator
+pub fn cotrans(xpo: [f64; 3], eps: f64) -> [f64; 3] {
+    let mut xpn = [0.0; 3];
+    unsafe { raw::swe_cotrans(xpo.as_ptr(), xpn.as_mut_ptr(), eps) };
+    xpn
+}
+
+/// Wrapper for `swe_cotrans_sp`
+pub fn cotrans_sp(xpo: [f64; 6], eps: f64) -> [f64; 6] {
+    let mut xpn = [0.0; 6];
+    unsafe { raw::swe_cotrans_sp(xpo.as_ptr(), xpn.as_mut_ptr(), eps) };
+    xpn
+}
+
+/// True obliquity of the ecliptic (including nutation) in degrees
+pub fn true_obliquity(tjd_ut: f64) -> Result<f64, SweError> {
+    Ok(calc_ut(tjd_ut, Bodies::EclNut, CalculationFlags::empty())?.longitude)
+}
+
+/// Converts positions between coordinate systems at a given date
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoordinateTransformer {
+    obliquity: f64,
+    horizon: Option<(f64, f64)>, // armc and geographic latitude
+}
+
+impl CoordinateTransformer {
+    /// Transformer without an observer; horizontal coordinates are not
+    /// available
+    pub fn new(tjd_ut: f64) -> Result<Self, SweError> {
+        Ok(CoordinateTransformer {
+            obliquity: true_obliquity(tjd_ut)?,
+            horizon: None,
+        })
+    }
+
+    /// Transformer for an observer, supporting horizontal coordinates
+    pub fn with_observer(
+        tjd_ut: f64,
+        observer: &Observer,
+    ) -> Result<Self, SweError> {
+        let armc = sidtime(tjd_ut).armc(observer.longitude);
+        Ok(CoordinateTransformer {
+            obliquity: true_obliquity(tjd_ut)?,
+            horizon: Some((armc, observer.latitude)),
+        })
+    }
+
+    /// True obliquity used for ecliptic conversions
+    pub fn obliquity(&self) -> f64 {
+        self.obliquity
+    }
+
+    pub fn transform(
+        &self,
+        position: &SphericalPosition,
+        from: CoordinateSystem,
+        to: CoordinateSystem,
+    ) -> Result<SphericalPosition, SweError> {
+        if from == to {
+            return Ok(*position);
+        }
+        let equatorial = self.equatorial_from(position.to_array(), from)?;
+        let x = self.equatorial_to(equatorial, to)?;
+        Ok(SphericalPosition::from_array(x))
+    }
+
+    fn equatorial_from(
+        &self,
+        x: [f64; 6],
+        from: CoordinateSystem,
+    ) -> Result<[f64; 6], SweError> {
+        match from {
+            CoordinateSystem::Equatorial => Ok(x),
+            CoordinateSystem::Ecliptic => Ok(cotrans_sp(x, -self.obliquity)),
+            CoordinateSystem::Galactic => {
+                let mut y = x;
+                y[0] = degnorm(x[0] - GALACTIC_NODE_LONGITUDE);
+                let mut y = cotrans_sp(y, -GALACTIC_INCLINATION);
+                y[0] = degnorm(y[0] + GALACTIC_NODE_RA);
+                Ok(y)
+            },
+            CoordinateSystem::Horizontal => {
+                let (armc, latitude) = self.horizon()?;
+                // azimuth from south via west -> from east, counterclockwise
+                let mut y = x;
+                y[0] = degnorm(270.0 - x[0]);
+                y[3] = -x[3];
+                let mut y = cotrans_sp(y, latitude - 90.0);
+                y[0] = degnorm(y[0] + armc + 90.0);
+                y[3] += SIDEREAL_RATE;
+                Ok(y)
+            },
+        }
+    }
+
+    fn equatorial_to(
+        &self,
+        x: [f64; 6],
+        to: CoordinateSystem,
+    ) -> Result<[f64; 6], SweError> {
+        match to {
+            CoordinateSystem::Equatorial => Ok(x),
+            CoordinateSystem::Ecliptic => Ok(cotrans_sp(x, self.obliquity)),
+            CoordinateSystem::Galactic => {
+                let mut y = x;
+                y[0] = degnorm(x[0] - GALACTIC_NODE_RA);
+                let mut y = cotrans_sp(y, GALACTIC_INCLINATION);
+                y[0] = degnorm(y[0] + GALACTIC_NODE_LONGITUDE);
+                Ok(y)
+            },
+            CoordinateSystem::Horizontal => {
+                let (armc, latitude) = self.horizon()?;
+                let mut y = x;
+                y[0] = degnorm(x[0] - armc - 90.0);
+                y[3] = x[3] - SIDEREAL_RATE;
+                let mut y = cotrans_sp(y, 90.0 - latitude);
+                // azimuth from east, counterclockwise -> from south via west
+                y[0] = degnorm(270.0 - y[0]);
+                y[3] = -y[3];
+                Ok(y)
+            },
+        }
+    }
+
+    fn horizon(&self) -> Result<(f64, f64), SweError> {
+        self.horizon.ok_or_else(|| {
+            SweError::InvalidArgument(
+                "Horizontal coordinates require an observer position"
+                    .to_string(),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::CalculationFlags;
+    use crate::ensure_ephemeris_initialized;
+    use crate::sweconst::Calendar;
+    use crate::swerust::handler_swe07::horizon::{
+        azalt, Atmosphere, HorizonFrame,
+    };
+    use crate::swerust::handler_swe08::{utc_time_zone, utc_to_jd};
+
+    fn test_jd() -> f64 {
+        let utc = utc_time_zone(2024, 11, 21, 16, 10, 0.0, 2.0);
+        utc_to_jd(
+            utc.year[0],
+            utc.month[0],
+            utc.day[0],
+            utc.hour[0],
+            utc.min[0],
+            utc.sec[0],
+            Calendar::Gregorian,
+        )
+        .unwrap()
+        .julian_day_ut
+    }
+
+    fn assert_close(a: f64, b: f64, eps: f64) {
+        assert!((a - b).abs() < eps, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_ecliptic_to_equatorial_matches_calc_ut() {
+        let _ = ensure_ephemeris_initialized();
+        let tjd_ut = test_jd();
+        let flags = CalculationFlags::SWISS_EPHEMERIS
+            | CalculationFlags::SPEED_PRECISION;
+        let ecliptic = calc_ut(tjd_ut, Bodies::Mars, flags).unwrap();
+        let equatorial = calc_ut(
+            tjd_ut,
+            Bodies::Mars,
+            flags | CalculationFlags::EQUATORIAL_POSITIONS,
+        )
+        .unwrap();
+        let transformer = CoordinateTransformer::new(tjd_ut).unwrap();
+
+        let result = transformer
+            .transform(
+                &SphericalPosition::from(&ecliptic),
+                CoordinateSystem::Ecliptic,
+                CoordinateSystem::Equatorial,
+            )
+            .unwrap();
+
+        assert_close(transformer.obliquity(), 23.438486, 1e-6);
+        assert_close(result.longitude, equatorial.longitude, 1e-7);
+        assert_close(result.latitude, equatorial.latitude, 1e-7);
+        assert_close(result.speed_longitude, equatorial.speed_longitude, 1e-5);
+        assert_close(result.speed_latitude, equatorial.speed_latitude, 1e-5);
+        assert_eq!(result.distance, ecliptic.distance_au);
+    }
+
+    #[test]
+    fn test_galactic_center() {
+        let _ = ensure_ephemeris_initialized();
+        let transformer = CoordinateTransformer::new(test_jd()).unwrap();
+        let center = SphericalPosition::new(266.404_99, -28.936_17, 1.0);
+
+        let galactic = transformer
+            .transform(
+                &center,
+                CoordinateSystem::Equatorial,
+                CoordinateSystem::Galactic,
+            )
+            .unwrap();
+        assert!(galactic.longitude < 1e-3 || galactic.longitude > 360.0 - 1e-3);
+        assert_close(galactic.latitude, 0.0, 1e-3);
+
+        let back = transformer
+            .transform(
+                &galactic,
+                CoordinateSystem::Galactic,
+                CoordinateSystem::Equatorial,
+            )
+            .unwrap();
+        assert_close(back.longitude, center.longitude, 1e-9);
+        assert_close(back.latitude, center.latitude, 1e-9);
+    }
+
+    #[test]
+    fn test_horizontal_matches_azalt() {
+        let _ = ensure_ephemeris_initialized();
+        let tjd_ut = test_jd();
+        let observer = Observer::new(43.084128, 25.5919228, 265.0);
+        let flags = CalculationFlags::SWISS_EPHEMERIS
+            | CalculationFlags::SPEED_PRECISION;
+        let sun = calc_ut(tjd_ut, Bodies::Sun, flags).unwrap();
+        let transformer =
+            CoordinateTransformer::with_observer(tjd_ut, &observer).unwrap();
+
+        let horizontal = transformer
+            .transform(
+                &SphericalPosition::from(&sun),
+                CoordinateSystem::Ecliptic,
+                CoordinateSystem::Horizontal,
+            )
+            .unwrap();
+        let expected = azalt(
+            tjd_ut,
+            HorizonFrame::Ecliptic,
+            &observer,
+            &Atmosphere::default(),
+            sun.longitude,
+            sun.latitude,
+        );
+        assert_close(horizontal.longitude, expected.azimuth, 1e-7);
+        assert_close(horizontal.latitude, expected.true_altitude, 1e-7);
+        // the sun is setting in the south-west
+        assert!(horizontal.speed_longitude > 0.0);
+        assert!(horizontal.speed_latitude < 0.0);
+
+        let back = transformer
+            .transform(
+                &horizontal,
+                CoordinateSystem::Horizontal,
+                CoordinateSystem::Ecliptic,
+            )
+            .unwrap();
+        assert_close(back.longitude, sun.longitude, 1e-8);
+        assert_close(back.latitude, sun.latitude, 1e-8);
+        assert_close(back.speed_longitude, sun.speed_longitude, 1e-6);
+    }
+
+    #[test]
+    fn test_horizontal_requires_observer() {
+        let _ = ensure_ephemeris_initialized();
+        let transformer = CoordinateTransformer::new(test_jd()).unwrap();
+        let result = transformer.transform(
+            &SphericalPosition::new(10.0, 0.0, 1.0),
+            CoordinateSystem::Ecliptic,
+            CoordinateSystem::Horizontal,
+        );
+        assert!(result.is_err());
+    }
+}
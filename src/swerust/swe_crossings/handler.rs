@@ -0,0 +1,489 @@
+use std::os::raw::c_char;
+
+use crate::constants::CalculationFlags;
+use crate::error::{serr_message, SweError};
+use crate::raw;
+use crate::stations::refine as refine_station;
+use crate::sweconst::Bodies;
+use crate::swerust::handler_swe03::calc_ut;
+use crate::swerust::handler_swe17::degnorm;
+
+/*
+ * Crossings over a given longitude
+ *
+ * swe_solcross_ut(), swe_mooncross_ut(), swe_mooncross_node_ut() and
+ * swe_helio_cross_ut() find the next crossing of the Sun, the Moon or a
+ * planet (heliocentric) over a longitude. They assume the body moves in one
+ * direction, so they are not suitable for geocentric planets which turn
+ * retrograde.
+ *
+ * crossings_between(), next_crossing() and previous_crossing() work for any
+ * body available in calc_ut(). They sample the longitude and speed with a
+ * step smaller than the time between two stations. A step in which the speed
+ * changes sign is split at the station, as the body may cross the longitude
+ * before and after it, and every bracketed crossing is refined by bisection,
+ * so a retrograde loop over the longitude yields all three crossings.
+ */
+
+/// Precision of the crossing times found by bisection (about 1 ms)
+const CROSS_PRECISION_DAYS: f64 = 1e-8;
+/// Search limit of next_crossing() and previous_crossing(), a bit longer than
+/// the orbital period of Pluto
+const MAX_SEARCH_DAYS: f64 = 260.0 * 365.25;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoonNodeCrossing {
+    pub julian_day_ut: f64,
+    pub longitude: f64, // longitude of the Moon at the crossing
+    pub latitude: f64,  // latitude of the Moon at the crossing (close to 0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crossing {
+    pub julian_day_ut: f64,
+    pub longitude: f64,
+    pub speed_longitude: f64, // negative when the body is retrograde
+}
+
+impl Crossing {
+    pub fn is_retrograde(&self) -> bool {
+        self.speed_longitude < 0.0
+    }
+}
+
+fn swe_error(serr: &[c_char]) -> SweError {
+    match serr_message(serr) {
+        Ok(message) => SweError::from_serr(&message),
+        Err(e) => e,
+    }
+}
+
+/// Wrapper for `swe_solcross_ut`
+pub fn solcross_ut(
+    x2cross: f64,
+    jd_ut: f64,
+    iflag: CalculationFlags,
+) -> Result<f64, SweError> {
+    iflag.validate()?;
+    let mut serr = [0; 256];
+    let jd = unsafe {
+        raw::swe_solcross_ut(x2cross, jd_ut, iflag.bits(), serr.as_mut_ptr())
+    };
+    if jd < jd_ut {
+        return Err(swe_error(&serr));
+    }
+    Ok(jd)
+}
+
+/// Wrapper for `swe_mooncross_ut`
+pub fn mooncross_ut(
+    x2cross: f64,
+    jd_ut: f64,
+    iflag: CalculationFlags,
+) -> Result<f64, SweError> {
+    iflag.validate()?;
+    let mut serr = [0; 256];
+    let jd = unsafe {
+        raw::swe_mooncross_ut(x2cross, jd_ut, iflag.bits(), serr.as_mut_ptr())
+    };
+    if jd < jd_ut {
+        return Err(swe_error(&serr));
+    }
+    Ok(jd)
+}
+
+/// Wrapper for `swe_mooncross_node_ut`
+pub fn mooncross_node_ut(
+    jd_ut: f64,
+    iflag: CalculationFlags,
+) -> Result<MoonNodeCrossing, SweError> {
+    iflag.validate()?;
+    let mut serr = [0; 256];
+    let mut longitude = 0.0;
+    let mut latitude = 0.0;
+    let jd = unsafe {
+        raw::swe_mooncross_node_ut(
+            jd_ut,
+            iflag.bits(),
+            &mut longitude,
+            &mut latitude,
+            serr.as_mut_ptr(),
+        )
+    };
+    if jd < jd_ut {
+        return Err(swe_error(&serr));
+    }
+    Ok(MoonNodeCrossing {
+        julian_day_ut: jd,
+        longitude,
+        latitude,
+    })
+}
+
+/// Wrapper for `swe_helio_cross_ut`
+/// Returns the next heliocentric crossing, or the previous one if `backward`
+pub fn helio_cross_ut(
+    body: Bodies,
+    x2cross: f64,
+    jd_ut: f64,
+    iflag: CalculationFlags,
+    backward: bool,
+) -> Result<f64, SweError> {
+    iflag.validate()?;
+    let mut serr = [0; 256];
+    let mut jd_cross = 0.0;
+    let dir = if backward { -1 } else { 1 };
+    let status = unsafe {
+        raw::swe_helio_cross_ut(
+            body as i32,
+            x2cross,
+            jd_ut,
+            iflag.bits(),
+            dir,
+            &mut jd_cross,
+            serr.as_mut_ptr(),
+        )
+    };
+    if status < 0 {
+        return Err(swe_error(&serr));
+    }
+    Ok(jd_cross)
+}
+
+/// All crossings of `body` over `x2cross` between `jd_start` and `jd_end`
+/// (UT), in chronological order
+pub fn crossings_between(
+    body: Bodies,
+    x2cross: f64,
+    jd_start: f64,
+    jd_end: f64,
+    iflag: CalculationFlags,
+) -> Result<Vec<Crossing>, SweError> {
+    let mut crossings = Vec::new();
+    if jd_end > jd_start {
+        scan(body, x2cross, jd_start, jd_end, iflag, |crossing| {
+            crossings.push(crossing);
+            true
+        })?;
+    }
+    Ok(crossings)
+}
+
+/// First crossing of `body` over `x2cross` after `jd_ut`
+pub fn next_crossing(
+    body: Bodies,
+    x2cross: f64,
+    jd_ut: f64,
+    iflag: CalculationFlags,
+) -> Result<Crossing, SweError> {
+    first_crossing(body, x2cross, jd_ut, jd_ut + MAX_SEARCH_DAYS, iflag)
+}
+
+/// Last crossing of `body` over `x2cross` before `jd_ut`
+pub fn previous_crossing(
+    body: Bodies,
+    x2cross: f64,
+    jd_ut: f64,
+    iflag: CalculationFlags,
+) -> Result<Crossing, SweError> {
+    first_crossing(body, x2cross, jd_ut, jd_ut - MAX_SEARCH_DAYS, iflag)
+}
+
+fn first_crossing(
+    body: Bodies,
+    x2cross: f64,
+    jd_start: f64,
+    jd_end: f64,
+    iflag: CalculationFlags,
+) -> Result<Crossing, SweError> {
+    let mut found = None;
+    scan(body, x2cross, jd_start, jd_end, iflag, |crossing| {
+        found = Some(crossing);
+        false
+    })?;
+    found.ok_or_else(|| {
+        SweError::NotFound(format!(
+            "{} does not cross {}° within the search range",
+            body, x2cross
+        ))
+    })
+}
+
+/// Sampling step in days; shorter than the time between two stations of a
+/// body
+pub(crate) fn search_step(body: Bodies) -> f64 {
+    match body {
+        Bodies::Moon => 0.25,
+        Bodies::Jupiter
+        | Bodies::Saturn
+        | Bodies::Uranus
+        | Bodies::Neptune
+        | Bodies::Pluto => 5.0,
+        _ => 1.0,
+    }
+}
+
+/// Longitude of the body relative to x2cross, normalized to -180..180
+pub(crate) fn distance(
+    body: Bodies,
+    x2cross: f64,
+    jd_ut: f64,
+    iflag: CalculationFlags,
+) -> Result<f64, SweError> {
+    Ok(sample(body, x2cross, jd_ut, iflag)?.0)
+}
+
+/// Distance to x2cross as in distance(), and speed in longitude
+fn sample(
+    body: Bodies,
+    x2cross: f64,
+    jd_ut: f64,
+    iflag: CalculationFlags,
+) -> Result<(f64, f64), SweError> {
+    let result = calc_ut(jd_ut, body, iflag)?;
+    let d = degnorm(result.longitude - x2cross);
+    let d = if d > 180.0 { d - 360.0 } else { d };
+    Ok((d, result.speed_longitude))
+}
+
+/// Walks from jd_start towards jd_end (in either direction) and calls
+/// `on_crossing` for every crossing, until it returns false
+fn scan<F>(
+    body: Bodies,
+    x2cross: f64,
+    jd_start: f64,
+    jd_end: f64,
+    iflag: CalculationFlags,
+    mut on_crossing: F,
+) -> Result<(), SweError>
+where
+    F: FnMut(Crossing) -> bool,
+{
+    let iflag = iflag | CalculationFlags::SPEED_PRECISION;
+    let forward = jd_end >= jd_start;
+    let step = if forward {
+        search_step(body)
+    } else {
+        -search_step(body)
+    };
+    let mut jd0 = jd_start;
+    let (mut d0, mut s0) = sample(body, x2cross, jd0, iflag)?;
+    while (forward && jd0 < jd_end) || (!forward && jd0 > jd_end) {
+        let jd1 = if forward {
+            (jd0 + step).min(jd_end)
+        } else {
+            (jd0 + step).max(jd_end)
+        };
+        let (d1, s1) = sample(body, x2cross, jd1, iflag)?;
+        let mut brackets = [Some((jd0, d0, jd1, d1)), None];
+        if s0 * s1 < 0.0 {
+            // the body turns within the step, it may reach x2cross before
+            // the station and return to it after
+            let station = if forward {
+                refine_station(body, jd0, s0, jd1, iflag)?
+            } else {
+                refine_station(body, jd1, s1, jd0, iflag)?
+            };
+            let jds = station.julian_day_ut;
+            let ds = distance(body, x2cross, jds, iflag)?;
+            brackets = [Some((jd0, d0, jds, ds)), Some((jds, ds, jd1, d1))];
+        }
+        for (ja, da, jb, db) in brackets.iter().flatten().copied() {
+            // a sign change far from x2cross is the opposite point
+            if da * db <= 0.0 && da.abs() < 90.0 && db.abs() < 90.0 && da != 0.0
+            {
+                let crossing = refine(body, x2cross, ja, da, jb, iflag)?;
+                if !on_crossing(crossing) {
+                    return Ok(());
+                }
+            }
+        }
+        jd0 = jd1;
+        d0 = d1;
+        s0 = s1;
+    }
+    Ok(())
+}
+
+/// Bisection between two bracketing times
+pub(crate) fn refine(
+    body: Bodies,
+    x2cross: f64,
+    mut jd0: f64,
+    mut d0: f64,
+    mut jd1: f64,
+    iflag: CalculationFlags,
+) -> Result<Crossing, SweError> {
+    while (jd1 - jd0).abs() > CROSS_PRECISION_DAYS {
+        let jd = (jd0 + jd1) / 2.0;
+        let d = distance(body, x2cross, jd, iflag)?;
+        if d0 * d <= 0.0 {
+            jd1 = jd;
+        } else {
+            jd0 = jd;
+            d0 = d;
+        }
+    }
+    let result = calc_ut(jd1, body, iflag)?;
+    Ok(Crossing {
+        julian_day_ut: jd1,
+        longitude: result.longitude,
+        speed_longitude: result.speed_longitude,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ensure_ephemeris_initialized,
+        sweconst::Calendar,
+        swerust::handler_swe08::{utc_time_zone, utc_to_jd},
+    };
+
+    fn jd(year: i32, month: i32, day: i32) -> f64 {
+        let utc = utc_time_zone(year, month, day, 0, 0, 0.0, 0.0);
+        utc_to_jd(
+            utc.year[0],
+            utc.month[0],
+            utc.day[0],
+            utc.hour[0],
+            utc.min[0],
+            utc.sec[0],
+            Calendar::Gregorian,
+        )
+        .unwrap()
+        .julian_day_ut
+    }
+
+    #[test]
+    pub fn test_solcross_ut_march_equinox() {
+        let _ = ensure_ephemeris_initialized();
+        let flag = CalculationFlags::SWISS_EPHEMERIS;
+        let equinox = solcross_ut(0.0, jd(2025, 1, 1), flag).unwrap();
+        assert!((equinox - 2460754.8760315767).abs() < 1e-6);
+
+        let generic = next_crossing(Bodies::Sun, 0.0, jd(2025, 1, 1), flag);
+        assert!((generic.unwrap().julian_day_ut - equinox).abs() < 1e-6);
+    }
+
+    #[test]
+    pub fn test_mooncross_ut_matches_generic_search() {
+        let _ = ensure_ephemeris_initialized();
+        let flag = CalculationFlags::SWISS_EPHEMERIS;
+        let start = jd(2024, 11, 21);
+        let moon = mooncross_ut(120.0, start, flag).unwrap();
+        let generic = next_crossing(Bodies::Moon, 120.0, start, flag).unwrap();
+
+        assert!(moon > start && moon < start + 28.0);
+        assert!((generic.julian_day_ut - moon).abs() < 1e-6);
+        assert!(!generic.is_retrograde());
+    }
+
+    #[test]
+    pub fn test_mooncross_node_ut() {
+        let _ = ensure_ephemeris_initialized();
+        let start = jd(2024, 11, 21);
+        let node = mooncross_node_ut(start, CalculationFlags::SWISS_EPHEMERIS)
+            .unwrap();
+
+        assert!(
+            node.julian_day_ut > start && node.julian_day_ut < start + 14.0
+        );
+        assert!(node.latitude.abs() < 1e-6);
+    }
+
+    #[test]
+    pub fn test_helio_cross_ut() {
+        let _ = ensure_ephemeris_initialized();
+        let flag = CalculationFlags::SWISS_EPHEMERIS;
+        let start = jd(2024, 11, 21);
+        let next =
+            helio_cross_ut(Bodies::Mars, 180.0, start, flag, false).unwrap();
+        let previous =
+            helio_cross_ut(Bodies::Mars, 180.0, start, flag, true).unwrap();
+
+        assert!(next > start && previous < start);
+        // one revolution of Mars
+        assert!((next - previous - 687.0).abs() < 5.0);
+        assert!(helio_cross_ut(Bodies::Moon, 0.0, start, flag, false).is_err());
+    }
+
+    #[test]
+    pub fn test_mars_enters_leo_three_times() {
+        let _ = ensure_ephemeris_initialized();
+        let crossings = crossings_between(
+            Bodies::Mars,
+            120.0,
+            jd(2024, 10, 1),
+            jd(2025, 6, 1),
+            CalculationFlags::SWISS_EPHEMERIS,
+        )
+        .unwrap();
+
+        assert_eq!(crossings.len(), 3);
+        let expected =
+            [2460618.673442382, 2460681.94729815, 2460783.6811317834];
+        for (crossing, expected) in crossings.iter().zip(expected) {
+            assert!((crossing.julian_day_ut - expected).abs() < 1e-6);
+        }
+        assert!(!crossings[0].is_retrograde());
+        assert!(crossings[1].is_retrograde());
+        assert!(!crossings[2].is_retrograde());
+        for crossing in crossings {
+            assert!((crossing.longitude - 120.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    pub fn test_crossings_around_station_within_one_step() {
+        let _ = ensure_ephemeris_initialized();
+        let flag = CalculationFlags::SWISS_EPHEMERIS;
+        let station = crate::stations::next_station(
+            Bodies::Jupiter,
+            jd(2024, 9, 1),
+            flag,
+        )
+        .unwrap();
+        // Jupiter needs about a day to move 0.002° from its station, which is
+        // in the middle of a step of the search
+        let x2cross = station.longitude - 0.002;
+        let step = search_step(Bodies::Jupiter);
+        let crossings = crossings_between(
+            Bodies::Jupiter,
+            x2cross,
+            station.julian_day_ut - 6.5 * step,
+            station.julian_day_ut + 6.5 * step,
+            flag,
+        )
+        .unwrap();
+
+        assert_eq!(crossings.len(), 2);
+        let span = crossings[1].julian_day_ut - crossings[0].julian_day_ut;
+        assert!(span < step / 2.0);
+        assert!(crossings[0].julian_day_ut < station.julian_day_ut);
+        assert!(crossings[1].julian_day_ut > station.julian_day_ut);
+        assert!(!crossings[0].is_retrograde());
+        assert!(crossings[1].is_retrograde());
+        for crossing in crossings {
+            assert!((crossing.longitude - x2cross).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    pub fn test_previous_crossing() {
+        let _ = ensure_ephemeris_initialized();
+        let flag = CalculationFlags::SWISS_EPHEMERIS;
+        let start = jd(2025, 6, 1);
+        let previous =
+            previous_crossing(Bodies::Mars, 120.0, start, flag).unwrap();
+        let forward =
+            crossings_between(Bodies::Mars, 120.0, jd(2025, 3, 1), start, flag)
+                .unwrap();
+
+        assert_eq!(forward.len(), 1);
+        assert!(
+            (previous.julian_day_ut - forward[0].julian_day_ut).abs() < 1e-6
+        );
+    }
+}
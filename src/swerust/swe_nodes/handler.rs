@@ -1,6 +1,10 @@
-use std::ffi::{CStr, CString};
-
-use crate::{raw::swe_nod_aps_ut, sweconst::Bodies};
+use crate::constants::CalculationFlags;
+use crate::error::{check_flags, serr_message, warning, SweError, SweWarning};
+use crate::raw::{
+    swe_get_orbital_elements, swe_nod_aps_ut, swe_orbit_max_min_true_distance,
+};
+use crate::sweconst::Bodies;
+use crate::swerust::handler_swe08::deltat_ex;
 
 /// Mean Nodes are available only for planets Moon, Mercury to Neptune.
 /// Pluto and Asteroids always return True Nodes, even if Means nodes are selected.
@@ -31,6 +35,7 @@ pub struct ApsidesAndNodesResult {
     pub south_node: CelestialPosition, // Where planet's orbit crosses the ecliptic going north
     pub perihelion: CelestialPosition, // Closest point to the Sun
     pub aphelion: CelestialPosition,   // Farthest point from the Sun
+    pub warning: Option<SweWarning>,
 }
 
 /// Calculates Planetary Nodes and Apsides: perihelia, aphelia, second focal points of the orbital ellipses.
@@ -46,37 +51,30 @@ pub struct ApsidesAndNodesResult {
 pub fn get_planetary_apsides_and_nodes(
     body: Bodies,
     jd: f64,
-    iflag: i32,
+    iflag: CalculationFlags,
     method: i32,
-) -> Result<ApsidesAndNodesResult, String> {
+) -> Result<ApsidesAndNodesResult, SweError> {
+    iflag.validate()?;
     let mut xnasc = [0.0; 6]; // North Node
     let mut xndsc = [0.0; 6]; // South Node
     let mut xperi = [0.0; 6]; // Perihelion
     let mut xaphe = [0.0; 6]; // Aphelion
-    let mut serr = [0; 255]; // Error buffer
-    let p_serr = serr.as_mut_ptr();
+    let mut serr = [0; 256]; // Error buffer
 
-    unsafe {
+    let status = unsafe {
         swe_nod_aps_ut(
             jd,
             body as i32,
-            iflag,
+            iflag.bits(),
             method,
             xnasc.as_mut_ptr(),
             xndsc.as_mut_ptr(),
             xperi.as_mut_ptr(),
             xaphe.as_mut_ptr(),
-            p_serr,
-        );
-
-        let s_serr = CString::from(CStr::from_ptr(p_serr))
-            .to_str()
-            .unwrap()
-            .to_string();
-        if !s_serr.is_empty() {
-            return Err(s_serr);
-        }
-    }
+            serr.as_mut_ptr(),
+        )
+    };
+    let warning = check_flags(iflag, status, &serr)?;
 
     Ok(ApsidesAndNodesResult {
         north_node: CelestialPosition {
@@ -112,8 +110,136 @@ pub fn get_planetary_apsides_and_nodes(
             radial_speed: xaphe[5],
         },
         body,
+        warning,
+    })
+}
+
+/// Osculating Kepler elements of the orbit of a body, heliocentric (geocentric
+/// for the Moon) unless CalculationFlags::BARYCENTRIC_POSITIONS is given.
+/// Angles are in degrees, for the ecliptic and equinox of date (J2000 with
+/// CalculationFlags::NO_PROCESSION_J2000).
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitalElements {
+    pub body: Bodies,
+    pub semi_major_axis: f64, // AU
+    pub eccentricity: f64,
+    pub inclination: f64,
+    pub ascending_node: f64, // Longitude of the ascending node
+    pub argument_of_perihelion: f64, // From the ascending node
+    pub longitude_of_perihelion: f64, // Node plus argument of perihelion
+    pub mean_anomaly: f64,
+    pub true_anomaly: f64,
+    pub eccentric_anomaly: f64,
+    pub mean_longitude: f64,
+    pub sidereal_period: f64,     // Tropical years
+    pub daily_motion: f64,        // Mean motion (deg/day)
+    pub tropical_period: f64,     // Tropical years
+    pub synodic_period: f64,      // Days
+    pub perihelion_passage: f64,  // Julian day (ET)
+    pub perihelion_distance: f64, // AU
+    pub aphelion_distance: f64,   // AU
+    pub distances: OrbitDistances,
+    pub warning: Option<SweWarning>,
+}
+
+/// Extreme distances of a body on its osculating ellipse, and the current
+/// one. Geocentric, from the ellipses of the body and of the Earth-Moon
+/// barycenter, unless CalculationFlags::HELIOCENTRIC_POSITIONS is given.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitDistances {
+    pub max: f64,           // AU
+    pub min: f64,           // AU
+    pub true_distance: f64, // AU
+}
+
+/// Orbital elements of a body at a julian day (UT), with its extreme
+/// distances
+pub fn get_orbital_elements(
+    body: Bodies,
+    jd: f64,
+    iflag: CalculationFlags,
+) -> Result<OrbitalElements, SweError> {
+    iflag.validate()?;
+    let tjd_et = jd + deltat_ex(jd, iflag)?.days;
+    let mut dret = [0.0; 50];
+    let mut serr = [0; 256];
+
+    let status = unsafe {
+        swe_get_orbital_elements(
+            tjd_et,
+            body as i32,
+            iflag.bits(),
+            dret.as_mut_ptr(),
+            serr.as_mut_ptr(),
+        )
+    };
+    let message = serr_message(&serr)?;
+    if status < 0 {
+        return Err(SweError::from_status(status, &message));
+    }
+
+    Ok(OrbitalElements {
+        body,
+        semi_major_axis: dret[0],
+        eccentricity: dret[1],
+        inclination: dret[2],
+        ascending_node: dret[3],
+        argument_of_perihelion: dret[4],
+        longitude_of_perihelion: dret[5],
+        mean_anomaly: dret[6],
+        true_anomaly: dret[7],
+        eccentric_anomaly: dret[8],
+        mean_longitude: dret[9],
+        sidereal_period: dret[10],
+        daily_motion: dret[11],
+        tropical_period: dret[12],
+        synodic_period: dret[13],
+        perihelion_passage: dret[14],
+        perihelion_distance: dret[15],
+        aphelion_distance: dret[16],
+        distances: orbit_distances_et(body, tjd_et, iflag)?,
+        warning: warning(iflag, iflag, &message),
     })
 }
+
+/// Maximum, minimum and current distances of a body at a julian day (UT)
+pub fn get_orbit_distances(
+    body: Bodies,
+    jd: f64,
+    iflag: CalculationFlags,
+) -> Result<OrbitDistances, SweError> {
+    iflag.validate()?;
+    orbit_distances_et(body, jd + deltat_ex(jd, iflag)?.days, iflag)
+}
+
+fn orbit_distances_et(
+    body: Bodies,
+    tjd_et: f64,
+    iflag: CalculationFlags,
+) -> Result<OrbitDistances, SweError> {
+    let mut distances = OrbitDistances {
+        max: 0.0,
+        min: 0.0,
+        true_distance: 0.0,
+    };
+    let mut serr = [0; 256];
+    let status = unsafe {
+        swe_orbit_max_min_true_distance(
+            tjd_et,
+            body as i32,
+            iflag.bits(),
+            &mut distances.max,
+            &mut distances.min,
+            &mut distances.true_distance,
+            serr.as_mut_ptr(),
+        )
+    };
+    if status < 0 {
+        return Err(SweError::from_status(status, &serr_message(&serr)?));
+    }
+    Ok(distances)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,6 +289,7 @@ mod tests {
                 latitude_speed: 0.0,
                 radial_speed: 0.0,
             },
+            warning: None,
         };
 
         let iflag = CalculationFlags::SWISS_EPHEMERIS;
@@ -221,10 +348,11 @@ mod tests {
                 latitude_speed: 0.0012260583942533664,
                 radial_speed: 0.0040172833176065625,
             },
+            warning: None,
         };
 
         let iflag = CalculationFlags::SWISS_EPHEMERIS
-            + CalculationFlags::SPEED_PRECISION;
+            | CalculationFlags::SPEED_PRECISION;
 
         let actual_result = get_planetary_apsides_and_nodes(
             Bodies::Pluto,
@@ -240,6 +368,53 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn test_get_orbital_elements() {
+        let _ = ensure_ephemeris_initialized();
+        let jd = get_test_julian_time();
+        let mars = get_orbital_elements(
+            Bodies::Mars,
+            jd,
+            CalculationFlags::SWISS_EPHEMERIS,
+        )
+        .unwrap();
+
+        assert!((mars.semi_major_axis - 1.5237).abs() < 1e-3);
+        assert!((mars.eccentricity - 0.0934).abs() < 1e-3);
+        assert!((mars.inclination - 1.85).abs() < 0.01);
+        assert!((mars.ascending_node - 49.5).abs() < 0.5);
+        assert!((mars.sidereal_period - 1.881).abs() < 1e-2);
+        assert!((mars.synodic_period - 780.0).abs() < 5.0);
+        let perihelion = mars.semi_major_axis * (1.0 - mars.eccentricity);
+        assert!((mars.perihelion_distance - perihelion).abs() < 1e-9);
+        // the mean anomaly is the mean motion since the perihelion passage
+        let since = mars.mean_anomaly / mars.daily_motion;
+        assert!((mars.perihelion_passage + since - jd).abs() < 0.01);
+        // geocentric distances, Mars at opposition and at conjunction
+        let distances = mars.distances;
+        assert!(distances.min > 0.35 && distances.min < 0.45);
+        assert!(distances.max > 2.5 && distances.max < 2.7);
+        assert!(distances.true_distance > distances.min);
+        assert!(distances.true_distance < distances.max);
+
+        let heliocentric = get_orbit_distances(
+            Bodies::Mars,
+            jd,
+            CalculationFlags::SWISS_EPHEMERIS
+                | CalculationFlags::HELIOCENTRIC_POSITIONS,
+        )
+        .unwrap();
+        assert!((heliocentric.max - mars.aphelion_distance).abs() < 1e-6);
+        assert!((heliocentric.min - mars.perihelion_distance).abs() < 1e-6);
+
+        assert!(get_orbital_elements(
+            Bodies::EclNut,
+            jd,
+            CalculationFlags::SWISS_EPHEMERIS
+        )
+        .is_err());
+    }
+
     pub fn get_test_julian_time() -> f64 {
         let utc_time_zone = utc_time_zone(2024, 11, 21, 16, 10, 0.0, 2.0);
         let jd = utc_to_jd(
@@ -250,7 +425,8 @@ mod tests {
             utc_time_zone.min[0],
             utc_time_zone.sec[0],
             Calendar::Gregorian,
-        );
+        )
+        .unwrap();
         jd.julian_day_ut
     }
 
@@ -0,0 +1,478 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use crate::sweconst::Observer;
+
+/*
+ * Elevation of a geographic position
+ *
+ * An ElevationProvider returns the height above sea level in meters, used as
+ * the altitude of an Observer for rise_trans and topocentric positions.
+ *
+ * Two providers read data from disk and work offline:
+ * - AsciiGrid, a gridded file in the ESRI ASCII format (.asc), loaded in
+ *   memory. Any raster (GeoTIFF included) can be converted to it, e.g. with
+ *   gdal_translate -of AAIGrid.
+ * - SrtmDirectory, a directory of SRTM tiles (N43E025.hgt), loaded when
+ *   first used.
+ * Both interpolate bilinearly between the samples around the position.
+ *
+ * OpenElevation queries api.open-elevation.com, behind the cargo feature
+ * "http-elevation".
+ */
+
+/// Value of a void in the SRTM tiles
+const SRTM_VOID: i16 = -32768;
+
+#[derive(Debug, Error)]
+pub enum ElevationError {
+    #[error("Failed to read {0:?}: {1}")]
+    ReadFailed(PathBuf, #[source] std::io::Error),
+
+    #[error("Invalid elevation data: {0}")]
+    InvalidData(String),
+
+    #[error("No elevation data for latitude {0}, longitude {1}")]
+    OutOfCoverage(f64, f64),
+
+    #[error("Elevation service error: {0}")]
+    Service(String),
+}
+
+pub trait ElevationProvider {
+    /// Height above sea level in meters
+    fn elevation(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<f64, ElevationError>;
+
+    /// Observer at a geographic position, at the height of the ground
+    fn observer(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<Observer, ElevationError> {
+        let altitude = self.elevation(latitude, longitude)?;
+        Ok(Observer::new(latitude, longitude, altitude))
+    }
+}
+
+/// Samples on a regular grid in degrees, row 0 is the northern one
+#[derive(Debug, Clone, PartialEq)]
+struct Grid {
+    north: f64, // latitude of the first row
+    west: f64,  // longitude of the first column
+    step: f64,
+    rows: usize,
+    cols: usize,
+    values: Vec<Option<f64>>,
+}
+
+impl Grid {
+    /// Bilinear interpolation, ignoring the missing samples. Positions up
+    /// to half a step outside the samples take the value of the border.
+    fn interpolate(&self, latitude: f64, longitude: f64) -> Option<f64> {
+        let y = (self.north - latitude) / self.step;
+        let x = (longitude - self.west) / self.step;
+        let last_row = (self.rows - 1) as f64;
+        let last_col = (self.cols - 1) as f64;
+        if !(-0.5..=last_row + 0.5).contains(&y)
+            || !(-0.5..=last_col + 0.5).contains(&x)
+        {
+            return None;
+        }
+        let y = y.clamp(0.0, last_row);
+        let x = x.clamp(0.0, last_col);
+        let row = (y.floor() as usize).min(self.rows.saturating_sub(2));
+        let col = (x.floor() as usize).min(self.cols.saturating_sub(2));
+        let dy = y - row as f64;
+        let dx = x - col as f64;
+
+        let mut sum = 0.0;
+        let mut weights = 0.0;
+        for (r, wy) in [(row, 1.0 - dy), (row + 1, dy)] {
+            for (c, wx) in [(col, 1.0 - dx), (col + 1, dx)] {
+                if r >= self.rows || c >= self.cols {
+                    continue;
+                }
+                if let Some(value) = self.values[r * self.cols + c] {
+                    sum += value * wy * wx;
+                    weights += wy * wx;
+                }
+            }
+        }
+        if weights > 0.0 {
+            Some(sum / weights)
+        } else {
+            None
+        }
+    }
+}
+
+/// Gridded elevation file in the ESRI ASCII format
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciiGrid {
+    grid: Grid,
+}
+
+impl AsciiGrid {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<AsciiGrid, ElevationError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| ElevationError::ReadFailed(path.to_path_buf(), e))?;
+        content.parse()
+    }
+}
+
+impl std::str::FromStr for AsciiGrid {
+    type Err = ElevationError;
+
+    fn from_str(content: &str) -> Result<AsciiGrid, ElevationError> {
+        let invalid = |message: &str| {
+            ElevationError::InvalidData(format!("ASCII grid, {}", message))
+        };
+        let mut header = HashMap::new();
+        let mut tokens = content.split_whitespace().peekable();
+        while let Some(key) = tokens.peek() {
+            if key.parse::<f64>().is_ok() {
+                break;
+            }
+            let key = key.to_lowercase();
+            tokens.next();
+            let value = tokens
+                .next()
+                .and_then(|v| v.parse::<f64>().ok())
+                .ok_or_else(|| invalid(&format!("no value for {}", key)))?;
+            header.insert(key, value);
+        }
+        let get = |key: &str| {
+            header
+                .get(key)
+                .copied()
+                .ok_or_else(|| invalid(&format!("missing {}", key)))
+        };
+        let cols = get("ncols")? as usize;
+        let rows = get("nrows")? as usize;
+        let step = get("cellsize")?;
+        if cols == 0 || rows == 0 || step <= 0.0 {
+            return Err(invalid("empty grid"));
+        }
+        // coordinates of the center of the south-western cell
+        let (west, south) =
+            match (header.get("xllcenter"), header.get("yllcenter")) {
+                (Some(&x), Some(&y)) => (x, y),
+                _ => (
+                    get("xllcorner")? + step / 2.0,
+                    get("yllcorner")? + step / 2.0,
+                ),
+            };
+        let nodata = header.get("nodata_value").copied();
+
+        let values = tokens
+            .map(|token| {
+                let value = token.parse::<f64>().map_err(|_| {
+                    invalid(&format!("invalid value {}", token))
+                })?;
+                Ok(if Some(value) == nodata {
+                    None
+                } else {
+                    Some(value)
+                })
+            })
+            .collect::<Result<Vec<_>, ElevationError>>()?;
+        if values.len() != rows * cols {
+            return Err(invalid(&format!(
+                "{} values for {} rows and {} columns",
+                values.len(),
+                rows,
+                cols
+            )));
+        }
+        Ok(AsciiGrid {
+            grid: Grid {
+                north: south + (rows - 1) as f64 * step,
+                west,
+                step,
+                rows,
+                cols,
+                values,
+            },
+        })
+    }
+}
+
+impl ElevationProvider for AsciiGrid {
+    fn elevation(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<f64, ElevationError> {
+        self.grid
+            .interpolate(latitude, longitude)
+            .ok_or(ElevationError::OutOfCoverage(latitude, longitude))
+    }
+}
+
+/// Directory of SRTM tiles of one degree (SRTM1 or SRTM3), named after
+/// their south-western corner: N43E025.hgt
+#[derive(Debug)]
+pub struct SrtmDirectory {
+    path: PathBuf,
+    tiles: Mutex<HashMap<(i32, i32), Arc<Grid>>>,
+}
+
+impl SrtmDirectory {
+    pub fn new<P: AsRef<Path>>(path: P) -> SrtmDirectory {
+        SrtmDirectory {
+            path: path.as_ref().to_path_buf(),
+            tiles: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// File name of the tile of a position
+    pub fn tile_name(latitude: f64, longitude: f64) -> String {
+        let lat = latitude.floor() as i32;
+        let lng = longitude.floor() as i32;
+        format!(
+            "{}{:02}{}{:03}.hgt",
+            if lat < 0 { 'S' } else { 'N' },
+            lat.abs(),
+            if lng < 0 { 'W' } else { 'E' },
+            lng.abs()
+        )
+    }
+
+    fn tile(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<Arc<Grid>, ElevationError> {
+        let key = (latitude.floor() as i32, longitude.floor() as i32);
+        let mut tiles = self.tiles.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(tile) = tiles.get(&key) {
+            return Ok(tile.clone());
+        }
+        let path = self
+            .path
+            .join(SrtmDirectory::tile_name(latitude, longitude));
+        if !path.exists() {
+            return Err(ElevationError::OutOfCoverage(latitude, longitude));
+        }
+        let bytes = fs::read(&path)
+            .map_err(|e| ElevationError::ReadFailed(path.clone(), e))?;
+        let tile =
+            Arc::new(srtm_grid(&bytes, key.0, key.1).ok_or_else(|| {
+                ElevationError::InvalidData(format!(
+                    "{:?} is not a SRTM tile",
+                    path
+                ))
+            })?);
+        tiles.insert(key, tile.clone());
+        Ok(tile)
+    }
+}
+
+/// Samples of a SRTM tile: big-endian i16 in rows from north to south,
+/// including the borders shared with the neighbouring tiles
+fn srtm_grid(bytes: &[u8], south: i32, west: i32) -> Option<Grid> {
+    let size = ((bytes.len() / 2) as f64).sqrt() as usize;
+    if size < 2 || size * size * 2 != bytes.len() {
+        return None;
+    }
+    let values = bytes
+        .chunks_exact(2)
+        .map(|b| {
+            let value = i16::from_be_bytes([b[0], b[1]]);
+            if value == SRTM_VOID {
+                None
+            } else {
+                Some(value as f64)
+            }
+        })
+        .collect();
+    Some(Grid {
+        north: (south + 1) as f64,
+        west: west as f64,
+        step: 1.0 / (size - 1) as f64,
+        rows: size,
+        cols: size,
+        values,
+    })
+}
+
+impl ElevationProvider for SrtmDirectory {
+    fn elevation(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<f64, ElevationError> {
+        if !(-90.0..90.0).contains(&latitude)
+            || !(-180.0..180.0).contains(&longitude)
+        {
+            return Err(ElevationError::OutOfCoverage(latitude, longitude));
+        }
+        self.tile(latitude, longitude)?
+            .interpolate(latitude, longitude)
+            .ok_or(ElevationError::OutOfCoverage(latitude, longitude))
+    }
+}
+
+/// Elevation service of api.open-elevation.com, one blocking request per
+/// position
+#[cfg(feature = "http-elevation")]
+#[derive(Debug, Clone)]
+pub struct OpenElevation {
+    pub url: String,
+}
+
+#[cfg(feature = "http-elevation")]
+impl Default for OpenElevation {
+    fn default() -> Self {
+        OpenElevation {
+            url: "https://api.open-elevation.com/api/v1/lookup".to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "http-elevation")]
+impl ElevationProvider for OpenElevation {
+    fn elevation(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<f64, ElevationError> {
+        let url = format!("{}?locations={},{}", self.url, latitude, longitude);
+        let response = reqwest::blocking::get(&url)
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.json::<serde_json::Value>())
+            .map_err(|e| ElevationError::Service(e.to_string()))?;
+        response["results"][0]["elevation"].as_f64().ok_or_else(|| {
+            ElevationError::Service(format!(
+                "no elevation in the response {}",
+                response
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const GRID: &str = "ncols 3
+nrows 2
+xllcorner 25.0
+yllcorner 43.0
+cellsize 0.5
+NODATA_value -9999
+100 200 -9999
+300 400 500
+";
+
+    #[test]
+    fn test_ascii_grid() {
+        let grid: AsciiGrid = GRID.parse().unwrap();
+
+        // cell centers
+        assert_eq!(grid.elevation(43.25, 25.25).unwrap(), 300.0);
+        assert_eq!(grid.elevation(43.75, 25.75).unwrap(), 200.0);
+        // between four cells, the missing one is ignored
+        let middle = grid.elevation(43.5, 26.0).unwrap();
+        assert!((middle - (200.0 + 400.0 + 500.0) / 3.0).abs() < 1e-9);
+        assert!((grid.elevation(43.5, 25.5).unwrap() - 250.0).abs() < 1e-9);
+        // up to the border of the grid
+        assert_eq!(grid.elevation(43.99, 25.01).unwrap(), 100.0);
+        assert!(matches!(
+            grid.elevation(44.5, 25.5),
+            Err(ElevationError::OutOfCoverage(_, _))
+        ));
+
+        let observer = grid.observer(43.25, 25.25).unwrap();
+        assert_eq!(observer.altitude, 300.0);
+        assert!("ncols 3\nnrows 2\ncellsize 0.5\n1 2 3"
+            .parse::<AsciiGrid>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_srtm_directory() {
+        let dir = tempdir().unwrap();
+        // 3x3 samples, 0.5 degree apart, void in the south-eastern corner
+        let samples: [i16; 9] =
+            [100, 200, 300, 400, 500, 600, 700, 800, -32768];
+        let bytes: Vec<u8> =
+            samples.iter().flat_map(|v| v.to_be_bytes()).collect();
+        fs::write(dir.path().join("N43E025.hgt"), bytes).unwrap();
+        fs::write(dir.path().join("N44E025.hgt"), [0u8; 5]).unwrap();
+
+        assert_eq!(SrtmDirectory::tile_name(43.08, 25.59), "N43E025.hgt");
+        assert_eq!(SrtmDirectory::tile_name(-33.9, -70.6), "S34W071.hgt");
+
+        let srtm = SrtmDirectory::new(dir.path());
+        assert!(
+            (srtm.elevation(44.0 - 1e-12, 25.0).unwrap() - 100.0).abs() < 1e-6
+        );
+        assert_eq!(srtm.elevation(43.5, 25.5).unwrap(), 500.0);
+        let value = srtm.elevation(43.25, 25.25).unwrap();
+        assert!((value - (400.0 + 500.0 + 700.0 + 800.0) / 4.0).abs() < 1e-9);
+        assert!(
+            (srtm.elevation(43.0, 26.0 - 1e-12).unwrap() - 800.0).abs() < 1e-6
+        );
+        assert!(matches!(
+            srtm.elevation(44.5, 25.5),
+            Err(ElevationError::InvalidData(_))
+        ));
+        assert!(matches!(
+            srtm.elevation(43.5, 27.5),
+            Err(ElevationError::OutOfCoverage(_, _))
+        ));
+    }
+
+    #[cfg(feature = "http-elevation")]
+    #[test]
+    pub fn test_get_altitude() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        // local stub of the lookup service, answering a single request
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            let body = r#"{"results":[{"latitude":43.084128,"longitude":25.5919228,"elevation":265.0}]}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            String::from_utf8_lossy(&request).into_owned()
+        });
+
+        let api = OpenElevation {
+            url: format!("http://{}/api/v1/lookup", address),
+        };
+        let result = api.elevation(43.084128, 25.5919228);
+        let request = server.join().unwrap();
+
+        assert!(request
+            .starts_with("GET /api/v1/lookup?locations=43.084128,25.5919228 "));
+        assert_eq!(result.unwrap(), 265.0);
+    }
+}
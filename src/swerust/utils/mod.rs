@@ -1 +1 @@
-pub mod calculate_altitude;
+pub mod elevation;
@@ -0,0 +1,520 @@
+use std::collections::VecDeque;
+
+use crate::constants::CalculationFlags;
+use crate::error::SweError;
+use crate::sweconst::{
+    Angle, Aspects, AspectsFilter, Bodies, House, Object, ObjectPos,
+};
+use crate::swerust::handler_crossings::{distance, refine, search_step};
+use crate::swerust::handler_swe03::calc_ut;
+use crate::swerust::handler_swe17::degnorm;
+
+/*
+ * Transits
+ *
+ * Search of the exact aspects formed by transiting bodies to the points of a
+ * natal chart over a date range. Every aspect of the filter gives one or two
+ * target longitudes per natal point (natal ± aspect angle). The longitude of
+ * each transiting body is sampled once per chunk of the range and every
+ * target is checked against the samples:
+ *
+ * - a change of sign of the distance to the target is an exact hit, refined
+ *   by bisection; a retrograde loop over the target gives three hits;
+ * - a minimum of the distance without a change of sign is a station close to
+ *   the target, reported as a hit when it is closer than STATION_TOLERANCE.
+ *
+ * Each hit carries the time the body entered the orb of the aspect (applying)
+ * and the time it leaves it (separating), with the orbs of Aspects::angle.
+ */
+
+/// Length of the part of the date range searched at once
+const CHUNK_DAYS: f64 = 30.0;
+/// Largest distance to a target for a station to count as a hit (1')
+const STATION_TOLERANCE: f64 = 1.0 / 60.0;
+/// Longest time searched for the orb boundaries of a hit
+const MAX_WINDOW_DAYS: f64 = 20.0 * 365.25;
+
+/// Point of the natal chart aspected by a transiting body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NatalPoint {
+    Body {
+        body: Bodies,
+        longitude: f64,
+    },
+    House {
+        number: i32,
+        angle: Angle,
+        longitude: f64,
+    },
+}
+
+impl NatalPoint {
+    pub fn longitude(&self) -> f64 {
+        match *self {
+            NatalPoint::Body { longitude, .. } => longitude,
+            NatalPoint::House { longitude, .. } => longitude,
+        }
+    }
+}
+
+impl From<&Object> for NatalPoint {
+    fn from(object: &Object) -> Self {
+        NatalPoint::Body {
+            body: object.object_enum,
+            longitude: object.longitude,
+        }
+    }
+}
+
+impl From<&House> for NatalPoint {
+    fn from(house: &House) -> Self {
+        NatalPoint::House {
+            number: house.object_id,
+            angle: house.angle,
+            longitude: house.longitude,
+        }
+    }
+}
+
+/// Exact aspect of a transiting body to a natal point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransitEvent {
+    pub julian_day_ut: f64,
+    pub transiting: Bodies,
+    pub natal: NatalPoint,
+    pub aspect: Aspects,
+    pub motion: ObjectPos,
+    /// Longitude of the transiting body
+    pub longitude: f64,
+    pub speed_longitude: f64,
+    /// Distance left at the hit, 0 unless the hit is a station
+    pub orb: f64,
+    /// Entry of the transiting body into the orb of the aspect
+    pub applying_since: Option<f64>,
+    /// Exit of the transiting body from the orb of the aspect
+    pub separating_until: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Target {
+    natal: NatalPoint,
+    aspect: Aspects,
+    longitude: f64,
+}
+
+/// Iterator over the transits of a date range, in chronological order
+pub struct TransitIter {
+    bodies: Vec<Bodies>,
+    targets: Vec<Target>,
+    iflag: CalculationFlags,
+    chunk_start: f64,
+    jd_end: f64,
+    pending: VecDeque<TransitEvent>,
+    done: bool,
+}
+
+/// Exact aspects of `bodies` to `natal` between `jd_start` and `jd_end` (UT)
+pub fn transits(
+    bodies: &[Bodies],
+    natal: &[NatalPoint],
+    filter: AspectsFilter,
+    jd_start: f64,
+    jd_end: f64,
+    iflag: CalculationFlags,
+) -> TransitIter {
+    let mut targets = Vec::new();
+    for point in natal {
+        for aspect in filter.vec_aspects() {
+            let angle = aspect.angle().0 as f64;
+            let first = degnorm(point.longitude() + angle);
+            let second = degnorm(point.longitude() - angle);
+            targets.push(Target {
+                natal: *point,
+                aspect,
+                longitude: first,
+            });
+            if (first - second).abs() > 1e-9 {
+                targets.push(Target {
+                    natal: *point,
+                    aspect,
+                    longitude: second,
+                });
+            }
+        }
+    }
+    TransitIter {
+        bodies: bodies.to_vec(),
+        targets,
+        iflag: iflag | CalculationFlags::SPEED_PRECISION,
+        chunk_start: jd_start,
+        jd_end,
+        pending: VecDeque::new(),
+        done: false,
+    }
+}
+
+impl Iterator for TransitIter {
+    type Item = Result<TransitEvent, SweError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.done || self.chunk_start >= self.jd_end {
+                return None;
+            }
+            let chunk_end = (self.chunk_start + CHUNK_DAYS).min(self.jd_end);
+            match self.search_chunk(self.chunk_start, chunk_end) {
+                Ok(events) => {
+                    self.pending.extend(events);
+                    self.chunk_start = chunk_end;
+                },
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                },
+            }
+        }
+    }
+}
+
+impl TransitIter {
+    /// Hits in [jd_start, jd_end), sorted by time
+    fn search_chunk(
+        &self,
+        jd_start: f64,
+        jd_end: f64,
+    ) -> Result<Vec<TransitEvent>, SweError> {
+        let mut events = Vec::new();
+        for &body in &self.bodies {
+            let step = search_step(body);
+            // one sample on each side so that hits close to the edges of
+            // the chunk are bracketed
+            let mut samples = Vec::new();
+            let mut jd = jd_start - step;
+            while jd <= jd_end + step {
+                let result = calc_ut(jd, body, self.iflag)?;
+                samples.push((jd, result.longitude));
+                jd += step;
+            }
+            for target in &self.targets {
+                let d: Vec<f64> = samples
+                    .iter()
+                    .map(|&(_, lon)| signed_distance(lon, target.longitude))
+                    .collect();
+                for i in 0..samples.len() - 1 {
+                    let (jd0, jd1) = (samples[i].0, samples[i + 1].0);
+                    let near = d[i].abs() < 90.0 && d[i + 1].abs() < 90.0;
+                    if near && d[i] != 0.0 && d[i] * d[i + 1] <= 0.0 {
+                        let crossing = refine(
+                            body,
+                            target.longitude,
+                            jd0,
+                            d[i],
+                            jd1,
+                            self.iflag,
+                        )?;
+                        if crossing.julian_day_ut >= jd_start
+                            && crossing.julian_day_ut < jd_end
+                        {
+                            events.push(self.event(
+                                body,
+                                target,
+                                crossing.julian_day_ut,
+                                0.0,
+                            )?);
+                        }
+                    }
+                    if i > 0
+                        && near
+                        && d[i - 1].abs() < 90.0
+                        && d[i - 1] * d[i] > 0.0
+                        && d[i] * d[i + 1] > 0.0
+                        && d[i].abs() <= d[i - 1].abs()
+                        && d[i].abs() < d[i + 1].abs()
+                    {
+                        let (jd, orb) = closest_approach(
+                            body,
+                            target.longitude,
+                            samples[i - 1].0,
+                            jd1,
+                            self.iflag,
+                        )?;
+                        if orb < STATION_TOLERANCE
+                            && jd >= jd_start
+                            && jd < jd_end
+                        {
+                            events.push(self.event(body, target, jd, orb)?);
+                        }
+                    }
+                }
+            }
+        }
+        events.sort_by(|a, b| a.julian_day_ut.total_cmp(&b.julian_day_ut));
+        Ok(events)
+    }
+
+    fn event(
+        &self,
+        body: Bodies,
+        target: &Target,
+        jd: f64,
+        orb: f64,
+    ) -> Result<TransitEvent, SweError> {
+        let result = calc_ut(jd, body, self.iflag)?;
+        let max_orb = target.aspect.angle().1 as f64;
+        Ok(TransitEvent {
+            julian_day_ut: jd,
+            transiting: body,
+            natal: target.natal,
+            aspect: target.aspect,
+            motion: ObjectPos::from_speed(result.speed_longitude),
+            longitude: result.longitude,
+            speed_longitude: result.speed_longitude,
+            orb,
+            applying_since: orb_boundary(
+                body,
+                target.longitude,
+                jd,
+                max_orb,
+                true,
+                self.iflag,
+            )?,
+            separating_until: orb_boundary(
+                body,
+                target.longitude,
+                jd,
+                max_orb,
+                false,
+                self.iflag,
+            )?,
+        })
+    }
+}
+
+/// Distance from `target` to `longitude`, normalized to -180..180
+fn signed_distance(longitude: f64, target: f64) -> f64 {
+    let d = degnorm(longitude - target);
+    if d > 180.0 {
+        d - 360.0
+    } else {
+        d
+    }
+}
+
+/// Time and distance of the closest approach to the target between jd0 and
+/// jd1 (ternary search, the distance has a single minimum there)
+fn closest_approach(
+    body: Bodies,
+    target: f64,
+    mut jd0: f64,
+    mut jd1: f64,
+    iflag: CalculationFlags,
+) -> Result<(f64, f64), SweError> {
+    while jd1 - jd0 > 1e-6 {
+        let m0 = jd0 + (jd1 - jd0) / 3.0;
+        let m1 = jd1 - (jd1 - jd0) / 3.0;
+        let d0 = distance(body, target, m0, iflag)?.abs();
+        let d1 = distance(body, target, m1, iflag)?.abs();
+        if d0 < d1 {
+            jd1 = m1;
+        } else {
+            jd0 = m0;
+        }
+    }
+    let jd = (jd0 + jd1) / 2.0;
+    Ok((jd, distance(body, target, jd, iflag)?.abs()))
+}
+
+/// Last time before `jd` (or first time after it) the body is `orb` degrees
+/// away from the target
+fn orb_boundary(
+    body: Bodies,
+    target: f64,
+    jd: f64,
+    orb: f64,
+    backward: bool,
+    iflag: CalculationFlags,
+) -> Result<Option<f64>, SweError> {
+    let step = if backward {
+        -search_step(body)
+    } else {
+        search_step(body)
+    };
+    let mut jd0 = jd;
+    while (jd0 - jd).abs() < MAX_WINDOW_DAYS {
+        let jd1 = jd0 + step;
+        if distance(body, target, jd1, iflag)?.abs() > orb {
+            // bisection on |distance| - orb
+            let (mut inside, mut outside) = (jd0, jd1);
+            while (outside - inside).abs() > 1e-6 {
+                let mid = (inside + outside) / 2.0;
+                if distance(body, target, mid, iflag)?.abs() > orb {
+                    outside = mid;
+                } else {
+                    inside = mid;
+                }
+            }
+            return Ok(Some(outside));
+        }
+        jd0 = jd1;
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ensure_ephemeris_initialized,
+        sweconst::Calendar,
+        swerust::handler_swe08::{utc_time_zone, utc_to_jd},
+    };
+
+    fn jd(year: i32, month: i32, day: i32) -> f64 {
+        let utc = utc_time_zone(year, month, day, 0, 0, 0.0, 0.0);
+        utc_to_jd(
+            utc.year[0],
+            utc.month[0],
+            utc.day[0],
+            utc.hour[0],
+            utc.min[0],
+            utc.sec[0],
+            Calendar::Gregorian,
+        )
+        .unwrap()
+        .julian_day_ut
+    }
+
+    fn natal_sun(longitude: f64) -> NatalPoint {
+        NatalPoint::Body {
+            body: Bodies::Sun,
+            longitude,
+        }
+    }
+
+    #[test]
+    fn test_mars_triple_pass() {
+        let _ = ensure_ephemeris_initialized();
+        let events: Vec<TransitEvent> = transits(
+            &[Bodies::Mars],
+            &[natal_sun(120.0)],
+            AspectsFilter::Conjunction,
+            jd(2024, 10, 1),
+            jd(2025, 6, 1),
+            CalculationFlags::SWISS_EPHEMERIS,
+        )
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+        assert_eq!(events.len(), 3);
+        let motions: Vec<ObjectPos> = events.iter().map(|e| e.motion).collect();
+        assert_eq!(
+            motions,
+            vec![ObjectPos::Direct, ObjectPos::Retrograde, ObjectPos::Direct]
+        );
+        assert_eq!(events[1].julian_day_ut, 2460681.94729815);
+        // The first two passes share one stay within the 10° orb; Mars
+        // then retrogrades to 17° Cancer, out of the 10° orb, before its
+        // third pass
+        let first = &events[0];
+        let second = &events[1];
+        let third = &events[2];
+        assert!(first.applying_since.unwrap() < first.julian_day_ut);
+        assert!(
+            (second.applying_since.unwrap() - first.applying_since.unwrap())
+                .abs()
+                < 1e-5
+        );
+        assert!(
+            (second.separating_until.unwrap()
+                - first.separating_until.unwrap())
+            .abs()
+                < 1e-5
+        );
+        assert!(
+            second.separating_until.unwrap() < third.applying_since.unwrap()
+        );
+        assert!(third.applying_since.unwrap() < third.julian_day_ut);
+        assert!(third.separating_until.unwrap() > third.julian_day_ut);
+        for event in &events {
+            assert_eq!(event.aspect, Aspects::Conjunction);
+        }
+    }
+
+    #[test]
+    fn test_station_on_natal_point() {
+        let _ = ensure_ephemeris_initialized();
+        let flags = CalculationFlags::SPEED_PRECISION;
+        // Mars turns retrograde at the beginning of December 2024
+        let (mut jd0, mut jd1) = (jd(2024, 12, 1), jd(2024, 12, 12));
+        while jd1 - jd0 > 1e-6 {
+            let mid = (jd0 + jd1) / 2.0;
+            if calc_ut(mid, Bodies::Mars, flags).unwrap().speed_longitude > 0.0
+            {
+                jd0 = mid;
+            } else {
+                jd1 = mid;
+            }
+        }
+        let station = calc_ut(jd0, Bodies::Mars, flags).unwrap().longitude;
+
+        let events: Vec<TransitEvent> = transits(
+            &[Bodies::Mars],
+            &[natal_sun(station)],
+            AspectsFilter::Conjunction,
+            jd(2024, 11, 15),
+            jd(2024, 12, 31),
+            CalculationFlags::SWISS_EPHEMERIS,
+        )
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].motion, ObjectPos::Stationary);
+        assert!((events[0].julian_day_ut - jd0).abs() < 1.0);
+        assert!(events[0].orb < 1e-6);
+    }
+
+    #[test]
+    fn test_sun_major_aspects_over_a_year() {
+        let _ = ensure_ephemeris_initialized();
+        let events: Vec<TransitEvent> = transits(
+            &[Bodies::Sun],
+            &[natal_sun(15.0)],
+            AspectsFilter::AllMajorsAspects,
+            jd(2025, 1, 1),
+            jd(2026, 1, 1),
+            CalculationFlags::SWISS_EPHEMERIS,
+        )
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+        // conjunction, opposition and two each of trine, square and sextile
+        assert_eq!(events.len(), 8);
+        for pair in events.windows(2) {
+            assert!(pair[0].julian_day_ut < pair[1].julian_day_ut);
+        }
+        let conjunction = events
+            .iter()
+            .find(|e| e.aspect == Aspects::Conjunction)
+            .unwrap();
+        assert!((conjunction.longitude - 15.0).abs() < 1e-6);
+        assert_eq!(conjunction.motion, ObjectPos::Direct);
+    }
+
+    #[test]
+    fn test_no_aspects() {
+        let _ = ensure_ephemeris_initialized();
+        let mut events = transits(
+            &[Bodies::Moon, Bodies::Sun],
+            &[natal_sun(15.0)],
+            AspectsFilter::NoAspects,
+            jd(2025, 1, 1),
+            jd(2025, 3, 1),
+            CalculationFlags::SWISS_EPHEMERIS,
+        );
+        assert!(events.next().is_none());
+    }
+}